@@ -0,0 +1,113 @@
+//! Functions for cutting the path of a `TransitEdge`.
+use geo::{Coord, CoordFloat, EuclideanLength, Line, LineString};
+use std::iter::Sum;
+
+/// Splits a `LineString` at the given fraction of its Euclidean length.
+///
+/// The coordinate at the split point is the last coordinate of the first part and the first coordinate of the second part.
+/// Fractions are clamped to the range `[0, 1]`.
+///
+/// # Arguments
+///
+/// * `path` - The `LineString` to be split.
+/// * `fraction` - The fraction of the length at which to split.
+///
+/// # Returns
+///
+/// * `(LineString<T>, LineString<T>)` - The parts before and after the split point.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, LineString};
+/// use transit_grid::algorithms::edge_split::split_path;
+///
+/// let path = LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]);
+/// let (first, second) = split_path(&path, 0.25);
+/// assert_eq!(first, LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 0.5, y: 0.0 }]));
+/// assert_eq!(second, LineString(vec![coord! { x: 0.5, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]));
+/// ```
+pub fn split_path<T: CoordFloat + Sum>(
+    path: &LineString<T>,
+    fraction: T,
+) -> (LineString<T>, LineString<T>) {
+    let fraction = fraction.max(T::zero()).min(T::one());
+    let target = path.euclidean_length() * fraction;
+
+    let mut first: Vec<Coord<T>> = path.0.iter().take(1).copied().collect();
+    let mut travelled = T::zero();
+    for (i, line) in path.lines().enumerate() {
+        let length = line.euclidean_length();
+        if travelled + length >= target && length > T::zero() {
+            let split = point_on_line(&line, (target - travelled) / length);
+            if split != line.start {
+                first.push(split);
+            }
+            let mut second = vec![split];
+            if split != line.end {
+                second.push(line.end);
+            }
+            second.extend(path.0.iter().skip(i + 2));
+            return (LineString(first), LineString(second));
+        }
+        travelled = travelled + length;
+        first.push(line.end);
+    }
+
+    let last: Vec<Coord<T>> = path.0.last().copied().into_iter().collect();
+    (path.clone(), LineString(last))
+}
+
+fn point_on_line<T: CoordFloat>(line: &Line<T>, fraction: T) -> Coord<T> {
+    line.start + line.delta() * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::coord;
+
+    #[test]
+    fn test_split_path_between_coordinates() {
+        let path = LineString(vec![
+            coord! { x: 0.0, y: 0.0 },
+            coord! { x: 1.0, y: 0.0 },
+            coord! { x: 1.0, y: 1.0 },
+        ]);
+
+        let (first, second) = split_path(&path, 0.75);
+
+        assert_eq!(
+            first,
+            LineString(vec![
+                coord! { x: 0.0, y: 0.0 },
+                coord! { x: 1.0, y: 0.0 },
+                coord! { x: 1.0, y: 0.5 }
+            ])
+        );
+        assert_eq!(
+            second,
+            LineString(vec![coord! { x: 1.0, y: 0.5 }, coord! { x: 1.0, y: 1.0 }])
+        );
+    }
+
+    #[test]
+    fn test_split_path_at_coordinate() {
+        let path = LineString(vec![
+            coord! { x: 0.0, y: 0.0 },
+            coord! { x: 1.0, y: 0.0 },
+            coord! { x: 2.0, y: 0.0 },
+        ]);
+
+        let (first, second) = split_path(&path, 0.5);
+
+        assert_eq!(
+            first,
+            LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }])
+        );
+        assert_eq!(
+            second,
+            LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }])
+        );
+    }
+}
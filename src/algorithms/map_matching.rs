@@ -0,0 +1,575 @@
+//! Map matching of GPS traces onto a `TransitNetwork`.
+//!
+//! The matcher follows the hidden Markov model approach of Newson & Krumm (2009) and decodes the most likely
+//! sequence of network positions with the Viterbi algorithm. The hidden states are directed traversals of
+//! `TransitEdge`s, i.e. edges of the `TopologyGraph`, so transitions between two samples are only possible along
+//! movements the topology permits and a matched route never takes an impossible switch turn.
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    iter::Sum,
+    ops::AddAssign,
+};
+
+use geo::{Coord, CoordNum, EuclideanDistance, GeoFloat, LineLocatePoint, Point};
+use num_traits::FromPrimitive;
+use petgraph::{
+    algo::astar,
+    stable_graph::{EdgeIndex, NodeIndex},
+    visit::{EdgeRef, IntoEdgeReferences},
+};
+
+use crate::{
+    core::{EdgeId, NetworkPosition},
+    graphs::TransitNetwork,
+};
+
+/// A single sample of a GPS trace.
+///
+/// # Fields
+///
+/// * `time: f64` - The timestamp of the sample in seconds.
+/// * `location: Coord<T>` - The measured location, in the same coordinate system as the network's edge paths.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GpsSample<T: CoordNum> {
+    /// The timestamp of the sample in seconds.
+    pub time: f64,
+    /// The measured location of the sample.
+    pub location: Coord<T>,
+}
+
+/// Parameters of the hidden Markov model used by `MapMatching`.
+///
+/// Distances are measured in the unit of the network's coordinates, speeds in that unit per second.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MapMatchingConfig<T> {
+    /// Edges farther than this distance from a sample are not considered as candidates for it.
+    pub search_radius: T,
+    /// Standard deviation of the GPS measurement noise.
+    pub gps_sigma: T,
+    /// Scale of the exponential distribution comparing route distance with straight-line distance.
+    pub beta: T,
+    /// Transitions requiring a higher speed than this are discarded.
+    pub max_speed: Option<T>,
+}
+
+/// The result of matching a GPS trace onto a network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedRoute<T> {
+    /// The sequence of traversed edges.
+    pub edges: Vec<EdgeId>,
+    /// The matched position of each sample, or `None` if no edge was within the search radius.
+    pub positions: Vec<Option<NetworkPosition<T>>>,
+    /// Indices of the samples at which the model had to restart because no permitted movement
+    /// connects them to the previous matched sample.
+    pub breaks: Vec<usize>,
+}
+
+/// `MapMatching` trait provides functionality to snap a GPS trace onto the edges of a network.
+pub trait MapMatching<T: CoordNum> {
+    /// Matches a sequence of GPS samples onto the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The samples of the trace, ordered by time.
+    /// * `config` - The parameters of the hidden Markov model.
+    ///
+    /// # Returns
+    ///
+    /// * `MatchedRoute<T>` - The traversed edges and the matched position of each sample.
+    fn match_trace(
+        &self,
+        samples: &[GpsSample<T>],
+        config: &MapMatchingConfig<T>,
+    ) -> MatchedRoute<T>;
+}
+
+/// A directed traversal of an edge that a sample may have been taken on.
+#[derive(Debug, Clone, Copy)]
+struct Candidate<T> {
+    topo_edge: EdgeIndex,
+    edge_id: EdgeId,
+    offset: T,
+    travel_offset: f64,
+    length: f64,
+    emission: f64,
+}
+
+/// An entry of the priority queue of `route_distances`, ordered by increasing distance.
+#[derive(Debug, PartialEq)]
+struct QueueEntry {
+    distance: f64,
+    toponode: NodeIndex,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.toponode.cmp(&self.toponode))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R: Copy, T> TransitNetwork<R, T>
+where
+    T: GeoFloat + AddAssign + FromPrimitive + Sum,
+{
+    fn find_candidates(
+        &self,
+        sample: &GpsSample<T>,
+        config: &MapMatchingConfig<T>,
+        topo_edges: &HashMap<EdgeId, Vec<EdgeIndex>>,
+    ) -> Vec<Candidate<T>> {
+        let point = Point::from(sample.location);
+        let sigma = config.gps_sigma.to_f64().unwrap_or(1.0);
+        let mut candidates = Vec::new();
+
+        for edge in self.physical_graph.graph.edge_weights() {
+            if edge.path.0.is_empty() {
+                continue;
+            }
+            let distance = edge.path.euclidean_distance(&point);
+            if distance > config.search_radius {
+                continue;
+            }
+            let fraction = edge.path.line_locate_point(&point).unwrap_or_else(T::zero);
+            let offset = fraction * edge.length;
+            let length = edge.length.to_f64().unwrap_or(0.0);
+            let distance = distance.to_f64().unwrap_or(f64::INFINITY);
+
+            for &topo_edge in topo_edges.get(&edge.id).into_iter().flatten() {
+                let forward = self.topology_graph.graph[topo_edge].from == edge.source;
+                let offset_f64 = offset.to_f64().unwrap_or(0.0);
+                candidates.push(Candidate {
+                    topo_edge,
+                    edge_id: edge.id,
+                    offset,
+                    travel_offset: if forward {
+                        offset_f64
+                    } else {
+                        length - offset_f64
+                    },
+                    length,
+                    emission: -0.5 * (distance / sigma).powi(2),
+                });
+            }
+        }
+        candidates
+    }
+
+    fn edge_lengths(&self) -> HashMap<EdgeId, f64> {
+        self.physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, edge.length.to_f64().unwrap_or(0.0)))
+            .collect()
+    }
+
+    /// Returns the distances along the topology from the end of the given candidate's edge.
+    ///
+    /// The search stops once the distances to all `goals` are known or the distance exceeds `cutoff`,
+    /// so the distances of other `TopoNode`s may be missing.
+    fn route_distances(
+        &self,
+        from: &Candidate<T>,
+        lengths: &HashMap<EdgeId, f64>,
+        goals: &HashSet<NodeIndex>,
+        cutoff: f64,
+    ) -> HashMap<NodeIndex, f64> {
+        let graph = &self.topology_graph.graph;
+        let (_, start) = graph.edge_endpoints(from.topo_edge).unwrap();
+        let mut distances: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut tentative: HashMap<NodeIndex, f64> = HashMap::from([(start, 0.0)]);
+        let mut queue = BinaryHeap::from([QueueEntry {
+            distance: 0.0,
+            toponode: start,
+        }]);
+        let mut remaining = goals.len();
+        while let Some(QueueEntry { distance, toponode }) = queue.pop() {
+            if distance > cutoff || remaining == 0 {
+                break;
+            }
+            if distances.contains_key(&toponode) {
+                continue;
+            }
+            distances.insert(toponode, distance);
+            remaining -= usize::from(goals.contains(&toponode));
+            for edge in graph.edges(toponode) {
+                let next = distance + lengths[&edge.weight().edge_id];
+                if tentative
+                    .get(&edge.target())
+                    .is_none_or(|known| next < *known)
+                {
+                    tentative.insert(edge.target(), next);
+                    queue.push(QueueEntry {
+                        distance: next,
+                        toponode: edge.target(),
+                    });
+                }
+            }
+        }
+        distances
+    }
+
+    fn route_distance(
+        &self,
+        from: &Candidate<T>,
+        to: &Candidate<T>,
+        distances: &HashMap<NodeIndex, f64>,
+    ) -> Option<f64> {
+        if from.topo_edge == to.topo_edge && to.travel_offset >= from.travel_offset {
+            return Some(to.travel_offset - from.travel_offset);
+        }
+        let (start, _) = self.topology_graph.graph.edge_endpoints(to.topo_edge)?;
+        distances
+            .get(&start)
+            .map(|distance| (from.length - from.travel_offset) + distance + to.travel_offset)
+    }
+
+    /// Returns the edges traversed between the end of `from`'s edge and the start of `to`'s edge.
+    fn connecting_edges(
+        &self,
+        from: &Candidate<T>,
+        to: &Candidate<T>,
+        lengths: &HashMap<EdgeId, f64>,
+    ) -> Vec<EdgeId> {
+        let graph = &self.topology_graph.graph;
+        let (_, start) = graph.edge_endpoints(from.topo_edge).unwrap();
+        let (goal, _) = graph.edge_endpoints(to.topo_edge).unwrap();
+        astar(
+            graph,
+            start,
+            |node| node == goal,
+            |edge| lengths[&edge.weight().edge_id],
+            |_| 0.0,
+        )
+        .map(|(_, path)| {
+            path.windows(2)
+                .filter_map(|pair| graph.find_edge(pair[0], pair[1]))
+                .map(|edge| graph[edge].edge_id)
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+}
+
+fn argmax(scores: &[f64]) -> Option<usize> {
+    scores
+        .iter()
+        .enumerate()
+        .filter(|(_, score)| score.is_finite())
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+impl<R: Copy, T> MapMatching<T> for TransitNetwork<R, T>
+where
+    T: GeoFloat + AddAssign + FromPrimitive + Sum,
+{
+    fn match_trace(
+        &self,
+        samples: &[GpsSample<T>],
+        config: &MapMatchingConfig<T>,
+    ) -> MatchedRoute<T> {
+        let mut topo_edges: HashMap<EdgeId, Vec<EdgeIndex>> = HashMap::new();
+        for edge in self.topology_graph.graph.edge_references() {
+            topo_edges
+                .entry(edge.weight().edge_id)
+                .or_default()
+                .push(edge.id());
+        }
+        let lengths = self.edge_lengths();
+        let beta = config.beta.to_f64().unwrap_or(1.0);
+        let max_speed = config.max_speed.and_then(|speed| speed.to_f64());
+
+        let candidates: Vec<Vec<Candidate<T>>> = samples
+            .iter()
+            .map(|sample| self.find_candidates(sample, config, &topo_edges))
+            .collect();
+
+        // Viterbi forward pass. Back pointers refer to the previous sample that has candidates.
+        let mut scores: Vec<Vec<f64>> = Vec::with_capacity(samples.len());
+        let mut back: Vec<Vec<Option<usize>>> = Vec::with_capacity(samples.len());
+        let mut breaks = Vec::new();
+        let mut previous: Option<usize> = None;
+
+        for (t, step) in candidates.iter().enumerate() {
+            let emissions: Vec<f64> = step.iter().map(|c| c.emission).collect();
+            if step.is_empty() {
+                scores.push(emissions);
+                back.push(vec![]);
+                continue;
+            }
+
+            let mut step_scores = vec![f64::NEG_INFINITY; step.len()];
+            let mut step_back = vec![None; step.len()];
+
+            if let Some(p) = previous {
+                let straight = samples[p]
+                    .location
+                    .euclidean_distance(&samples[t].location)
+                    .to_f64()
+                    .unwrap_or(0.0);
+                let elapsed = samples[t].time - samples[p].time;
+                // Routes longer than this would need more than the maximum speed.
+                let cutoff = match max_speed {
+                    Some(max_speed) if elapsed > 0.0 => max_speed * elapsed,
+                    _ => f64::INFINITY,
+                };
+                let goals: HashSet<NodeIndex> = step
+                    .iter()
+                    .filter_map(|to| self.topology_graph.graph.edge_endpoints(to.topo_edge))
+                    .map(|(start, _)| start)
+                    .collect();
+
+                for (i, from) in candidates[p].iter().enumerate() {
+                    if !scores[p][i].is_finite() {
+                        continue;
+                    }
+                    let distances = self.route_distances(from, &lengths, &goals, cutoff);
+                    for (j, to) in step.iter().enumerate() {
+                        let Some(route) = self.route_distance(from, to, &distances) else {
+                            continue;
+                        };
+                        if let Some(max_speed) = max_speed {
+                            if elapsed > 0.0 && route / elapsed > max_speed {
+                                continue;
+                            }
+                        }
+                        let score = scores[p][i] - (route - straight).abs() / beta + to.emission;
+                        if score > step_scores[j] {
+                            step_scores[j] = score;
+                            step_back[j] = Some(i);
+                        }
+                    }
+                }
+
+                if step_scores.iter().all(|score| !score.is_finite()) {
+                    breaks.push(t);
+                    step_scores = emissions;
+                }
+            } else {
+                step_scores = emissions;
+            }
+
+            scores.push(step_scores);
+            back.push(step_back);
+            previous = Some(t);
+        }
+
+        // Backtracking, restarting at the best candidate after every break.
+        let mut chosen: Vec<Option<usize>> = vec![None; samples.len()];
+        let mut next: Option<usize> = None;
+        for t in (0..samples.len()).rev() {
+            if candidates[t].is_empty() {
+                continue;
+            }
+            let index = next.or_else(|| argmax(&scores[t]));
+            chosen[t] = index;
+            next = index.and_then(|i| back[t][i]);
+        }
+
+        let mut edges: Vec<EdgeId> = Vec::new();
+        let mut last: Option<&Candidate<T>> = None;
+        for (t, index) in chosen.iter().enumerate() {
+            let Some(current) = index.map(|i| &candidates[t][i]) else {
+                continue;
+            };
+            match last {
+                Some(previous)
+                    if previous.topo_edge == current.topo_edge
+                        && current.travel_offset >= previous.travel_offset => {}
+                Some(previous) if !breaks.contains(&t) => {
+                    edges.extend(self.connecting_edges(previous, current, &lengths));
+                    edges.push(current.edge_id);
+                }
+                _ => edges.push(current.edge_id),
+            }
+            last = Some(current);
+        }
+        edges.dedup();
+
+        let positions = chosen
+            .iter()
+            .enumerate()
+            .map(|(t, index)| {
+                index.map(|i| NetworkPosition {
+                    edge_id: candidates[t][i].edge_id,
+                    offset: candidates[t][i].offset,
+                })
+            })
+            .collect();
+
+        MatchedRoute {
+            edges,
+            positions,
+            breaks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Accessability, TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::{coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+
+        network.add_node(TransitNode {
+            id: 0,
+            location: coord! {x: 0.0, y: 0.0},
+        });
+        network.add_node(TransitNode {
+            id: 1,
+            location: coord! {x: 1.0, y: 0.0},
+        });
+        network.add_node(TransitNode {
+            id: 2,
+            location: coord! {x: 2.0, y: 0.0},
+        });
+        network.add_node(TransitNode {
+            id: 3,
+            location: coord! {x: 2.0, y: 0.3},
+        });
+
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 0,
+            target: 1,
+            length: 1.0,
+            path: LineString(vec![coord! {x: 0.0, y: 0.0}, coord! {x: 1.0, y: 0.0}]),
+        });
+        network.add_edge_with_accessibility(
+            TransitEdge {
+                id: 2,
+                source: 1,
+                target: 2,
+                length: 1.0,
+                path: LineString(vec![coord! {x: 1.0, y: 0.0}, coord! {x: 2.0, y: 0.0}]),
+            },
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            TransitEdge {
+                id: 3,
+                source: 1,
+                target: 3,
+                length: 1.0,
+                path: LineString(vec![coord! {x: 1.0, y: 0.0}, coord! {x: 2.0, y: 0.3}]),
+            },
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network
+    }
+
+    fn create_trace(points: &[(f64, f64)]) -> Vec<GpsSample<f64>> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| GpsSample {
+                time: i as f64,
+                location: coord! {x: *x, y: *y},
+            })
+            .collect()
+    }
+
+    fn create_config() -> MapMatchingConfig<f64> {
+        MapMatchingConfig {
+            search_radius: 0.3,
+            gps_sigma: 0.05,
+            beta: 0.5,
+            max_speed: None,
+        }
+    }
+
+    #[test]
+    fn test_route_distances_bounded() {
+        let network = create_network();
+        let graph = &network.topology_graph.graph;
+        let topo_edge = |edge_id: EdgeId, from: u64| {
+            graph
+                .edge_references()
+                .find(|edge| edge.weight().edge_id == edge_id && edge.weight().from == from)
+                .unwrap()
+        };
+        let from = Candidate {
+            topo_edge: topo_edge(1, 0).id(),
+            edge_id: 1,
+            offset: 0.5,
+            travel_offset: 0.5,
+            length: 1.0,
+            emission: 0.0,
+        };
+        let lengths = network.edge_lengths();
+        let start = topo_edge(1, 0).target();
+        let far = topo_edge(2, 1).target();
+
+        let distances = network.route_distances(&from, &lengths, &HashSet::from([far]), 10.0);
+        assert_eq!(distances[&far], 1.0);
+        let distances = network.route_distances(&from, &lengths, &HashSet::from([far]), 0.5);
+        assert_eq!(distances, HashMap::from([(start, 0.0)]));
+        let distances =
+            network.route_distances(&from, &lengths, &HashSet::from([start]), f64::INFINITY);
+        assert_eq!(distances, HashMap::from([(start, 0.0)]));
+    }
+
+    #[test]
+    fn test_match_straight_trace() {
+        let network = create_network();
+        let trace = create_trace(&[(0.1, 0.05), (0.6, -0.05), (1.4, 0.02), (1.9, 0.0)]);
+
+        let matched = network.match_trace(&trace, &create_config());
+
+        assert_eq!(matched.edges, vec![1, 2]);
+        assert!(matched.breaks.is_empty());
+        let position = matched.positions[2].unwrap();
+        assert_eq!(position.edge_id, 2);
+        assert!((position.offset - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_match_diverging_trace() {
+        let network = create_network();
+        let trace = create_trace(&[(0.1, 0.0), (0.6, 0.0), (1.5, 0.15), (1.9, 0.27)]);
+
+        let matched = network.match_trace(&trace, &create_config());
+
+        assert_eq!(matched.edges, vec![1, 3]);
+        assert!(matched.breaks.is_empty());
+    }
+
+    #[test]
+    fn test_match_impossible_turn() {
+        let network = create_network();
+        let trace = create_trace(&[(1.8, 0.0), (1.2, 0.0), (1.6, 0.18), (1.9, 0.27)]);
+
+        let matched = network.match_trace(&trace, &create_config());
+
+        assert_eq!(matched.breaks, vec![2]);
+    }
+
+    #[test]
+    fn test_match_without_candidates() {
+        let network = create_network();
+        let trace = create_trace(&[(0.5, 0.0), (5.0, 5.0), (0.8, 0.0)]);
+
+        let matched = network.match_trace(&trace, &create_config());
+
+        assert_eq!(matched.edges, vec![1]);
+        assert_eq!(matched.positions[1], None);
+    }
+}
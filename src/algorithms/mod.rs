@@ -9,6 +9,12 @@
 //!
 //! The module also provides implementations of these traits for `TransitNetwork` struct.
 //! It uses A* algorithm from `petgraph` crate for shortest path computation.
+//!
+//! The `map_matching` submodule snaps GPS traces onto the network, restricted to the movements
+//! permitted by the topology graph.
+//!
+//! The `raptor` submodule plans passenger journeys over the services of a timetable,
+//! trading arrival time against the number of transfers.
 
 use std::{cmp::Ordering, f64::INFINITY};
 
@@ -21,6 +27,9 @@ use crate::{
 };
 
 pub mod edge_length;
+pub mod edge_split;
+pub mod map_matching;
+pub mod raptor;
 
 /// `ShortestPath` trait provides functionality to compute shortest path in a network.
 ///
@@ -0,0 +1,781 @@
+//! Earliest-arrival journey planning over the services of a `Timetable` with the RAPTOR algorithm
+//! (Delling, Pajor & Werneck, 2012).
+//!
+//! Services calling at the same sequence of nodes are grouped into routes, which are scanned once per round.
+//! Round `k` finds the earliest arrivals with `k` rides, so the rounds yield the Pareto set of journeys
+//! trading arrival time against the number of transfers. Between rides, passengers may walk to nearby nodes.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    iter::Sum,
+};
+
+use geo::{Coord, CoordFloat, LineString};
+use num_traits::FromPrimitive;
+
+use crate::{
+    core::{NodeId, ServiceId, Timetable},
+    graphs::TransitNetwork,
+};
+
+/// Mean radius of the earth in meters, as used by the haversine formula.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Options for `Raptor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaptorOptions {
+    /// The maximum number of transfers of a journey.
+    pub max_transfers: usize,
+    /// The minimum time in seconds to change from one service to another at the same node.
+    pub change_time: f64,
+    /// The walking speed of passengers, in the units of `TransitNetwork::path_length` per second.
+    pub walking_speed: f64,
+    /// The maximum walking distance between two nodes, in the units of `TransitNetwork::path_length`.
+    pub max_walking_distance: f64,
+}
+
+impl Default for RaptorOptions {
+    fn default() -> Self {
+        RaptorOptions {
+            max_transfers: 5,
+            change_time: 0.0,
+            walking_speed: 1.4,
+            max_walking_distance: 400.0,
+        }
+    }
+}
+
+/// A walking connection between two nearby nodes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transfer {
+    /// The ID of the node the walk starts at.
+    pub from: NodeId,
+    /// The ID of the node the walk ends at.
+    pub to: NodeId,
+    /// The walking time in seconds.
+    pub duration: f64,
+}
+
+/// Enum `Leg` defining a part of a `Journey`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Leg {
+    /// A ride with a service from one of its stops to a later one.
+    Ride {
+        /// The ID of the service.
+        service_id: ServiceId,
+        /// The ID of the node of boarding.
+        from: NodeId,
+        /// The ID of the node of alighting.
+        to: NodeId,
+        /// The departure time in seconds.
+        departure: f64,
+        /// The arrival time in seconds.
+        arrival: f64,
+    },
+    /// A walk between two nearby nodes.
+    Walk {
+        /// The ID of the node the walk starts at.
+        from: NodeId,
+        /// The ID of the node the walk ends at.
+        to: NodeId,
+        /// The departure time in seconds.
+        departure: f64,
+        /// The arrival time in seconds.
+        arrival: f64,
+    },
+}
+
+/// A passenger journey from an origin to a destination.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Journey {
+    /// The time in seconds the journey starts at.
+    pub departure: f64,
+    /// The time in seconds the journey reaches the destination.
+    pub arrival: f64,
+    /// The number of changes between services.
+    pub transfers: usize,
+    /// The rides and walks of the journey in order.
+    pub legs: Vec<Leg>,
+}
+
+#[derive(Debug, Clone)]
+struct Trip {
+    service_id: ServiceId,
+    arrivals: Vec<f64>,
+    departures: Vec<f64>,
+}
+
+/// Services with the same sequence of stops, none of which overtakes another.
+#[derive(Debug, Clone)]
+struct Route {
+    stops: Vec<usize>,
+    /// Trips sorted by departure.
+    trips: Vec<Trip>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Label {
+    Origin,
+    Ride {
+        route: usize,
+        trip: usize,
+        board: usize,
+    },
+    Walk {
+        from: usize,
+    },
+}
+
+/// Earliest-arrival journey planner over the services of a timetable.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord};
+/// use transit_grid::algorithms::raptor::{Raptor, RaptorOptions};
+/// use transit_grid::prelude::*;
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// for (id, x) in [(1, 0.0), (2, 1000.0), (3, 2000.0)] {
+///     network.add_node(TransitNode { id, location: coord! { x: x, y: 0.0 } });
+/// }
+/// let stop = |node_id, time| Stop { node_id, arrival: time, departure: time };
+/// let mut timetable = Timetable::new();
+/// timetable.add_service(Service::new(1, vec![stop(1, 0.0), stop(2, 60.0)]));
+/// timetable.add_service(Service::new(2, vec![stop(2, 120.0), stop(3, 180.0)]));
+///
+/// let raptor = Raptor::new(&network, &timetable, &RaptorOptions::default());
+/// let journeys = raptor.earliest_arrival(1, 3, 0.0);
+/// assert_eq!(journeys.len(), 1);
+/// assert_eq!(journeys[0].arrival, 180.0);
+/// assert_eq!(journeys[0].transfers, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Raptor {
+    options: RaptorOptions,
+    stops: Vec<NodeId>,
+    stop_indices: HashMap<NodeId, usize>,
+    routes: Vec<Route>,
+    /// The routes serving each stop with the position of the stop in the route.
+    stop_routes: Vec<Vec<(usize, usize)>>,
+    transfers: Vec<Vec<(usize, f64)>>,
+}
+
+impl Raptor {
+    /// Prepares the routes of a timetable and the walking transfers between its stops.
+    ///
+    /// Walking transfers connect stops at nodes whose locations are at most
+    /// `RaptorOptions::max_walking_distance` apart, measured with `TransitNetwork::path_length`.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network providing the locations of the nodes.
+    /// * `timetable` - The services passengers can ride. Services with fewer than two stops are ignored.
+    /// * `options` - The options of the journey planner.
+    ///
+    /// # Returns
+    ///
+    /// * `Raptor` - The journey planner.
+    pub fn new<R, T>(
+        network: &TransitNetwork<R, T>,
+        timetable: &Timetable,
+        options: &RaptorOptions,
+    ) -> Self
+    where
+        R: Copy + Into<Coord<T>> + From<Coord<T>>,
+        T: CoordFloat + FromPrimitive + Sum,
+    {
+        let mut stops = Vec::new();
+        let mut stop_indices = HashMap::new();
+        let mut patterns: BTreeMap<Vec<usize>, Vec<Trip>> = BTreeMap::new();
+        for service in timetable
+            .services
+            .iter()
+            .filter(|service| service.stops.len() > 1)
+        {
+            let pattern = service
+                .stops
+                .iter()
+                .map(|stop| {
+                    *stop_indices.entry(stop.node_id).or_insert_with(|| {
+                        stops.push(stop.node_id);
+                        stops.len() - 1
+                    })
+                })
+                .collect();
+            patterns.entry(pattern).or_default().push(Trip {
+                service_id: service.id,
+                arrivals: service.stops.iter().map(|stop| stop.arrival).collect(),
+                departures: service.stops.iter().map(|stop| stop.departure).collect(),
+            });
+        }
+
+        let mut routes: Vec<Route> = Vec::new();
+        for (pattern, mut trips) in patterns {
+            trips.sort_by(|a, b| a.departures[0].total_cmp(&b.departures[0]));
+            let first_route = routes.len();
+            for trip in trips {
+                // A trip overtaking the last trip of a route goes into another route of the same pattern.
+                let route = routes[first_route..].iter_mut().find(|route| {
+                    let last = &route.trips[route.trips.len() - 1];
+                    (0..pattern.len()).all(|i| {
+                        last.arrivals[i] <= trip.arrivals[i]
+                            && last.departures[i] <= trip.departures[i]
+                    })
+                });
+                match route {
+                    Some(route) => route.trips.push(trip),
+                    None => routes.push(Route {
+                        stops: pattern.clone(),
+                        trips: vec![trip],
+                    }),
+                }
+            }
+        }
+
+        let mut stop_routes = vec![Vec::new(); stops.len()];
+        for (route_index, route) in routes.iter().enumerate() {
+            for (position, stop) in route.stops.iter().enumerate() {
+                stop_routes[*stop].push((route_index, position));
+            }
+        }
+
+        let location = |node_id: NodeId| -> Option<Coord<T>> {
+            let index = network.physical_graph.id_to_index(node_id)?;
+            Some(network.physical_graph.graph[*index].location.into())
+        };
+        // Stops are bucketed into cells of the maximum walking distance, so that only the stops in neighbouring
+        // cells are measured. Geographic locations are placed on a sphere of the earth's radius in meters, where
+        // the chord between two locations is never longer than their haversine distance.
+        let geographic = network.crs.is_some_and(|crs| crs.is_geographic());
+        let cell_size = options.max_walking_distance.max(f64::MIN_POSITIVE);
+        let cell = |location: Coord<T>| -> Option<[i64; 3]> {
+            let (x, y) = (location.x.to_f64()?, location.y.to_f64()?);
+            let position = if geographic {
+                let (longitude, latitude) = (x.to_radians(), y.to_radians());
+                [
+                    EARTH_RADIUS * latitude.cos() * longitude.cos(),
+                    EARTH_RADIUS * latitude.cos() * longitude.sin(),
+                    EARTH_RADIUS * latitude.sin(),
+                ]
+            } else {
+                [x, y, 0.0]
+            };
+            Some(position.map(|value| (value / cell_size).floor() as i64))
+        };
+        let located: Vec<Option<(Coord<T>, [i64; 3])>> = stops
+            .iter()
+            .map(|node_id| {
+                let location = location(*node_id)?;
+                Some((location, cell(location)?))
+            })
+            .collect();
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (stop, (_, key)) in located
+            .iter()
+            .enumerate()
+            .filter_map(|(stop, located)| Some((stop, (*located)?)))
+        {
+            grid.entry(key).or_default().push(stop);
+        }
+
+        let mut transfers = vec![Vec::new(); stops.len()];
+        for (from, (from_location, [x, y, z])) in located
+            .iter()
+            .enumerate()
+            .filter_map(|(from, located)| Some((from, (*located)?)))
+        {
+            let mut nearby: Vec<usize> = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                .map(|(dx, dy, dz)| {
+                    [
+                        x.saturating_add(dx),
+                        y.saturating_add(dy),
+                        z.saturating_add(dz),
+                    ]
+                })
+                .filter_map(|key| grid.get(&key))
+                .flatten()
+                .copied()
+                .filter(|to| *to != from)
+                .collect();
+            nearby.sort_unstable();
+            nearby.dedup();
+            for to in nearby {
+                let Some((to_location, _)) = located[to] else {
+                    continue;
+                };
+                let distance = network
+                    .path_length(&LineString(vec![from_location, to_location]))
+                    .to_f64()
+                    .unwrap_or(f64::INFINITY);
+                if distance <= options.max_walking_distance {
+                    transfers[from].push((to, distance / options.walking_speed));
+                }
+            }
+        }
+
+        Raptor {
+            options: *options,
+            stops,
+            stop_indices,
+            routes,
+            stop_routes,
+            transfers,
+        }
+    }
+
+    /// Returns the walking transfers between the stops of the timetable.
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.transfers
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transfers)| {
+                transfers.iter().map(move |(to, duration)| Transfer {
+                    from: self.stops[from],
+                    to: self.stops[*to],
+                    duration: *duration,
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the journeys with the earliest arrival for each number of transfers.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The ID of the node the journey starts at.
+    /// * `to` - The ID of the destination node.
+    /// * `departure` - The earliest departure time in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Journey>` - The Pareto-optimal journeys sorted by increasing number of rides and decreasing
+    ///   arrival time. Each journey arrives strictly earlier than all journeys with fewer rides. A journey without
+    ///   rides walks to the destination, or has no legs if the destination is the origin.
+    ///   Empty if the destination cannot be reached or one of the nodes is not served by the timetable.
+    pub fn earliest_arrival(&self, from: NodeId, to: NodeId, departure: f64) -> Vec<Journey> {
+        let (Some(&origin), Some(&target)) =
+            (self.stop_indices.get(&from), self.stop_indices.get(&to))
+        else {
+            return vec![];
+        };
+        let count = self.stops.len();
+        let mut arrivals = vec![vec![f64::INFINITY; count]];
+        let mut labels = vec![vec![None; count]];
+        let mut best = vec![f64::INFINITY; count];
+        arrivals[0][origin] = departure;
+        labels[0][origin] = Some(Label::Origin);
+        best[origin] = departure;
+        let mut marked = BTreeSet::from([origin]);
+        self.walk(
+            origin,
+            &mut arrivals[0],
+            &mut labels[0],
+            &mut best,
+            target,
+            &mut marked,
+        );
+
+        // The destination is the origin or lies within walking distance of it.
+        let mut journeys: Vec<Journey> = Vec::new();
+        if labels[0][target].is_some() {
+            journeys.push(self.journey(&arrivals, &labels, 0, target, departure));
+        }
+        for round in 1..=self.options.max_transfers + 1 {
+            let previous = &arrivals[round - 1];
+            let mut current = previous.clone();
+            let mut round_labels = vec![None; count];
+
+            let mut queue: BTreeMap<usize, usize> = BTreeMap::new();
+            for stop in std::mem::take(&mut marked) {
+                for (route, position) in &self.stop_routes[stop] {
+                    let entry = queue.entry(*route).or_insert(*position);
+                    *entry = (*entry).min(*position);
+                }
+            }
+
+            for (route_index, start) in queue {
+                let route = &self.routes[route_index];
+                let mut boarded: Option<(usize, usize)> = None;
+                for position in start..route.stops.len() {
+                    let stop = route.stops[position];
+                    if let Some((trip, board)) = boarded {
+                        let arrival = route.trips[trip].arrivals[position];
+                        if arrival < best[stop].min(best[target]) {
+                            current[stop] = arrival;
+                            best[stop] = arrival;
+                            round_labels[stop] = Some(Label::Ride {
+                                route: route_index,
+                                trip,
+                                board,
+                            });
+                            marked.insert(stop);
+                        }
+                    }
+
+                    let changes = round > 1
+                        && !matches!(
+                            self.label(&labels, round - 1, stop),
+                            Some(Label::Walk { .. })
+                        );
+                    let ready = previous[stop]
+                        + if changes {
+                            self.options.change_time
+                        } else {
+                            0.0
+                        };
+                    if ready.is_infinite() {
+                        continue;
+                    }
+                    let catchable = route
+                        .trips
+                        .partition_point(|trip| trip.departures[position] < ready);
+                    if catchable < route.trips.len()
+                        && boarded.is_none_or(|(trip, _)| catchable < trip)
+                    {
+                        boarded = Some((catchable, position));
+                    }
+                }
+            }
+
+            for stop in marked.clone() {
+                self.walk(
+                    stop,
+                    &mut current,
+                    &mut round_labels,
+                    &mut best,
+                    target,
+                    &mut marked,
+                );
+            }
+
+            arrivals.push(current);
+            labels.push(round_labels);
+            if labels[round][target].is_some()
+                && journeys
+                    .last()
+                    .is_none_or(|journey| arrivals[round][target] < journey.arrival)
+            {
+                journeys.push(self.journey(&arrivals, &labels, round, target, departure));
+            }
+            if marked.is_empty() {
+                break;
+            }
+        }
+        journeys
+    }
+
+    /// Relaxes the walking transfers from a stop within the same round.
+    fn walk(
+        &self,
+        stop: usize,
+        arrivals: &mut [f64],
+        labels: &mut [Option<Label>],
+        best: &mut [f64],
+        target: usize,
+        marked: &mut BTreeSet<usize>,
+    ) {
+        for (to, duration) in &self.transfers[stop] {
+            let arrival = arrivals[stop] + duration;
+            if arrival < best[*to].min(best[target]) {
+                arrivals[*to] = arrival;
+                best[*to] = arrival;
+                labels[*to] = Some(Label::Walk { from: stop });
+                marked.insert(*to);
+            }
+        }
+    }
+
+    /// Returns the label of a stop in the latest round up to the given one that improved it.
+    fn label(&self, labels: &[Vec<Option<Label>>], round: usize, stop: usize) -> Option<Label> {
+        (0..=round).rev().find_map(|round| labels[round][stop])
+    }
+
+    fn journey(
+        &self,
+        arrivals: &[Vec<f64>],
+        labels: &[Vec<Option<Label>>],
+        round: usize,
+        target: usize,
+        departure: f64,
+    ) -> Journey {
+        let arrival = arrivals[round][target];
+        let mut legs = Vec::new();
+        let mut stop = target;
+        let mut round = round;
+        while let Some(found) = (0..=round)
+            .rev()
+            .find(|round| labels[*round][stop].is_some())
+        {
+            round = found;
+            match labels[round][stop] {
+                Some(Label::Walk { from }) => {
+                    legs.push(Leg::Walk {
+                        from: self.stops[from],
+                        to: self.stops[stop],
+                        departure: arrivals[round][from],
+                        arrival: arrivals[round][stop],
+                    });
+                    stop = from;
+                }
+                Some(Label::Ride { route, trip, board }) => {
+                    let route = &self.routes[route];
+                    let trip = &route.trips[trip];
+                    let alight = (board..route.stops.len())
+                        .find(|position| {
+                            route.stops[*position] == stop
+                                && trip.arrivals[*position] == arrivals[round][stop]
+                        })
+                        .unwrap_or(route.stops.len() - 1);
+                    legs.push(Leg::Ride {
+                        service_id: trip.service_id,
+                        from: self.stops[route.stops[board]],
+                        to: self.stops[stop],
+                        departure: trip.departures[board],
+                        arrival: trip.arrivals[alight],
+                    });
+                    stop = route.stops[board];
+                    round -= 1;
+                }
+                _ => break,
+            }
+        }
+        legs.reverse();
+
+        let rides = legs
+            .iter()
+            .filter(|leg| matches!(leg, Leg::Ride { .. }))
+            .count();
+        Journey {
+            departure: legs.first().map_or(departure, |leg| match leg {
+                Leg::Ride { departure, .. } | Leg::Walk { departure, .. } => *departure,
+            }),
+            arrival,
+            transfers: rides.saturating_sub(1),
+            legs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Crs, Service, Stop, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::coord;
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let locations = [
+            (0, 0.0, 0.0),
+            (1, 1000.0, 0.0),
+            (2, 2000.0, 0.0),
+            (3, 2000.0, 140.0),
+            (4, 3000.0, 140.0),
+        ];
+        for (id, x, y) in locations {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: y },
+            });
+        }
+        network
+    }
+
+    fn service(id: ServiceId, stops: &[(NodeId, f64)]) -> Service {
+        Service::new(
+            id,
+            stops
+                .iter()
+                .map(|(node_id, time)| Stop {
+                    node_id: *node_id,
+                    arrival: *time,
+                    departure: *time,
+                })
+                .collect(),
+        )
+    }
+
+    fn create_timetable() -> Timetable {
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(1, &[(0, 0.0), (1, 100.0), (2, 200.0)]));
+        timetable.add_service(service(2, &[(0, 300.0), (1, 400.0), (2, 500.0)]));
+        timetable.add_service(service(3, &[(3, 400.0), (4, 500.0)]));
+        timetable.add_service(service(4, &[(0, 0.0), (4, 900.0)]));
+        timetable
+    }
+
+    #[test]
+    fn test_transfers() {
+        let raptor = Raptor::new(
+            &create_network(),
+            &create_timetable(),
+            &RaptorOptions::default(),
+        );
+
+        let transfers = raptor.transfers();
+
+        assert_eq!(transfers.len(), 2);
+        assert!(transfers.contains(&Transfer {
+            from: 2,
+            to: 3,
+            duration: 100.0
+        }));
+    }
+
+    #[test]
+    fn test_transfers_geographic() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        network.crs = Some(Crs::Wgs84);
+        let locations = [
+            (0, 13.0, 52.0),
+            (1, 13.004, 52.0),
+            (2, 13.0, 52.003),
+            (3, 13.01, 52.0),
+        ];
+        for (id, x, y) in locations {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(1, &[(0, 0.0), (3, 100.0)]));
+        timetable.add_service(service(2, &[(1, 0.0), (2, 100.0)]));
+
+        let transfers = Raptor::new(&network, &timetable, &RaptorOptions::default()).transfers();
+
+        // Nodes 1 and 2 are 432 m apart, node 3 is 411 m from node 1.
+        let mut pairs: Vec<_> = transfers
+            .iter()
+            .map(|transfer| (transfer.from, transfer.to))
+            .collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 0), (2, 0)]);
+        let transfer = transfers
+            .iter()
+            .find(|transfer| transfer.from == 0 && transfer.to == 1);
+        assert!((transfer.unwrap().duration - 274.0 / 1.4).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_walking_journey() {
+        let mut network = create_network();
+        network.add_node(TransitNode {
+            id: 5,
+            location: coord! { x: 140.0, y: 0.0 },
+        });
+        let mut timetable = create_timetable();
+        timetable.add_service(service(5, &[(0, 10.0), (5, 1000.0)]));
+        let walk = Leg::Walk {
+            from: 0,
+            to: 5,
+            departure: 0.0,
+            arrival: 100.0,
+        };
+
+        let raptor = Raptor::new(&network, &timetable, &RaptorOptions::default());
+        let journeys = raptor.earliest_arrival(0, 5, 0.0);
+
+        assert_eq!(journeys.len(), 1);
+        assert_eq!(journeys[0].arrival, 100.0);
+        assert_eq!(journeys[0].legs, vec![walk]);
+
+        // A faster ride is kept besides the walk.
+        timetable.add_service(service(6, &[(0, 20.0), (5, 60.0)]));
+        let raptor = Raptor::new(&network, &timetable, &RaptorOptions::default());
+        let journeys = raptor.earliest_arrival(0, 5, 0.0);
+
+        assert_eq!(journeys.len(), 2);
+        assert_eq!(journeys[0].legs, vec![walk]);
+        assert_eq!(journeys[1].arrival, 60.0);
+        assert!(matches!(
+            journeys[1].legs[..],
+            [Leg::Ride { service_id: 6, .. }]
+        ));
+
+        let journeys = raptor.earliest_arrival(1, 1, 50.0);
+        assert_eq!(journeys.len(), 1);
+        assert_eq!(journeys[0].arrival, 50.0);
+        assert!(journeys[0].legs.is_empty());
+    }
+
+    #[test]
+    fn test_pareto_journeys() {
+        let raptor = Raptor::new(
+            &create_network(),
+            &create_timetable(),
+            &RaptorOptions::default(),
+        );
+
+        let journeys = raptor.earliest_arrival(0, 4, 0.0);
+
+        assert_eq!(journeys.len(), 2);
+        assert_eq!(journeys[0].arrival, 900.0);
+        assert_eq!(journeys[0].transfers, 0);
+        assert_eq!(journeys[1].arrival, 500.0);
+        assert_eq!(journeys[1].transfers, 1);
+        assert_eq!(
+            journeys[1].legs,
+            vec![
+                Leg::Ride {
+                    service_id: 1,
+                    from: 0,
+                    to: 2,
+                    departure: 0.0,
+                    arrival: 200.0
+                },
+                Leg::Walk {
+                    from: 2,
+                    to: 3,
+                    departure: 200.0,
+                    arrival: 300.0
+                },
+                Leg::Ride {
+                    service_id: 3,
+                    from: 3,
+                    to: 4,
+                    departure: 400.0,
+                    arrival: 500.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_earliest_catchable_trip() {
+        let raptor = Raptor::new(
+            &create_network(),
+            &create_timetable(),
+            &RaptorOptions::default(),
+        );
+
+        let journeys = raptor.earliest_arrival(0, 2, 50.0);
+
+        assert_eq!(journeys.len(), 1);
+        assert_eq!(journeys[0].departure, 300.0);
+        assert_eq!(journeys[0].arrival, 500.0);
+        assert!(raptor.earliest_arrival(0, 4, 50.0).is_empty());
+        assert!(raptor.earliest_arrival(0, 9, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_change_time() {
+        let mut timetable = create_timetable();
+        timetable.add_service(service(5, &[(1, 110.0), (4, 400.0)]));
+        let options = RaptorOptions {
+            change_time: 20.0,
+            ..Default::default()
+        };
+
+        let raptor = Raptor::new(&create_network(), &timetable, &options);
+        let journeys = raptor.earliest_arrival(0, 4, 0.0);
+        assert_eq!(journeys.last().unwrap().arrival, 500.0);
+
+        let raptor = Raptor::new(&create_network(), &timetable, &RaptorOptions::default());
+        let journeys = raptor.earliest_arrival(0, 4, 0.0);
+        assert_eq!(journeys.last().unwrap().arrival, 400.0);
+    }
+}
@@ -0,0 +1,487 @@
+//! Command-line tool for converting, inspecting and querying transit networks.
+//!
+//! Build it with `cargo run --features cli -- <command>`. Run `transit-grid help` for the list of commands.
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use geo::Coord;
+use petgraph::algo::connected_components;
+use transit_grid::{
+    algorithms::ShortestPathWithAccessability,
+    core::{Accessability, NodeId},
+    graphs::AuditOptions,
+    io::{
+        csv_wkt::{read_csv, write_csv, CsvOptions},
+        graph_export::{ExportOptions, GraphExport},
+        gtfs::read_gtfs,
+        railml::{read_railml, write_railml},
+        snapshot::{write_snapshot, Snapshot},
+        svg::{Overlay, SvgOptions, SvgRender},
+    },
+    prelude::TransitNetwork,
+};
+
+type Network = TransitNetwork<Coord<f64>, f64>;
+
+/// Converts, inspects and queries transit networks.
+#[derive(Parser)]
+#[command(name = "transit-grid", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Converts a network into another format.
+    Import {
+        #[command(flatten)]
+        input: Input,
+        /// The file or directory to write.
+        output: PathBuf,
+        /// The output format, detected from the extension if omitted.
+        #[arg(long)]
+        to: Option<OutputFormat>,
+    },
+    /// Prints node and edge counts, connected components, lengths and data quality statistics.
+    Stats {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Audits the edges and checks the consistency of the physical and the topology graph.
+    Validate {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Prints the shortest path between two nodes as GeoJSON.
+    Route {
+        #[command(flatten)]
+        input: Input,
+        /// The start node.
+        from: NodeId,
+        /// The destination node.
+        to: NodeId,
+    },
+    /// Renders a network as SVG.
+    Render {
+        #[command(flatten)]
+        input: Input,
+        /// The SVG file to write.
+        output: PathBuf,
+        /// Highlights the shortest path between two nodes.
+        #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+        route: Option<Vec<NodeId>>,
+        /// The width of the image in pixels.
+        #[arg(long, default_value_t = 800.0)]
+        width: f64,
+        /// The height of the image in pixels.
+        #[arg(long, default_value_t = 600.0)]
+        height: f64,
+    },
+}
+
+#[derive(Args)]
+struct Input {
+    /// The network to read: a GTFS directory, a railML file, a snapshot or a directory with nodes.csv and edges.csv.
+    input: PathBuf,
+    /// The input format, detected from the path if omitted.
+    #[arg(long)]
+    format: Option<InputFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    Gtfs,
+    Railml,
+    Csv,
+    Snapshot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Railml,
+    Csv,
+    Snapshot,
+    Dot,
+    Graphml,
+    Svg,
+}
+
+impl InputFormat {
+    fn detect(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            if path.join("stops.txt").exists() {
+                Some(InputFormat::Gtfs)
+            } else if path.join("nodes.csv").exists() {
+                Some(InputFormat::Csv)
+            } else {
+                None
+            }
+        } else {
+            match extension(path).as_deref() {
+                Some("xml" | "railml") => Some(InputFormat::Railml),
+                Some("snap") => Some(InputFormat::Snapshot),
+                _ => None,
+            }
+        }
+    }
+}
+
+impl OutputFormat {
+    fn detect(path: &Path) -> Option<Self> {
+        match extension(path).as_deref() {
+            Some("xml" | "railml") => Some(OutputFormat::Railml),
+            Some("snap") => Some(OutputFormat::Snapshot),
+            Some("dot" | "gv") => Some(OutputFormat::Dot),
+            Some("graphml") => Some(OutputFormat::Graphml),
+            Some("svg") => Some(OutputFormat::Svg),
+            Some(_) => None,
+            None => Some(OutputFormat::Csv),
+        }
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+fn load(input: &Input) -> Result<Network, Box<dyn Error>> {
+    let format = input
+        .format
+        .or_else(|| InputFormat::detect(&input.input))
+        .ok_or_else(|| format!("cannot detect the format of {}", input.input.display()))?;
+    let network = match format {
+        InputFormat::Gtfs => {
+            let feed = read_gtfs(&input.input)?;
+            for movement in &feed.unwired {
+                eprintln!(
+                    "warning: movement from edge {} to edge {} at node {} is not wired",
+                    movement.arrival, movement.departure, movement.node_id
+                );
+            }
+            feed.network
+        }
+        InputFormat::Railml => read_railml(BufReader::new(File::open(&input.input)?))?.network,
+        InputFormat::Csv => {
+            let import = read_csv(
+                File::open(input.input.join("nodes.csv"))?,
+                File::open(input.input.join("edges.csv"))?,
+                &CsvOptions::default(),
+            )?;
+            for error in &import.errors {
+                eprintln!("warning: skipped {}", error);
+            }
+            import.network
+        }
+        InputFormat::Snapshot => Snapshot::open(&input.input)?.to_network(),
+    };
+    Ok(network)
+}
+
+fn import(
+    network: &Network,
+    output: &Path,
+    format: Option<OutputFormat>,
+) -> Result<(), Box<dyn Error>> {
+    let format = format
+        .or_else(|| OutputFormat::detect(output))
+        .ok_or_else(|| format!("cannot detect the format of {}", output.display()))?;
+    if format == OutputFormat::Csv {
+        fs::create_dir_all(output)?;
+        return Ok(write_csv(
+            network,
+            File::create(output.join("nodes.csv"))?,
+            File::create(output.join("edges.csv"))?,
+        )?);
+    }
+    let mut writer = BufWriter::new(File::create(output)?);
+    match format {
+        OutputFormat::Railml => write_railml(network, &mut writer)?,
+        OutputFormat::Snapshot => write_snapshot(network, &mut writer)?,
+        OutputFormat::Dot => {
+            writer.write_all(network.to_dot(&ExportOptions::default()).as_bytes())?
+        }
+        OutputFormat::Graphml => {
+            writer.write_all(network.to_graphml(&ExportOptions::default()).as_bytes())?
+        }
+        OutputFormat::Svg => {
+            writer.write_all(network.render_svg(&[], &SvgOptions::default()).as_bytes())?
+        }
+        OutputFormat::Csv => unreachable!(),
+    }
+    Ok(writer.flush()?)
+}
+
+fn location(network: &Network, node_id: NodeId) -> Option<Coord<f64>> {
+    let index = network.physical_graph.id_to_index(node_id)?;
+    Some(network.physical_graph.graph[*index].location)
+}
+
+fn stats(network: &Network) -> String {
+    let length: f64 = network
+        .physical_graph
+        .graph
+        .edge_weights()
+        .map(|edge| edge.length)
+        .sum();
+    let stats = network.stats();
+    let degrees: Vec<String> = stats
+        .degree_histogram
+        .iter()
+        .map(|(degree, count)| format!("{}:{}", degree, count))
+        .collect();
+    let bounding_box = stats
+        .bounding_box
+        .map(|rect| {
+            format!(
+                "{} {} {} {}",
+                rect.min().x,
+                rect.min().y,
+                rect.max().x,
+                rect.max().y
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "nodes: {}\nedges: {}\ncomponents: {}\ntotal length: {}\neuclidean length: {}\n\
+         haversine length: {:.1}\npath length: {}\n\
+         degrees: {}\nswitches: {}\ndead ends: {}\nparallel edges: {}\n\
+         degenerate paths: {}\nbounding box: {}\nunused toponodes: {}\n",
+        stats.node_count,
+        stats.edge_count,
+        connected_components(&network.physical_graph.graph),
+        length,
+        stats.euclidean_length,
+        stats.haversine_length,
+        stats.path_length,
+        degrees.join(" "),
+        stats.switches,
+        stats.dead_ends,
+        stats.parallel_edges,
+        stats.degenerate_paths,
+        bounding_box,
+        stats.unused_toponodes
+    )
+}
+
+/// Returns a description of every issue found by the audit of the edges and of the topology graph.
+fn validate(network: &Network) -> Vec<String> {
+    let issues = network.audit(&AuditOptions::default());
+    let topology_issues = network.audit_topology();
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .chain(topology_issues.iter().map(ToString::to_string))
+        .collect()
+}
+
+fn shortest_path(
+    network: &Network,
+    from: NodeId,
+    to: NodeId,
+) -> Result<(f64, Vec<NodeId>), Box<dyn Error>> {
+    network
+        .find_shortest_path_with_accessability(
+            from,
+            to,
+            Accessability::UnreachableNodes(vec![]),
+            |edge| edge.length,
+        )
+        .ok_or_else(|| format!("no route from node {} to node {}", from, to).into())
+}
+
+/// Formats a route as a GeoJSON feature along the paths of its edges.
+fn route_geojson(network: &Network, length: f64, nodes: &[NodeId]) -> String {
+    let mut coords: Vec<Coord<f64>> = Vec::new();
+    for pair in nodes.windows(2) {
+        if let Some(edge) = network.physical_graph.get_transit_edge(pair[0], pair[1]) {
+            let mut path = edge.path.0.clone();
+            if edge.source != pair[0] {
+                path.reverse();
+            }
+            let skip = usize::from(coords.last() == path.first());
+            coords.extend(path.into_iter().skip(skip));
+        }
+    }
+    if coords.is_empty() {
+        coords.extend(
+            nodes
+                .iter()
+                .filter_map(|node_id| location(network, *node_id)),
+        );
+    }
+    let coordinates: Vec<String> = coords
+        .iter()
+        .map(|coord| format!("[{},{}]", coord.x, coord.y))
+        .collect();
+    let node_ids: Vec<String> = nodes.iter().map(ToString::to_string).collect();
+    format!(
+        "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[{}]}},\
+         \"properties\":{{\"from\":{},\"to\":{},\"length\":{},\"nodes\":[{}]}}}}",
+        coordinates.join(","),
+        nodes.first().copied().unwrap_or_default(),
+        nodes.last().copied().unwrap_or_default(),
+        length,
+        node_ids.join(",")
+    )
+}
+
+fn run(command: Command) -> Result<ExitCode, Box<dyn Error>> {
+    match command {
+        Command::Import { input, output, to } => import(&load(&input)?, &output, to)?,
+        Command::Stats { input } => print!("{}", stats(&load(&input)?)),
+        Command::Validate { input } => {
+            let problems = validate(&load(&input)?);
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            if !problems.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("ok");
+        }
+        Command::Route { input, from, to } => {
+            let network = load(&input)?;
+            let (length, nodes) = shortest_path(&network, from, to)?;
+            println!("{}", route_geojson(&network, length, &nodes));
+        }
+        Command::Render {
+            input,
+            output,
+            route,
+            width,
+            height,
+        } => {
+            let network = load(&input)?;
+            let overlays = match route.as_deref() {
+                Some([from, to]) => vec![Overlay::Route(shortest_path(&network, *from, *to)?.1)],
+                _ => vec![],
+            };
+            let options = SvgOptions {
+                width,
+                height,
+                ..SvgOptions::default()
+            };
+            fs::write(output, network.render_svg(&overlays, &options))?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{coord, LineString};
+    use transit_grid::{
+        core::{TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+
+    fn network() -> Network {
+        let mut network = TransitNetwork::new();
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 3.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 0.0 },
+            });
+        }
+        for (id, source, target) in [(1, 1, 2), (2, 2, 3)] {
+            let (from, to) = (
+                location(&network, source).unwrap(),
+                location(&network, target).unwrap(),
+            );
+            network.add_edge_with_accessibility(
+                TransitEdge {
+                    id,
+                    source,
+                    target,
+                    length: to.x - from.x,
+                    path: LineString(vec![from, to]),
+                },
+                Accessability::ReachableNodes(vec![source]),
+            );
+        }
+        network
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let cli = Cli::parse_from(["transit-grid", "route", "net.xml", "1", "3"]);
+        assert!(matches!(cli.command, Command::Route { from: 1, to: 3, .. }));
+        assert_eq!(
+            OutputFormat::detect(Path::new("net.graphml")),
+            Some(OutputFormat::Graphml)
+        );
+        assert_eq!(
+            OutputFormat::detect(Path::new("out")),
+            Some(OutputFormat::Csv)
+        );
+        assert_eq!(
+            InputFormat::detect(Path::new("infrastructure.railml")),
+            Some(InputFormat::Railml)
+        );
+        assert_eq!(
+            InputFormat::detect(Path::new("network.snap")),
+            Some(InputFormat::Snapshot)
+        );
+    }
+
+    #[test]
+    fn test_stats_and_validate() {
+        let network = network();
+        assert_eq!(
+            stats(&network),
+            "nodes: 3\nedges: 2\ncomponents: 1\ntotal length: 3\neuclidean length: 3\n\
+             haversine length: 333585.2\npath length: 3\n\
+             degrees: 1:2 2:1\nswitches: 0\ndead ends: 2\nparallel edges: 0\n\
+             degenerate paths: 0\nbounding box: 0 0 3 0\nunused toponodes: 0\n"
+        );
+        assert!(validate(&network).is_empty());
+
+        let mut broken = network.clone();
+        broken.physical_graph.add_transit_edge(TransitEdge {
+            id: 3,
+            source: 1,
+            target: 3,
+            length: 1.0,
+            path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 9.0, y: 0.0 }]),
+        });
+        assert_eq!(
+            validate(&broken),
+            vec![
+                "edge 3 ends 6 away from node 3",
+                "edge 3 has length 1 but its path is 9 long",
+                "edge 3 is missing in the topology graph",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_geojson() {
+        let network = network();
+        let (length, nodes) = shortest_path(&network, 3, 1).unwrap();
+        assert_eq!(nodes, vec![3, 2, 1]);
+        assert_eq!(
+            route_geojson(&network, length, &nodes),
+            "{\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[3,0],[1,0],[0,0]]},\
+             \"properties\":{\"from\":3,\"to\":1,\"length\":3,\"nodes\":[3,2,1]}}"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4},
+    iter::Sum,
+};
+
+use geo::{Coord, CoordFloat, EuclideanLength, HaversineLength, LineString};
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+/// Semi-major axis of the WGS84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// Flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Scale factor on the central meridian of UTM zones.
+const UTM_K0: f64 = 0.9996;
+/// False easting of UTM zones in meters.
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+/// False northing of UTM zones on the southern hemisphere in meters.
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// Enum representing the coordinate reference system of the locations in a transit network.
+///
+/// Each variant corresponds to an EPSG code. Transformations with `transform` are supported between
+/// `Wgs84`, `WebMercator` and `Utm`, other systems can only be used as a tag.
+///
+/// # Examples
+///
+/// ```
+/// use geo::coord;
+/// use transit_grid::core::Crs;
+///
+/// let crs = Crs::from_epsg(32632);
+/// assert_eq!(crs, Crs::Utm { zone: 32, north: true });
+/// assert_eq!(crs.epsg(), 32632);
+///
+/// let utm = Crs::Wgs84.transform(coord! { x: 9.0, y: 0.0 }, crs).unwrap();
+/// assert!((utm.x - 500_000.0).abs() < 1e-6);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Crs {
+    /// WGS84 longitude and latitude in degrees, EPSG:4326.
+    Wgs84,
+    /// Web Mercator in meters as used by web maps, EPSG:3857.
+    WebMercator,
+    /// A zone of the Universal Transverse Mercator projection on WGS84 in meters, EPSG:326xx or EPSG:327xx.
+    Utm {
+        /// The zone number from 1 to 60.
+        zone: u8,
+        /// Whether the zone lies on the northern hemisphere.
+        north: bool,
+    },
+    /// Any other coordinate reference system, given by its EPSG code.
+    Other(u32),
+}
+
+impl Crs {
+    /// Returns the `Crs` with the given EPSG code.
+    pub fn from_epsg(code: u32) -> Self {
+        match code {
+            4326 => Crs::Wgs84,
+            3857 => Crs::WebMercator,
+            32601..=32660 => Crs::Utm {
+                zone: (code - 32600) as u8,
+                north: true,
+            },
+            32701..=32760 => Crs::Utm {
+                zone: (code - 32700) as u8,
+                north: false,
+            },
+            code => Crs::Other(code),
+        }
+    }
+
+    /// Returns the EPSG code of the `Crs`.
+    pub fn epsg(&self) -> u32 {
+        match self {
+            Crs::Wgs84 => 4326,
+            Crs::WebMercator => 3857,
+            Crs::Utm { zone, north: true } => 32600 + *zone as u32,
+            Crs::Utm { zone, north: false } => 32700 + *zone as u32,
+            Crs::Other(code) => *code,
+        }
+    }
+
+    /// Returns `true` if coordinates are longitude and latitude in degrees rather than projected meters.
+    pub fn is_geographic(&self) -> bool {
+        *self == Crs::Wgs84
+    }
+
+    /// Returns the UTM zone containing a WGS84 location, ignoring the exceptions around Norway and Svalbard.
+    pub fn utm_for(location: Coord<f64>) -> Self {
+        let zone = ((location.x + 180.0) / 6.0).floor().rem_euclid(60.0) as u8 + 1;
+        Crs::Utm {
+            zone,
+            north: location.y >= 0.0,
+        }
+    }
+
+    /// Transforms a coordinate from this `Crs` to another.
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The coordinate in this `Crs`.
+    /// * `to` - The `Crs` to transform to.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Coord<f64>>` - The coordinate in `to`, or `None` if one of the systems is not supported.
+    pub fn transform(&self, coord: Coord<f64>, to: Crs) -> Option<Coord<f64>> {
+        if *self == to {
+            return Some(coord);
+        }
+        let wgs84 = match self {
+            Crs::Wgs84 => coord,
+            Crs::WebMercator => web_mercator_inverse(coord),
+            Crs::Utm { zone, north } => utm_inverse(coord, *zone, *north),
+            Crs::Other(_) => return None,
+        };
+        match to {
+            Crs::Wgs84 => Some(wgs84),
+            Crs::WebMercator => Some(web_mercator_forward(wgs84)),
+            Crs::Utm { zone, north } => Some(utm_forward(wgs84, zone, north)),
+            Crs::Other(_) => None,
+        }
+    }
+}
+
+/// Returns the length of a path, in meters with the haversine formula for a geographic `Crs`
+/// and in the units of the coordinates otherwise.
+///
+/// Web Mercator stretches distances by `1 / cos(latitude)`, so its paths are projected back to WGS84
+/// and measured with the haversine formula as well.
+pub(crate) fn measure<T: CoordFloat + FromPrimitive + Sum>(
+    crs: Option<Crs>,
+    path: &LineString<T>,
+) -> T {
+    match crs {
+        Some(crs) if crs.is_geographic() => path.haversine_length(),
+        Some(Crs::WebMercator) => {
+            let wgs84: LineString<f64> = path
+                .coords()
+                .map(|coord| {
+                    web_mercator_inverse(Coord {
+                        x: coord.x.to_f64().unwrap_or(f64::NAN),
+                        y: coord.y.to_f64().unwrap_or(f64::NAN),
+                    })
+                })
+                .collect();
+            T::from_f64(wgs84.haversine_length()).unwrap_or_else(T::nan)
+        }
+        _ => path.euclidean_length(),
+    }
+}
+
+fn web_mercator_forward(coord: Coord<f64>) -> Coord<f64> {
+    Coord {
+        x: WGS84_A * coord.x.to_radians(),
+        y: WGS84_A * (FRAC_PI_4 + coord.y.to_radians() / 2.0).tan().ln(),
+    }
+}
+
+fn web_mercator_inverse(coord: Coord<f64>) -> Coord<f64> {
+    Coord {
+        x: (coord.x / WGS84_A).to_degrees(),
+        y: (2.0 * (coord.y / WGS84_A).exp().atan() - FRAC_PI_2).to_degrees(),
+    }
+}
+
+/// Coefficients of the Krüger series for the transverse Mercator projection of the WGS84 ellipsoid.
+struct Kruger {
+    /// The rectifying radius.
+    radius: f64,
+    alpha: [f64; 4],
+    beta: [f64; 4],
+    delta: [f64; 4],
+}
+
+impl Kruger {
+    fn wgs84() -> Self {
+        let n = WGS84_F / (2.0 - WGS84_F);
+        let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+        Kruger {
+            radius: WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0),
+            alpha: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0,
+                13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0,
+                61.0 * n3 / 240.0 - 103.0 * n4 / 140.0,
+                49561.0 * n4 / 161280.0,
+            ],
+            beta: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0,
+                n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0,
+                17.0 * n3 / 480.0 - 37.0 * n4 / 840.0,
+                4397.0 * n4 / 161280.0,
+            ],
+            delta: [
+                2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3 + 116.0 * n4 / 45.0,
+                7.0 * n2 / 3.0 - 8.0 * n3 / 5.0 - 227.0 * n4 / 45.0,
+                56.0 * n3 / 15.0 - 136.0 * n4 / 35.0,
+                4279.0 * n4 / 630.0,
+            ],
+        }
+    }
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    (zone as f64 * 6.0 - 183.0).to_radians()
+}
+
+fn utm_forward(coord: Coord<f64>, zone: u8, north: bool) -> Coord<f64> {
+    let kruger = Kruger::wgs84();
+    let n = WGS84_F / (2.0 - WGS84_F);
+    let e = 2.0 * n.sqrt() / (1.0 + n);
+    let (lat, dlon) = (
+        coord.y.to_radians(),
+        coord.x.to_radians() - central_meridian(zone),
+    );
+
+    let t = (lat.sin().atanh() - e * (e * lat.sin()).atanh()).sinh();
+    let xi = t.atan2(dlon.cos());
+    let eta = (dlon.sin() / (1.0 + t * t).sqrt()).atanh();
+    let (mut easting, mut northing) = (eta, xi);
+    for (j, alpha) in kruger.alpha.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        easting += alpha * (k * xi).cos() * (k * eta).sinh();
+        northing += alpha * (k * xi).sin() * (k * eta).cosh();
+    }
+    let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+    Coord {
+        x: UTM_FALSE_EASTING + UTM_K0 * kruger.radius * easting,
+        y: false_northing + UTM_K0 * kruger.radius * northing,
+    }
+}
+
+fn utm_inverse(coord: Coord<f64>, zone: u8, north: bool) -> Coord<f64> {
+    let kruger = Kruger::wgs84();
+    let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+    let xi = (coord.y - false_northing) / (UTM_K0 * kruger.radius);
+    let eta = (coord.x - UTM_FALSE_EASTING) / (UTM_K0 * kruger.radius);
+
+    let (mut xi_prime, mut eta_prime) = (xi, eta);
+    for (j, beta) in kruger.beta.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+        eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+    }
+    let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+    let mut lat = chi;
+    for (j, delta) in kruger.delta.iter().enumerate() {
+        lat += delta * (2.0 * (j + 1) as f64 * chi).sin();
+    }
+    let lon = central_meridian(zone) + eta_prime.sinh().atan2(xi_prime.cos());
+    Coord {
+        x: lon.to_degrees(),
+        y: lat.to_degrees(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::coord;
+
+    fn assert_close(actual: Coord<f64>, expected: Coord<f64>, tolerance: f64) {
+        assert!(
+            (actual.x - expected.x).abs() < tolerance && (actual.y - expected.y).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_epsg_codes() {
+        for code in [4326, 3857, 32601, 32632, 32660, 32701, 32733, 31467] {
+            assert_eq!(Crs::from_epsg(code).epsg(), code);
+        }
+        assert_eq!(
+            Crs::from_epsg(32733),
+            Crs::Utm {
+                zone: 33,
+                north: false
+            }
+        );
+        assert_eq!(Crs::from_epsg(31467), Crs::Other(31467));
+        assert!(Crs::Wgs84.is_geographic());
+        assert!(!Crs::WebMercator.is_geographic());
+    }
+
+    #[test]
+    fn test_measure_web_mercator() {
+        let wgs84 = LineString(vec![
+            coord! { x: 13.0, y: 52.0 },
+            coord! { x: 13.01, y: 52.005 },
+            coord! { x: 13.02, y: 52.0 },
+        ]);
+        let mercator: LineString<f64> = wgs84
+            .coords()
+            .map(|coord| Crs::Wgs84.transform(*coord, Crs::WebMercator).unwrap())
+            .collect();
+
+        let expected = measure(Some(Crs::Wgs84), &wgs84);
+
+        assert!((measure(Some(Crs::WebMercator), &mercator) - expected).abs() < 1e-6);
+        assert!(measure(None, &mercator) > 1.6 * expected);
+    }
+
+    #[test]
+    fn test_utm_for() {
+        assert_eq!(
+            Crs::utm_for(coord! { x: 13.4, y: 52.5 }),
+            Crs::Utm {
+                zone: 33,
+                north: true
+            }
+        );
+        assert_eq!(
+            Crs::utm_for(coord! { x: -70.6, y: -33.4 }),
+            Crs::Utm {
+                zone: 19,
+                north: false
+            }
+        );
+        assert_eq!(
+            Crs::utm_for(coord! { x: 180.0, y: 0.0 }),
+            Crs::Utm {
+                zone: 1,
+                north: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_utm() {
+        let utm31 = Crs::Utm {
+            zone: 31,
+            north: true,
+        };
+        let origin = Crs::Wgs84
+            .transform(coord! { x: 0.0, y: 0.0 }, utm31)
+            .unwrap();
+        assert_close(origin, coord! { x: 166_021.443, y: 0.0 }, 1e-3);
+
+        let utm33s = Crs::Utm {
+            zone: 33,
+            north: false,
+        };
+        let location = coord! { x: 13.7, y: -12.3 };
+        let projected = Crs::Wgs84.transform(location, utm33s).unwrap();
+        assert!(projected.y > 8_000_000.0 && projected.y < UTM_FALSE_NORTHING_SOUTH);
+        assert_close(
+            utm33s.transform(projected, Crs::Wgs84).unwrap(),
+            location,
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn test_web_mercator() {
+        let corner = Crs::Wgs84
+            .transform(
+                coord! { x: 180.0, y: 85.051_128_779_806_59 },
+                Crs::WebMercator,
+            )
+            .unwrap();
+        assert_close(
+            corner,
+            coord! { x: 20_037_508.342_789_244, y: 20_037_508.342_789_244 },
+            1e-6,
+        );
+
+        let utm32 = Crs::Utm {
+            zone: 32,
+            north: true,
+        };
+        let location = coord! { x: 1_000_000.0, y: 6_000_000.0 };
+        let back = Crs::WebMercator
+            .transform(location, utm32)
+            .and_then(|utm| utm32.transform(utm, Crs::WebMercator))
+            .unwrap();
+        assert_close(back, location, 1e-5);
+        assert_eq!(Crs::Other(31467).transform(location, Crs::Wgs84), None);
+    }
+}
@@ -1,7 +1,11 @@
 //! This module provides basic structures for representing a transit network.
 //! It provides `TransitNode` and `TransitEdge` structures, along with ID types for them.
 //! The `TransitNode` represents a node in the transit network, while the `TransitEdge` represents a connection between two nodes.
-//! The module also provides `Accessability`, an enum for representing the accessibility of nodes in the network.
+//! The module also provides `Accessability`, an enum for representing the accessibility of nodes in the network,
+//! `NetworkPosition`, a position on the network given as an edge and an offset along it,
+//! `NodeTag`, an enum for the operational role of a node,
+//! `Crs`, the coordinate reference system of node locations and edge paths,
+//! and `Timetable`, `Service` and `Stop` for the scheduled runs of vehicles over the network.
 
 mod edge;
 pub use edge::{EdgeId, PathCoordinates, TransitEdge};
@@ -9,7 +13,20 @@ pub use edge::{EdgeId, PathCoordinates, TransitEdge};
 mod accessability;
 /// Re-export of the `Accessability` enum from the `accessability` module.
 pub use accessability::Accessability;
+
+mod position;
+pub use position::NetworkPosition;
+
+mod node_tag;
+pub use node_tag::NodeTag;
+
+mod crs;
+pub(crate) use crs::measure;
+pub use crs::Crs;
+
+mod timetable;
 use serde::{Deserialize, Serialize};
+pub use timetable::{Service, ServiceId, Stop, Timetable};
 
 /// Type alias for an identifier.
 pub type IdType = u64;
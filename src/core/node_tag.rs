@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Enum `NodeTag` representing the operational role of a node in a network.
+///
+/// Tags carry information that cannot be derived from the graph structure,
+/// so that operations that simplify a network know which nodes have to be kept.
+///
+/// # Example
+///
+/// ```
+/// use transit_grid::core::NodeTag;
+///
+/// let tag = NodeTag::Station;
+/// assert_ne!(tag, NodeTag::ReversalPoint);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum NodeTag {
+    /// A node where vehicles stop to serve passengers or freight.
+    Station,
+    /// A node where vehicles may change their direction of travel.
+    ReversalPoint,
+}
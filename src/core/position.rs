@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use super::EdgeId;
+
+/// Structure representing a position on the transit network.
+///
+/// A position is given by the `EdgeId` of a `TransitEdge` and an offset along the edge's path,
+/// measured from the first coordinate of the path in the same unit as the edge's `length`.
+///
+/// # Examples
+///
+/// ```
+/// use transit_grid::core::NetworkPosition;
+///
+/// let position = NetworkPosition { edge_id: 1, offset: 0.5 };
+/// assert_eq!(position.edge_id, 1);
+/// assert_eq!(position.offset, 0.5);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkPosition<T> {
+    /// The identifier of the edge the position lies on.
+    pub edge_id: EdgeId,
+
+    /// The offset along the edge's path, measured from its first coordinate.
+    pub offset: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_position() {
+        let position = NetworkPosition {
+            edge_id: 1,
+            offset: 0.5,
+        };
+        assert_eq!(position.edge_id, 1);
+        assert_eq!(position.offset, 0.5);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use super::{EdgeId, IdType, NodeId};
+
+/// Type alias for a service identifier.
+pub type ServiceId = IdType;
+
+/// A scheduled call of a service at a node.
+///
+/// Times are given in seconds, e.g. since midnight of the operating day.
+/// The dwell time at the stop is the difference between `departure` and `arrival`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stop {
+    /// The ID of the node the service stops at.
+    pub node_id: NodeId,
+    /// The scheduled arrival time in seconds.
+    pub arrival: f64,
+    /// The scheduled departure time in seconds.
+    pub departure: f64,
+}
+
+/// A single scheduled run of a vehicle calling at a sequence of stops.
+///
+/// Each run between two consecutive stops is bound to a path through the network:
+/// `paths[i]` holds the IDs of the nodes from `stops[i]` to `stops[i + 1]`, both included,
+/// and `edges[i]` the IDs of the edges between them, which tells parallel edges apart.
+/// Empty `paths` and `edges` mean the service is not bound yet, see `TransitNetwork::bind_service`.
+///
+/// # Example
+///
+/// ```
+/// use transit_grid::core::{Service, Stop};
+///
+/// let service = Service::new(1, vec![
+///     Stop { node_id: 1, arrival: 0.0, departure: 0.0 },
+///     Stop { node_id: 3, arrival: 120.0, departure: 150.0 },
+///     Stop { node_id: 4, arrival: 300.0, departure: 300.0 },
+/// ]);
+/// assert_eq!(service.running_time(2), Some(150.0));
+/// assert_eq!(service.dwell_time(1), Some(30.0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Service {
+    /// The unique identifier of the service.
+    pub id: ServiceId,
+    /// The stops in the order they are served.
+    pub stops: Vec<Stop>,
+    /// The paths between consecutive stops.
+    pub paths: Vec<Vec<NodeId>>,
+    /// The IDs of the edges along each path, so that `edges[i][j]` connects `paths[i][j]` and `paths[i][j + 1]`.
+    #[serde(default)]
+    pub edges: Vec<Vec<EdgeId>>,
+}
+
+impl Service {
+    /// Creates a service with the given stops and without bound paths.
+    pub fn new(id: ServiceId, stops: Vec<Stop>) -> Self {
+        Service {
+            id,
+            stops,
+            paths: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Returns the scheduled time in seconds from the departure at stop `index - 1` to the arrival at stop `index`.
+    pub fn running_time(&self, index: usize) -> Option<f64> {
+        let previous = self.stops.get(index.checked_sub(1)?)?;
+        Some(self.stops.get(index)?.arrival - previous.departure)
+    }
+
+    /// Returns the scheduled dwell time in seconds at stop `index`.
+    pub fn dwell_time(&self, index: usize) -> Option<f64> {
+        let stop = self.stops.get(index)?;
+        Some(stop.departure - stop.arrival)
+    }
+
+    /// Returns `true` if every run between consecutive stops is bound to a path and its edges.
+    pub fn is_bound(&self) -> bool {
+        !self.stops.is_empty()
+            && self.paths.len() + 1 == self.stops.len()
+            && self.edges.len() == self.paths.len()
+    }
+
+    /// Returns the IDs of all nodes passed by the service, following its bound paths.
+    ///
+    /// Stops are listed once even though they end one path and start the next.
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = Vec::new();
+        for path in &self.paths {
+            let skip = usize::from(nodes.last().is_some() && nodes.last() == path.first());
+            nodes.extend(path.iter().skip(skip));
+        }
+        nodes
+    }
+}
+
+/// A collection of services operated on a network.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timetable {
+    /// The services of the timetable.
+    pub services: Vec<Service>,
+}
+
+impl Timetable {
+    /// Creates an empty timetable.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a service to the timetable.
+    pub fn add_service(&mut self, service: Service) {
+        self.services.push(service);
+    }
+
+    /// Returns the service with the given ID.
+    pub fn service(&self, service_id: ServiceId) -> Option<&Service> {
+        self.services
+            .iter()
+            .find(|service| service.id == service_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service() {
+        let mut service = Service::new(
+            1,
+            vec![
+                Stop {
+                    node_id: 1,
+                    arrival: 0.0,
+                    departure: 10.0,
+                },
+                Stop {
+                    node_id: 3,
+                    arrival: 70.0,
+                    departure: 70.0,
+                },
+            ],
+        );
+
+        assert_eq!(service.running_time(0), None);
+        assert_eq!(service.running_time(1), Some(60.0));
+        assert_eq!(service.dwell_time(0), Some(10.0));
+        assert_eq!(service.dwell_time(2), None);
+        assert!(!service.is_bound());
+
+        service.paths = vec![vec![1, 2, 3]];
+        assert!(!service.is_bound());
+        service.edges = vec![vec![1, 2]];
+        assert!(service.is_bound());
+        assert_eq!(service.nodes(), vec![1, 2, 3]);
+
+        let mut timetable = Timetable::new();
+        timetable.add_service(service.clone());
+        assert_eq!(timetable.service(1), Some(&service));
+        assert_eq!(timetable.service(2), None);
+    }
+}
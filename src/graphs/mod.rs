@@ -21,6 +21,10 @@ mod physical;
 mod topology;
 mod transit_network;
 
-pub use physical::PhysicalGraph;
+pub use physical::{AuditIssue, AuditOptions, PathEnd, PhysicalGraph};
 pub use topology::*;
-pub use transit_network::TransitNetwork;
+pub use transit_network::{
+    BoundaryMode, ContractionMapping, EdgeMapping, MergeMapping, MergePolicy, NetworkStats,
+    NodeCollision, SplitPoint, SubnetworkFilter, TimetableIssue, TimetableOptions, TopologyIssue,
+    TransitNetwork,
+};
@@ -0,0 +1,473 @@
+use std::{collections::HashMap, fmt, iter::Sum};
+
+use geo::{Coord, CoordFloat, LineString};
+use num_traits::FromPrimitive;
+
+use crate::core::{measure, Crs, EdgeId, NodeId, TransitEdge};
+
+use super::PhysicalGraph;
+
+/// Options for `PhysicalGraph::audit`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuditOptions<T> {
+    /// The largest accepted distance between the end of a path and the location of its node, measured like the lengths.
+    pub endpoint_tolerance: T,
+    /// The largest accepted difference between the stored and the computed length, as a fraction of the computed length.
+    pub length_tolerance: T,
+}
+
+impl<T: CoordFloat + FromPrimitive> Default for AuditOptions<T> {
+    fn default() -> Self {
+        AuditOptions {
+            endpoint_tolerance: T::from_f64(1e-6).unwrap(),
+            length_tolerance: T::from_f64(0.01).unwrap(),
+        }
+    }
+}
+
+/// Enum `PathEnd` naming an end of an edge path, taken in the direction from the source to the target node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathEnd {
+    /// The end at the source node.
+    Start,
+    /// The end at the target node.
+    End,
+}
+
+/// Enum `AuditIssue` describing a data-quality problem of an edge found by `PhysicalGraph::audit`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditIssue<T> {
+    /// The path runs from the target to the source, as detected by `PhysicalGraph::repair_edge`.
+    ReversedPath {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// An end of the path lies farther than the tolerance from the location of its node.
+    DetachedEndpoint {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+        /// The ID of the node the end belongs to.
+        node_id: NodeId,
+        /// The detached end of the path, after reversing a reversed path.
+        end: PathEnd,
+        /// The distance between the end of the path and the node.
+        distance: T,
+    },
+    /// The stored length differs from the length of the path by more than the tolerance.
+    LengthMismatch {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+        /// The length stored in the edge.
+        stored: T,
+        /// The length of the path, in meters for a geographic `Crs` and in coordinate units otherwise.
+        computed: T,
+    },
+    /// The edge starts and ends at the same node.
+    SelfLoop {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The path has no coordinates, so the `PathCoordinates` of the edge cannot be computed.
+    EmptyPath {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The path has the same coordinates as the path of an edge with a lower ID, in the same or reverse order.
+    DuplicateGeometry {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+        /// The ID of the edge with the same path.
+        duplicate_of: EdgeId,
+    },
+}
+
+impl<T> AuditIssue<T> {
+    /// Returns the ID of the edge the issue was found at.
+    pub fn edge_id(&self) -> EdgeId {
+        match self {
+            AuditIssue::ReversedPath { edge_id }
+            | AuditIssue::DetachedEndpoint { edge_id, .. }
+            | AuditIssue::LengthMismatch { edge_id, .. }
+            | AuditIssue::SelfLoop { edge_id }
+            | AuditIssue::EmptyPath { edge_id }
+            | AuditIssue::DuplicateGeometry { edge_id, .. } => *edge_id,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for AuditIssue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditIssue::ReversedPath { edge_id } => {
+                write!(f, "edge {} has a reversed path", edge_id)
+            }
+            AuditIssue::DetachedEndpoint {
+                edge_id,
+                node_id,
+                distance,
+                ..
+            } => write!(
+                f,
+                "edge {} ends {} away from node {}",
+                edge_id, distance, node_id
+            ),
+            AuditIssue::LengthMismatch {
+                edge_id,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "edge {} has length {} but its path is {} long",
+                edge_id, stored, computed
+            ),
+            AuditIssue::SelfLoop { edge_id } => write!(f, "edge {} is a self-loop", edge_id),
+            AuditIssue::EmptyPath { edge_id } => write!(f, "edge {} has an empty path", edge_id),
+            AuditIssue::DuplicateGeometry {
+                edge_id,
+                duplicate_of,
+            } => write!(
+                f,
+                "edge {} has the same path as edge {}",
+                edge_id, duplicate_of
+            ),
+        }
+    }
+}
+
+impl<R, T> PhysicalGraph<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordFloat + FromPrimitive + Sum,
+{
+    /// Checks all edges for inconsistencies between their path, their length and their nodes.
+    ///
+    /// Unlike `repair_edge`, which only reverses paths, the audit reports every `AuditIssue` it finds
+    /// and leaves the graph unchanged. The ends of a reversed path are compared with the nodes they are nearer to.
+    /// Lengths and distances are measured with the haversine formula for a geographic `Crs` and with the
+    /// Euclidean distance otherwise, so `TransitNetwork::audit` passes the `Crs` of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `AuditOptions` with the tolerances of the checks.
+    /// * `crs` - The coordinate reference system of the node locations and edge paths, if known.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<AuditIssue<T>>` - The issues found, ordered by edge ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::core::{TransitEdge, TransitNode};
+    /// use transit_grid::prelude::{AuditIssue, AuditOptions, PhysicalGraph};
+    ///
+    /// let mut graph: PhysicalGraph<Coord, f64> = PhysicalGraph::new();
+    /// graph.add_transit_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// graph.add_transit_node(TransitNode { id: 2, location: coord! { x: 1.0, y: 0.0 } });
+    /// graph.add_transit_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 2.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let issues = graph.audit(&AuditOptions::default(), None);
+    /// assert_eq!(issues, vec![AuditIssue::LengthMismatch { edge_id: 1, stored: 2.0, computed: 1.0 }]);
+    ///
+    /// graph.apply_fixes(&issues, None);
+    /// assert!(graph.audit(&AuditOptions::default(), None).is_empty());
+    /// ```
+    pub fn audit(&self, options: &AuditOptions<T>, crs: Option<Crs>) -> Vec<AuditIssue<T>> {
+        let mut edges: Vec<&TransitEdge<T>> = self.graph.edge_weights().collect();
+        edges.sort_by_key(|edge| edge.id);
+
+        let mut issues = Vec::new();
+        let mut geometries: HashMap<Vec<(u64, u64)>, EdgeId> = HashMap::new();
+        for edge in edges {
+            if edge.source == edge.target {
+                issues.push(AuditIssue::SelfLoop { edge_id: edge.id });
+            }
+            let (Some(first), Some(last)) = (edge.path.0.first(), edge.path.0.last()) else {
+                issues.push(AuditIssue::EmptyPath { edge_id: edge.id });
+                continue;
+            };
+
+            let source = self.location(edge.source);
+            let target = self.location(edge.target);
+            let reversed = distance(crs, source, *first) > distance(crs, source, *last);
+            let (start, end) = if reversed {
+                issues.push(AuditIssue::ReversedPath { edge_id: edge.id });
+                (last, first)
+            } else {
+                (first, last)
+            };
+            for (node_id, location, coord, end) in [
+                (edge.source, source, start, PathEnd::Start),
+                (edge.target, target, end, PathEnd::End),
+            ] {
+                let distance = distance(crs, location, *coord);
+                if distance > options.endpoint_tolerance {
+                    issues.push(AuditIssue::DetachedEndpoint {
+                        edge_id: edge.id,
+                        node_id,
+                        end,
+                        distance,
+                    });
+                }
+            }
+
+            let computed = measure(crs, &edge.path);
+            if (edge.length - computed).abs() > options.length_tolerance * computed {
+                issues.push(AuditIssue::LengthMismatch {
+                    edge_id: edge.id,
+                    stored: edge.length,
+                    computed,
+                });
+            }
+
+            let key = |coords: &mut dyn Iterator<Item = &Coord<T>>| -> Vec<(u64, u64)> {
+                coords
+                    .map(|coord| {
+                        let bits = |value: T| value.to_f64().unwrap_or_default().to_bits();
+                        (bits(coord.x), bits(coord.y))
+                    })
+                    .collect()
+            };
+            let forward = key(&mut edge.path.0.iter());
+            let backward = key(&mut edge.path.0.iter().rev());
+            match geometries
+                .get(&forward)
+                .or_else(|| geometries.get(&backward))
+            {
+                Some(duplicate_of) => issues.push(AuditIssue::DuplicateGeometry {
+                    edge_id: edge.id,
+                    duplicate_of: *duplicate_of,
+                }),
+                None => {
+                    geometries.insert(forward, edge.id);
+                }
+            }
+        }
+        issues
+    }
+
+    /// Fixes the issues found by `audit` where possible.
+    ///
+    /// Reversed paths are reversed and detached endpoints are snapped to the location of their node. The length of
+    /// every edge with a snapped endpoint or a mismatching length is recomputed from the path after snapping. Self-loops, empty paths and
+    /// duplicate geometries need a decision about the intended network and are left unchanged.
+    /// Pass only some of the issues to apply only some of the fixes.
+    ///
+    /// # Arguments
+    ///
+    /// * `issues` - The issues to fix, as returned by `audit`.
+    /// * `crs` - The coordinate reference system the lengths are measured in, as passed to `audit`.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of issues fixed.
+    pub fn apply_fixes(&mut self, issues: &[AuditIssue<T>], crs: Option<Crs>) -> usize {
+        let mut fixes: HashMap<EdgeId, Vec<&AuditIssue<T>>> = HashMap::new();
+        for issue in issues {
+            fixes.entry(issue.edge_id()).or_default().push(issue);
+        }
+        let locations: HashMap<NodeId, Coord<T>> = self
+            .graph
+            .node_weights()
+            .map(|node| (node.id, node.location.into()))
+            .collect();
+
+        let mut fixed = 0;
+        for edge in self.graph.edge_weights_mut() {
+            let Some(edge_fixes) = fixes.get(&edge.id) else {
+                continue;
+            };
+            if edge_fixes
+                .iter()
+                .any(|issue| matches!(issue, AuditIssue::ReversedPath { .. }))
+            {
+                edge.path.0.reverse();
+                fixed += 1;
+            }
+            let mut snapped = false;
+            for issue in edge_fixes {
+                if let AuditIssue::DetachedEndpoint { node_id, end, .. } = issue {
+                    let coord = match end {
+                        PathEnd::Start => edge.path.0.first_mut(),
+                        PathEnd::End => edge.path.0.last_mut(),
+                    };
+                    if let (Some(coord), Some(location)) = (coord, locations.get(node_id)) {
+                        *coord = *location;
+                        snapped = true;
+                        fixed += 1;
+                    }
+                }
+            }
+            let mismatch = edge_fixes
+                .iter()
+                .any(|issue| matches!(issue, AuditIssue::LengthMismatch { .. }));
+            if snapped || mismatch {
+                edge.length = measure(crs, &edge.path);
+            }
+            fixed += usize::from(mismatch);
+        }
+        fixed
+    }
+
+    fn location(&self, node_id: NodeId) -> Option<Coord<T>> {
+        let index = self.id_to_index(node_id)?;
+        Some(self.graph[*index].location.into())
+    }
+}
+
+fn distance<T: CoordFloat + FromPrimitive + Sum>(
+    crs: Option<Crs>,
+    location: Option<Coord<T>>,
+    coord: Coord<T>,
+) -> T {
+    location
+        .map(|location| measure(crs, &LineString(vec![location, coord])))
+        .unwrap_or_else(T::infinity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TransitNode;
+    use geo::{coord, LineString};
+
+    fn graph() -> PhysicalGraph<Coord, f64> {
+        let mut graph = PhysicalGraph::new();
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 2.0)] {
+            graph.add_transit_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 0.0 },
+            });
+        }
+        graph
+    }
+
+    fn edge(
+        id: EdgeId,
+        source: NodeId,
+        target: NodeId,
+        length: f64,
+        path: &[(f64, f64)],
+    ) -> TransitEdge<f64> {
+        TransitEdge {
+            id,
+            source,
+            target,
+            length,
+            path: LineString::from(path.to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut graph = graph();
+        graph.add_transit_edge(edge(1, 1, 2, 1.0, &[(0.0, 0.0), (1.0, 0.0)]));
+        graph.add_transit_edge(edge(2, 2, 3, 1.0, &[(2.0, 0.0), (1.0, 0.0)]));
+        graph.add_transit_edge(edge(3, 3, 2, 1.005, &[(2.0, 0.0), (1.0, 0.0)]));
+        graph.add_transit_edge(edge(4, 3, 3, 0.0, &[]));
+        graph.add_transit_edge(edge(5, 2, 1, 3.0, &[(0.0, 0.0), (1.0, 0.0)]));
+        graph.add_transit_edge(edge(6, 1, 3, 2.0, &[(0.0, 0.1), (2.0, 0.0)]));
+
+        let issues = graph.audit(&AuditOptions::default(), None);
+
+        assert_eq!(
+            issues,
+            vec![
+                AuditIssue::ReversedPath { edge_id: 2 },
+                AuditIssue::DuplicateGeometry {
+                    edge_id: 3,
+                    duplicate_of: 2
+                },
+                AuditIssue::SelfLoop { edge_id: 4 },
+                AuditIssue::EmptyPath { edge_id: 4 },
+                AuditIssue::ReversedPath { edge_id: 5 },
+                AuditIssue::LengthMismatch {
+                    edge_id: 5,
+                    stored: 3.0,
+                    computed: 1.0
+                },
+                AuditIssue::DuplicateGeometry {
+                    edge_id: 5,
+                    duplicate_of: 1
+                },
+                AuditIssue::DetachedEndpoint {
+                    edge_id: 6,
+                    node_id: 1,
+                    end: PathEnd::Start,
+                    distance: 0.1
+                },
+            ]
+        );
+        assert_eq!(issues[7].to_string(), "edge 6 ends 0.1 away from node 1");
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let mut graph = graph();
+        graph.add_transit_edge(edge(1, 1, 2, 1.0, &[(0.0, 0.0), (1.0, 0.0)]));
+        graph.add_transit_edge(edge(2, 2, 3, 1.0, &[(2.0, 0.5), (1.0, 0.0)]));
+        let options = AuditOptions::default();
+
+        let issues = graph.audit(&options, None);
+        let fixed = graph.apply_fixes(&issues, None);
+
+        assert_eq!(fixed, 3);
+        assert!(graph.audit(&options, None).is_empty());
+        assert_eq!(
+            graph.get_transit_edge_by_id(2).unwrap().path,
+            LineString::from(vec![(1.0, 0.0), (2.0, 0.0)])
+        );
+    }
+
+    #[test]
+    fn test_apply_selected_fixes() {
+        let mut graph = graph();
+        graph.add_transit_edge(edge(1, 1, 2, 1.0, &[(0.0, 0.0), (1.0, 0.2)]));
+        let options = AuditOptions::default();
+
+        let issues = graph.audit(&options, None);
+        assert_eq!(issues.len(), 2);
+        let snaps: Vec<_> = issues
+            .into_iter()
+            .filter(|issue| matches!(issue, AuditIssue::DetachedEndpoint { .. }))
+            .collect();
+        graph.apply_fixes(&snaps, None);
+
+        assert!(graph.audit(&options, None).is_empty());
+        assert_eq!(graph.get_transit_edge_by_id(1).unwrap().length, 1.0);
+    }
+
+    #[test]
+    fn test_apply_fixes_self_loop() {
+        let mut graph = graph();
+        graph.add_transit_edge(edge(1, 2, 2, 2.0, &[(1.0, 0.0), (1.5, 0.5), (1.2, 0.1)]));
+        let options = AuditOptions {
+            length_tolerance: 1.0,
+            ..Default::default()
+        };
+
+        let issues = graph.audit(&options, None);
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            AuditIssue::DetachedEndpoint {
+                end: PathEnd::End,
+                ..
+            }
+        )));
+        graph.apply_fixes(&issues, None);
+
+        assert_eq!(
+            graph.get_transit_edge_by_id(1).unwrap().path,
+            LineString::from(vec![(1.0, 0.0), (1.5, 0.5), (1.0, 0.0)])
+        );
+        let length = graph.get_transit_edge_by_id(1).unwrap().length;
+        assert!((length - 2.0 * 0.5f64.sqrt()).abs() < 1e-12);
+    }
+}
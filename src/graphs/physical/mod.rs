@@ -7,6 +7,10 @@ use petgraph::{
     graph::{NodeIndex, UnGraph},
 };
 
+mod audit;
+
+pub use audit::{AuditIssue, AuditOptions, PathEnd};
+
 /// Represents the physical layout of the transit network.
 ///
 /// `PhysicalGraph` is an undirected graph where each node represents a transit node (a point in the transit network where a vehicle can stop) and each edge represents a transit edge (a path between two transit nodes).
@@ -221,6 +225,57 @@ impl<R: Copy, T: CoordNum> PhysicalGraph<R, T> {
             .map(|edge| edge.weight())
     }
 
+    /// Removes the `TransitEdge` with the specified `EdgeId` from the `PhysicalGraph`.
+    ///
+    /// # Returns
+    ///
+    /// The removed `TransitEdge`, or `None` if there is no edge with the given ID.
+    pub fn remove_transit_edge(&mut self, edge_id: EdgeId) -> Option<TransitEdge<T>> {
+        let edge_index = self
+            .graph
+            .edge_indices()
+            .find(|index| self.graph[*index].id == edge_id)?;
+        self.graph.remove_edge(edge_index)
+    }
+
+    /// Removes the `TransitNode` with the specified `NodeId` and all its edges from the `PhysicalGraph`.
+    ///
+    /// Removing a node moves the last node of the underlying graph to the freed `NodeIndex`,
+    /// the mappings between `NodeId`s and `NodeIndex`es are updated accordingly.
+    ///
+    /// # Returns
+    ///
+    /// The removed `TransitNode`, or `None` if there is no node with the given ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use transit_grid::prelude::PhysicalGraph;
+    /// use transit_grid::core::TransitNode;
+    /// use geo::{coord, Coord};
+    ///
+    /// let mut graph: PhysicalGraph<Coord, f64> = PhysicalGraph::new();
+    /// let node1_index = graph.add_transit_node(TransitNode { id: 1, location: coord! { x:0.0, y:0.0 } });
+    /// graph.add_transit_node(TransitNode { id: 2, location: coord! { x:1.0, y:1.0 } });
+    ///
+    /// graph.remove_transit_node(1);
+    /// assert_eq!(graph.id_to_index(1), None);
+    /// assert_eq!(graph.id_to_index(2), Some(&node1_index));
+    /// ```
+    pub fn remove_transit_node(&mut self, node_id: NodeId) -> Option<TransitNode<R>> {
+        let index = self.id_to_index.remove(&node_id)?;
+        self.index_to_id.remove(&index);
+        let last = NodeIndex::new(self.graph.node_count() - 1);
+        let node = self.graph.remove_node(index)?;
+        if last != index {
+            if let Some(moved_id) = self.index_to_id.remove(&last) {
+                self.id_to_index.insert(moved_id, index);
+                self.index_to_id.insert(index, moved_id);
+            }
+        }
+        Some(node)
+    }
+
     /// Repairs a physical edge in the `PhysicalGraph` based on its nodes' locations.
     ///
     /// # Arguments
@@ -338,6 +393,11 @@ mod tests {
 
         assert!(graph.get_transit_edge(1, 2).is_some());
         assert!(graph.get_transit_edge(1, 3).is_none());
+
+        let removed = graph.remove_transit_edge(1);
+        assert_eq!(removed.map(|edge| edge.id), Some(1));
+        assert_eq!(graph.graph.edge_count(), 0);
+        assert!(graph.remove_transit_edge(1).is_none());
     }
 
     #[test]
@@ -339,6 +339,92 @@ impl TopologyGraph {
         unreachable!("Could not add edge with accessibility");
     }
 
+    /// Adds a single directed `TopoEdge` between two `TopoNode`s.
+    ///
+    /// Unlike `add_edge`, this does not add the twin edge and does not choose the `TopoNode`s,
+    /// which allows rewiring the graph while keeping existing switch connections intact.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge_id` - The `EdgeId` of the edge the `TopoEdge` belongs to.
+    /// * `from` - The `NodeIndex` of the `TopoNode` where the edge starts.
+    /// * `to` - The `NodeIndex` of the `TopoNode` where the edge ends.
+    ///
+    /// # Returns
+    ///
+    /// * `EdgeIndex` - The index of the added edge.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if one of the `NodeIndex` values does not exist in the graph.
+    pub fn add_topo_edge(&mut self, edge_id: EdgeId, from: NodeIndex, to: NodeIndex) -> EdgeIndex {
+        let topo_edge = TopoEdge {
+            id: EdgeIndex::new(0), // Temporary value; will be updated
+            from: *self.index_to_id.get(&from).unwrap(),
+            to: *self.index_to_id.get(&to).unwrap(),
+            edge_id,
+        };
+        let topo_edge_id = self.graph.add_edge(from, to, topo_edge);
+        self.graph.edge_weight_mut(topo_edge_id).unwrap().id = topo_edge_id;
+        topo_edge_id
+    }
+
+    /// Returns the indices of all `TopoEdge`s belonging to the edge with the given `EdgeId`.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge_id` - The `EdgeId` of the edge.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<EdgeIndex>` - The indices of the `TopoEdge`s, usually one for each direction.
+    pub fn find_topo_edges(&self, edge_id: EdgeId) -> Vec<EdgeIndex> {
+        self.graph
+            .edge_indices()
+            .filter(|index| self.graph[*index].edge_id == edge_id)
+            .collect()
+    }
+
+    /// Removes all `TopoEdge`s belonging to the edge with the given `EdgeId`.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge_id` - The `EdgeId` of the edge to be removed.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(NodeIndex, NodeIndex)>` - The endpoints of the removed `TopoEdge`s.
+    pub fn remove_edge(&mut self, edge_id: EdgeId) -> Vec<(NodeIndex, NodeIndex)> {
+        self.find_topo_edges(edge_id)
+            .into_iter()
+            .filter_map(|index| {
+                let endpoints = self.graph.edge_endpoints(index);
+                self.graph.remove_edge(index);
+                endpoints
+            })
+            .collect()
+    }
+
+    /// Removes a Node with a `NodeId` from the topological graph.
+    ///
+    /// Both `TopoNode`s of the node and all `TopoEdge`s connected to them are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The `NodeId` to be removed from the graph.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(NodeIndex, NodeIndex)>` - The indices of the removed `TopoNode`s, if the node existed.
+    pub fn remove_node(&mut self, node_id: NodeId) -> Option<(NodeIndex, NodeIndex)> {
+        let (topo_node_id1, topo_node_id2) = self.id_to_index.remove(&node_id)?;
+        for topo_node_id in [topo_node_id1, topo_node_id2] {
+            self.index_to_id.remove(&topo_node_id);
+            self.graph.remove_node(topo_node_id);
+        }
+        Some((topo_node_id1, topo_node_id2))
+    }
+
     /// Checks if a node has an incoming edge in the topological graph.
     ///
     /// # Arguments
@@ -785,4 +871,44 @@ mod tests {
             Some((node2.0, node1.0))
         );
     }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut topo_graph = TopologyGraph::default();
+        let node1 = topo_graph.add_node(1);
+        let node2 = topo_graph.add_node(2);
+        topo_graph.add_node(3);
+        topo_graph.add_edge(12, 1, 2);
+        topo_graph.add_edge(23, 2, 3);
+
+        let removed = topo_graph.remove_edge(12);
+
+        assert_eq!(removed, vec![(node1.0, node2.0), (node2.1, node1.1)]);
+        assert!(topo_graph.find_topo_edges(12).is_empty());
+        assert_eq!(topo_graph.find_topo_edges(23).len(), 2);
+
+        let edge_index = topo_graph.add_topo_edge(12, node1.0, node2.0);
+        assert_eq!(topo_graph.graph[edge_index].id, edge_index);
+        assert_eq!(topo_graph.graph[edge_index].from, 1);
+        assert_eq!(topo_graph.graph[edge_index].to, 2);
+        assert_eq!(topo_graph.find_topo_edges(12), vec![edge_index]);
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut topo_graph = TopologyGraph::default();
+        topo_graph.add_node(1);
+        let node2 = topo_graph.add_node(2);
+        topo_graph.add_node(3);
+        topo_graph.add_edge(12, 1, 2);
+        topo_graph.add_edge(23, 2, 3);
+
+        assert_eq!(topo_graph.remove_node(2), Some(node2));
+        assert_eq!(topo_graph.remove_node(2), None);
+
+        assert_eq!(topo_graph.graph.node_count(), 4);
+        assert_eq!(topo_graph.graph.edge_count(), 0);
+        assert_eq!(topo_graph.id_to_index(2), None);
+        assert_eq!(topo_graph.index_to_id(node2.0), None);
+    }
 }
@@ -0,0 +1,481 @@
+use std::collections::{HashMap, HashSet};
+
+use geo::{Coord, CoordNum, LineString};
+use petgraph::{stable_graph::NodeIndex, Direction};
+
+use crate::{
+    core::{EdgeId, NetworkPosition, NodeId, TransitEdge},
+    operations::TransitNetworkRemover,
+};
+
+use super::TransitNetwork;
+
+/// Location of a removed edge within the edge that replaced it.
+///
+/// # Fields
+///
+/// * `edge_id: EdgeId` - The ID of the edge that replaced the removed edge.
+/// * `offset: T` - The offset along the new edge at which the removed edge's segment begins.
+/// * `reversed: bool` - Whether the removed edge's path runs against the new edge's path.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EdgeMapping<T> {
+    /// The ID of the edge that replaced the removed edge.
+    pub edge_id: EdgeId,
+    /// The offset along the new edge at which the removed edge's segment begins.
+    pub offset: T,
+    /// Whether the removed edge's path runs against the new edge's path.
+    pub reversed: bool,
+}
+
+/// Mapping from the nodes and edges removed by `TransitNetwork::contract_pass_through_nodes`
+/// to their location on the edges that replaced them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractionMapping<T> {
+    /// The position of every removed node on the edge that replaced it.
+    pub nodes: HashMap<NodeId, NetworkPosition<T>>,
+    /// The location of every contracted edge within the edge that replaced it.
+    pub edges: HashMap<EdgeId, EdgeMapping<T>>,
+}
+
+/// A chain of edges through pass-through nodes, each edge with a flag whether it is traversed against its path.
+struct Chain<T: CoordNum> {
+    start: NodeId,
+    end: NodeId,
+    edges: Vec<(TransitEdge<T>, bool)>,
+    nodes: Vec<NodeId>,
+}
+
+impl<R: Copy, T: CoordNum> TransitNetwork<R, T> {
+    /// Merges chains of edges through pass-through nodes into single edges.
+    ///
+    /// A node is a pass-through node if it carries no `NodeTag`, has exactly two edges leading to two different
+    /// neighbors with the same speed limit and both of its `TopoNode`s lead straight from one edge to the other.
+    /// Every chain of edges through pass-through nodes is replaced by an edge with the ID of the chain's first edge,
+    /// the concatenated `path`, the summed `length` and the speed limit of the chain.
+    /// Chains forming a cycle of pass-through nodes are kept.
+    ///
+    /// # Returns
+    ///
+    /// * `ContractionMapping<T>` - The location of all removed nodes and contracted edges on the new edges.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 2.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 2.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]),
+    /// });
+    /// let new_node = TransitNode { id: 3, location: coord! { x: 1.0, y: 0.0 } };
+    /// network.split_edge(1, SplitPoint::Offset(1.0), new_node);
+    ///
+    /// let mapping = network.contract_pass_through_nodes();
+    /// assert_eq!(mapping.nodes[&3], NetworkPosition { edge_id: 1, offset: 1.0 });
+    /// assert_eq!(network.get_edge_by_id(1).unwrap().length, 2.0);
+    /// ```
+    pub fn contract_pass_through_nodes(&mut self) -> ContractionMapping<T> {
+        let mut mapping = ContractionMapping {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        };
+
+        let mut node_ids: Vec<NodeId> = self
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .collect();
+        node_ids.sort_unstable();
+        let pass_through: HashSet<NodeId> = node_ids
+            .iter()
+            .copied()
+            .filter(|node_id| self.is_pass_through(*node_id))
+            .collect();
+
+        let mut visited: HashSet<EdgeId> = HashSet::new();
+        let mut chains = Vec::new();
+        for start in node_ids.iter().filter(|id| !pass_through.contains(id)) {
+            for edge in self.incident_edges(*start) {
+                if !visited.insert(edge.id) {
+                    continue;
+                }
+                let mut current = if edge.source == *start {
+                    edge.target
+                } else {
+                    edge.source
+                };
+                let mut chain = Chain {
+                    start: *start,
+                    end: current,
+                    edges: vec![(edge.clone(), edge.source != *start)],
+                    nodes: vec![],
+                };
+                while pass_through.contains(&current) {
+                    let last_id = chain.edges.last().unwrap().0.id;
+                    let Some(next) = self
+                        .incident_edges(current)
+                        .into_iter()
+                        .find(|edge| edge.id != last_id)
+                    else {
+                        break;
+                    };
+                    visited.insert(next.id);
+                    chain.nodes.push(current);
+                    let reversed = next.source != current;
+                    current = if reversed { next.source } else { next.target };
+                    chain.edges.push((next, reversed));
+                }
+                chain.end = current;
+                if chain.edges.len() > 1 {
+                    chains.push(chain);
+                }
+            }
+        }
+
+        for chain in chains {
+            self.contract_chain(chain, &mut mapping);
+        }
+        mapping
+    }
+
+    fn incident_edges(&self, node_id: NodeId) -> Vec<TransitEdge<T>> {
+        let mut edges: Vec<TransitEdge<T>> = self
+            .physical_graph
+            .id_to_index(node_id)
+            .map(|index| {
+                self.physical_graph
+                    .graph
+                    .edges(*index)
+                    .map(|edge| edge.weight().clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        edges.sort_by_key(|edge| edge.id);
+        edges
+    }
+
+    fn is_pass_through(&self, node_id: NodeId) -> bool {
+        if self
+            .node_tags
+            .get(&node_id)
+            .is_some_and(|tags| !tags.is_empty())
+        {
+            return false;
+        }
+
+        let edges = self.incident_edges(node_id);
+        let neighbor = |edge: &TransitEdge<T>| {
+            if edge.source == node_id {
+                edge.target
+            } else {
+                edge.source
+            }
+        };
+        if edges.len() != 2
+            || edges.iter().any(|edge| edge.source == edge.target)
+            || neighbor(&edges[0]) == neighbor(&edges[1])
+            || self.speed_limit(edges[0].id) != self.speed_limit(edges[1].id)
+        {
+            return false;
+        }
+
+        let Some(&(topo_node1, topo_node2)) = self.topology_graph.id_to_index(node_id) else {
+            return false;
+        };
+        [topo_node1, topo_node2].iter().all(|topo_node| {
+            let edge_ids = |direction| -> Vec<EdgeId> {
+                self.topology_graph
+                    .graph
+                    .edges_directed(*topo_node, direction)
+                    .map(|edge| edge.weight().edge_id)
+                    .collect()
+            };
+            let incoming = edge_ids(Direction::Incoming);
+            let outgoing = edge_ids(Direction::Outgoing);
+            incoming.len() == 1 && outgoing.len() == 1 && incoming[0] != outgoing[0]
+        })
+    }
+
+    /// Returns the endpoints of the `TopoEdge` of the given edge that leaves or enters the given node.
+    fn topo_endpoints(
+        &self,
+        edge_id: EdgeId,
+        node_id: NodeId,
+        direction: Direction,
+    ) -> Option<(NodeIndex, NodeIndex)> {
+        self.topology_graph
+            .find_topo_edges(edge_id)
+            .into_iter()
+            .filter_map(|index| self.topology_graph.graph.edge_endpoints(index))
+            .find(|(source, target)| {
+                let endpoint = match direction {
+                    Direction::Outgoing => source,
+                    Direction::Incoming => target,
+                };
+                self.topology_graph.index_to_id(*endpoint) == Some(&node_id)
+            })
+    }
+
+    fn contract_chain(&mut self, chain: Chain<T>, mapping: &mut ContractionMapping<T>) {
+        let first_id = chain.edges.first().unwrap().0.id;
+        let last_id = chain.edges.last().unwrap().0.id;
+        let speed_limit = self.speed_limit(first_id);
+
+        let forward_start = self.topo_endpoints(first_id, chain.start, Direction::Outgoing);
+        let forward_end = self.topo_endpoints(last_id, chain.end, Direction::Incoming);
+        let backward_start = self.topo_endpoints(last_id, chain.end, Direction::Outgoing);
+        let backward_end = self.topo_endpoints(first_id, chain.start, Direction::Incoming);
+        let (Some(forward_start), Some(forward_end), Some(backward_start), Some(backward_end)) =
+            (forward_start, forward_end, backward_start, backward_end)
+        else {
+            return;
+        };
+
+        let mut coords: Vec<Coord<T>> = Vec::new();
+        let mut length = T::zero();
+        for (i, (edge, reversed)) in chain.edges.iter().enumerate() {
+            let mut path = edge.path.0.clone();
+            if *reversed {
+                path.reverse();
+            }
+            if coords.last().is_some() && coords.last() == path.first() {
+                path.remove(0);
+            }
+            coords.extend(path);
+
+            mapping.edges.insert(
+                edge.id,
+                EdgeMapping {
+                    edge_id: first_id,
+                    offset: length,
+                    reversed: *reversed,
+                },
+            );
+            length = length + edge.length;
+            if let Some(node_id) = chain.nodes.get(i) {
+                mapping.nodes.insert(
+                    *node_id,
+                    NetworkPosition {
+                        edge_id: first_id,
+                        offset: length,
+                    },
+                );
+            }
+        }
+
+        for (edge, _) in &chain.edges {
+            self.remove_edge(edge.id);
+        }
+        for node_id in &chain.nodes {
+            self.remove_node(*node_id);
+        }
+
+        self.physical_graph.add_transit_edge(TransitEdge {
+            id: first_id,
+            source: chain.start,
+            target: chain.end,
+            length,
+            path: LineString(coords),
+        });
+        self.topology_graph
+            .add_topo_edge(first_id, forward_start.0, forward_end.1);
+        self.topology_graph
+            .add_topo_edge(first_id, backward_start.0, backward_end.1);
+        if let Some(limit) = speed_limit {
+            self.set_speed_limit(first_id, limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::ShortestPath,
+        core::{Accessability, NodeTag, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::coord;
+
+    fn add_nodes(network: &mut TransitNetwork<Coord, f64>, count: u64) {
+        for id in 0..count {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: id as f64, y: 0.0 },
+            });
+        }
+    }
+
+    fn create_edge(id: EdgeId, source: NodeId, target: NodeId) -> TransitEdge<f64> {
+        TransitEdge {
+            id,
+            source,
+            target,
+            length: 1.0,
+            path: LineString(vec![
+                coord! { x: source as f64, y: 0.0 },
+                coord! { x: target as f64, y: 0.0 },
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_contract_chain() {
+        let mut network = TransitNetwork::new();
+        add_nodes(&mut network, 5);
+        network.add_edge(create_edge(10, 0, 1));
+        network.add_edge_with_accessibility(
+            create_edge(11, 1, 2),
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            create_edge(12, 2, 3),
+            Accessability::ReachableNodes(vec![1]),
+        );
+        network.add_edge_with_accessibility(
+            create_edge(13, 3, 4),
+            Accessability::ReachableNodes(vec![2]),
+        );
+        network.tag_node(2, NodeTag::Station);
+        assert_eq!(network.find_shortest_path(0, 4), Some(vec![0, 1, 2, 3, 4]));
+
+        let mapping = network.contract_pass_through_nodes();
+
+        assert_eq!(network.physical_graph.graph.node_count(), 3);
+        assert_eq!(network.physical_graph.graph.edge_count(), 2);
+        assert_eq!(network.topology_graph.graph.edge_count(), 4);
+
+        let edge = network.get_edge_by_id(10).unwrap();
+        assert_eq!((edge.source, edge.target, edge.length), (0, 2, 2.0));
+        assert_eq!(
+            edge.path,
+            LineString(vec![
+                coord! { x: 0.0, y: 0.0 },
+                coord! { x: 1.0, y: 0.0 },
+                coord! { x: 2.0, y: 0.0 }
+            ])
+        );
+        let edge = network.get_edge_by_id(12).unwrap();
+        assert_eq!((edge.source, edge.target, edge.length), (2, 4, 2.0));
+
+        assert_eq!(
+            mapping.nodes[&1],
+            NetworkPosition {
+                edge_id: 10,
+                offset: 1.0
+            }
+        );
+        assert_eq!(
+            mapping.nodes[&3],
+            NetworkPosition {
+                edge_id: 12,
+                offset: 1.0
+            }
+        );
+        assert_eq!(
+            mapping.edges[&11],
+            EdgeMapping {
+                edge_id: 10,
+                offset: 1.0,
+                reversed: false
+            }
+        );
+        assert_eq!(mapping.edges.len(), 4);
+
+        assert_eq!(network.find_shortest_path(0, 4), Some(vec![0, 2, 4]));
+        assert_eq!(network.find_shortest_path(4, 0), Some(vec![4, 2, 0]));
+    }
+
+    #[test]
+    fn test_contract_reversed_edge() {
+        let mut network = TransitNetwork::new();
+        add_nodes(&mut network, 3);
+        network.add_edge(create_edge(1, 0, 1));
+        network
+            .physical_graph
+            .add_transit_edge(create_edge(2, 2, 1));
+        let node1 = *network.topology_graph.id_to_index(1).unwrap();
+        let node2 = *network.topology_graph.id_to_index(2).unwrap();
+        network.topology_graph.add_topo_edge(2, node1.0, node2.0);
+        network.topology_graph.add_topo_edge(2, node2.1, node1.1);
+        assert_eq!(network.find_shortest_path(0, 2), Some(vec![0, 1, 2]));
+
+        let mapping = network.contract_pass_through_nodes();
+
+        let edge = network.get_edge_by_id(1).unwrap();
+        assert_eq!((edge.source, edge.target, edge.length), (0, 2, 2.0));
+        assert_eq!(edge.path.0.len(), 3);
+        assert!(mapping.edges[&2].reversed);
+        assert_eq!(network.find_shortest_path(0, 2), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn test_keep_switches_and_dead_ends() {
+        let mut network = TransitNetwork::new();
+        add_nodes(&mut network, 4);
+        network.add_edge(create_edge(1, 0, 1));
+        network.add_edge_with_accessibility(
+            create_edge(2, 1, 2),
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            create_edge(3, 1, 3),
+            Accessability::ReachableNodes(vec![0]),
+        );
+
+        let mapping = network.contract_pass_through_nodes();
+
+        assert!(mapping.nodes.is_empty());
+        assert_eq!(network.physical_graph.graph.edge_count(), 3);
+        assert_eq!(network.topology_graph.graph.edge_count(), 6);
+    }
+
+    #[test]
+    fn test_keep_reversal_points() {
+        let mut network = TransitNetwork::new();
+        add_nodes(&mut network, 3);
+        network.add_edge(create_edge(1, 0, 1));
+        network.add_edge_with_accessibility(
+            create_edge(2, 1, 2),
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.tag_node(1, NodeTag::ReversalPoint);
+
+        let mapping = network.contract_pass_through_nodes();
+
+        assert!(mapping.edges.is_empty());
+        assert_eq!(network.physical_graph.graph.node_count(), 3);
+    }
+
+    #[test]
+    fn test_contract_speed_limits() {
+        let mut network = TransitNetwork::new();
+        add_nodes(&mut network, 4);
+        network.add_edge(create_edge(1, 0, 1));
+        for (id, source) in [(2, 1), (3, 2)] {
+            network.add_edge_with_accessibility(
+                create_edge(id, source, source + 1),
+                Accessability::ReachableNodes(vec![source - 1]),
+            );
+        }
+        network.set_speed_limit(1, 10.0);
+        network.set_speed_limit(2, 10.0);
+        network.set_speed_limit(3, 5.0);
+
+        let mapping = network.contract_pass_through_nodes();
+
+        assert_eq!(mapping.nodes.keys().collect::<Vec<_>>(), vec![&1]);
+        assert_eq!(network.get_edge_by_id(1).unwrap().target, 2);
+        assert_eq!(network.speed_limit(1), Some(10.0));
+        assert_eq!(network.speed_limit(2), None);
+        assert_eq!(network.speed_limit(3), Some(5.0));
+    }
+}
@@ -0,0 +1,454 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    iter::Sum,
+};
+
+use geo::{Coord, GeoFloat, LineString};
+use num_traits::FromPrimitive;
+use petgraph::{
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    Direction,
+};
+
+use crate::{
+    core::{measure, EdgeId, NodeId, TransitEdge, TransitNode},
+    operations::TransitNetworkModifier,
+};
+
+use super::TransitNetwork;
+
+/// Enum `NodeCollision` defining how nodes of a merged network are treated if their ID is already in use.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NodeCollision {
+    /// The node is added with a new, unused ID.
+    Remap,
+    /// The node is joined with the existing node of the same ID.
+    Join,
+}
+
+/// Policy for `TransitNetwork::merge`.
+///
+/// # Fields
+///
+/// * `node_collision: NodeCollision` - How nodes with an ID that is already in use are treated.
+/// * `snap_tolerance: Option<T>` - If set, nodes closer than this distance to an existing node are joined with it.
+///   The distance is measured in the `Crs` of the networks like `TransitNetwork::path_length` does.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MergePolicy<T> {
+    /// How nodes with an ID that is already in use are treated.
+    pub node_collision: NodeCollision,
+    /// If set, nodes closer than this distance to an existing node are joined with it.
+    pub snap_tolerance: Option<T>,
+}
+
+impl<T> Default for MergePolicy<T> {
+    fn default() -> Self {
+        Self {
+            node_collision: NodeCollision::Remap,
+            snap_tolerance: None,
+        }
+    }
+}
+
+/// Mapping from the IDs of a merged network to the IDs in the combined network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeMapping {
+    /// The new ID of every node of the merged network.
+    pub nodes: HashMap<NodeId, NodeId>,
+    /// The new ID of every edge of the merged network.
+    pub edges: HashMap<EdgeId, EdgeId>,
+}
+
+impl<R, T> TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: GeoFloat + FromPrimitive + Sum,
+{
+    /// Merges another network into this network.
+    ///
+    /// Nodes and edges of `other` whose IDs are already in use get the next free IDs, unless nodes are joined with
+    /// existing nodes according to the `policy`. Where nodes are joined, the paths of the connected edges are snapped
+    /// to the existing node's location and their lengths change by as much as their paths, measured in the `Crs` of
+    /// this network. The `TopoNode`s are paired so that movements arriving from one network can continue into the
+    /// other.
+    ///
+    /// An existing node is joined with at most one node of `other`. If several nodes snap onto the same existing
+    /// node, only the nearest is joined and the others are added as new nodes, so that no edge collapses into a loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The network to be merged into this network.
+    /// * `policy` - The `MergePolicy` defining how nodes are joined.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<MergeMapping>` - The new IDs of all nodes and edges of `other`, or `None` without any change if the
+    ///   networks have a different `Crs`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut west: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// west.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// west.add_node(TransitNode { id: 2, location: coord! { x: 1.0, y: 0.0 } });
+    /// west.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+    /// });
+    /// let east = west.clone();
+    ///
+    /// let policy = MergePolicy { node_collision: NodeCollision::Remap, snap_tolerance: None };
+    /// let mapping = west.merge(&east, &policy).unwrap();
+    /// assert_eq!(mapping.nodes[&1], 3);
+    /// assert_eq!(mapping.edges[&1], 2);
+    /// ```
+    pub fn merge(
+        &mut self,
+        other: &TransitNetwork<R, T>,
+        policy: &MergePolicy<T>,
+    ) -> Option<MergeMapping> {
+        if self.crs != other.crs {
+            return None;
+        }
+        let mut mapping = MergeMapping::default();
+        let existing: Vec<TransitNode<R>> =
+            self.physical_graph.graph.node_weights().copied().collect();
+        let existing_ids: HashSet<NodeId> = existing.iter().map(|node| node.id).collect();
+        let mut next_node_id = self.next_node_id().max(other.next_node_id());
+        let mut next_edge_id = self.next_edge_id().max(other.next_edge_id());
+
+        let mut other_nodes: Vec<TransitNode<R>> =
+            other.physical_graph.graph.node_weights().copied().collect();
+        other_nodes.sort_by_key(|node| node.id);
+
+        let mut joined: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut snaps: HashMap<NodeId, (T, NodeId)> = HashMap::new();
+        for node in &other_nodes {
+            if policy.node_collision == NodeCollision::Join && existing_ids.contains(&node.id) {
+                joined.insert(node.id, node.id);
+            } else if let Some(tolerance) = policy.snap_tolerance {
+                let location: Coord<T> = node.location.into();
+                let nearest = existing
+                    .iter()
+                    .map(|candidate| {
+                        let line = LineString(vec![location, candidate.location.into()]);
+                        (measure(self.crs, &line), candidate.id)
+                    })
+                    .filter(|(distance, _)| *distance <= tolerance)
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+                if let Some((distance, id)) = nearest {
+                    let closer = snaps.get(&id).is_none_or(|(known, _)| distance < *known);
+                    if closer {
+                        snaps.insert(id, (distance, node.id));
+                    }
+                }
+            }
+        }
+        let claimed: HashSet<NodeId> = joined.values().copied().collect();
+        for (id, (_, node_id)) in snaps {
+            if !claimed.contains(&id) {
+                joined.insert(node_id, id);
+            }
+        }
+
+        let mut toponodes: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for node in &other_nodes {
+            let Some(&(other1, other2)) = other.topology_graph.id_to_index(node.id) else {
+                continue;
+            };
+            let (node_id, pairs) = match joined.get(&node.id) {
+                Some(&node_id) => {
+                    let (own1, own2) = *self.topology_graph.id_to_index(node_id).unwrap();
+                    let straight = self.pairing_score(own1, other, other1)
+                        + self.pairing_score(own2, other, other2);
+                    let crossed = self.pairing_score(own1, other, other2)
+                        + self.pairing_score(own2, other, other1);
+                    if crossed > straight {
+                        (node_id, [(other1, own2), (other2, own1)])
+                    } else {
+                        (node_id, [(other1, own1), (other2, own2)])
+                    }
+                }
+                None => {
+                    let node_id = if existing_ids.contains(&node.id) {
+                        next_node_id += 1;
+                        next_node_id - 1
+                    } else {
+                        node.id
+                    };
+                    self.add_node(TransitNode {
+                        id: node_id,
+                        location: node.location,
+                    });
+                    let (own1, own2) = *self.topology_graph.id_to_index(node_id).unwrap();
+                    (node_id, [(other1, own1), (other2, own2)])
+                }
+            };
+            toponodes.extend(pairs);
+            mapping.nodes.insert(node.id, node_id);
+            for tag in other.node_tags.get(&node.id).into_iter().flatten() {
+                self.tag_node(node_id, *tag);
+            }
+        }
+
+        let existing_edge_ids: HashSet<EdgeId> = self
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| edge.id)
+            .collect();
+        let mut other_edges: Vec<&TransitEdge<T>> =
+            other.physical_graph.graph.edge_weights().collect();
+        other_edges.sort_by_key(|edge| edge.id);
+        for edge in other_edges {
+            let edge_id = if existing_edge_ids.contains(&edge.id) {
+                next_edge_id += 1;
+                next_edge_id - 1
+            } else {
+                edge.id
+            };
+            let mut path = edge.path.clone();
+            if joined.contains_key(&edge.source) {
+                if let (Some(first), Some(location)) = (
+                    path.0.first_mut(),
+                    self.node_location(mapping.nodes[&edge.source]),
+                ) {
+                    *first = location;
+                }
+            }
+            if joined.contains_key(&edge.target) {
+                if let (Some(last), Some(location)) = (
+                    path.0.last_mut(),
+                    self.node_location(mapping.nodes[&edge.target]),
+                ) {
+                    *last = location;
+                }
+            }
+            let length = if path == edge.path {
+                edge.length
+            } else {
+                let snapped =
+                    edge.length + measure(self.crs, &path) - measure(self.crs, &edge.path);
+                snapped.max(T::zero())
+            };
+            self.physical_graph.add_transit_edge(TransitEdge {
+                id: edge_id,
+                source: mapping.nodes[&edge.source],
+                target: mapping.nodes[&edge.target],
+                length,
+                path,
+            });
+            if let Some(limit) = other.speed_limit(edge.id) {
+                self.set_speed_limit(edge_id, limit);
+            }
+            mapping.edges.insert(edge.id, edge_id);
+        }
+
+        for edge in other.topology_graph.graph.edge_references() {
+            let (Some(from), Some(to)) =
+                (toponodes.get(&edge.source()), toponodes.get(&edge.target()))
+            else {
+                continue;
+            };
+            if let Some(edge_id) = mapping.edges.get(&edge.weight().edge_id) {
+                self.topology_graph.add_topo_edge(*edge_id, *from, *to);
+            }
+        }
+
+        Some(mapping)
+    }
+
+    fn node_location(&self, node_id: NodeId) -> Option<Coord<T>> {
+        let index = self.physical_graph.id_to_index(node_id)?;
+        Some(self.physical_graph.graph[*index].location.into())
+    }
+
+    /// Rates joining an own `TopoNode` with a `TopoNode` of another network.
+    /// Each way of passing from one network into the other through the joined `TopoNode` counts one point.
+    fn pairing_score(&self, own: NodeIndex, other: &TransitNetwork<R, T>, theirs: NodeIndex) -> u8 {
+        let has_edges = |network: &TransitNetwork<R, T>, index, direction| {
+            network
+                .topology_graph
+                .graph
+                .edges_directed(index, direction)
+                .next()
+                .is_some()
+        };
+        let arrives_here = has_edges(self, own, Direction::Incoming);
+        let departs_here = has_edges(self, own, Direction::Outgoing);
+        let arrives_there = has_edges(other, theirs, Direction::Incoming);
+        let departs_there = has_edges(other, theirs, Direction::Outgoing);
+        u8::from(arrives_here && departs_there) + u8::from(arrives_there && departs_here)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::ShortestPath,
+        core::{Crs, NodeTag},
+    };
+    use geo::{coord, LineString};
+
+    fn create_network(offset: f64) -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        network.add_node(TransitNode {
+            id: 1,
+            location: coord! { x: offset, y: 0.0 },
+        });
+        network.add_node(TransitNode {
+            id: 2,
+            location: coord! { x: offset + 1.0, y: 0.0 },
+        });
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 1.0,
+            path: LineString(vec![
+                coord! { x: offset, y: 0.0 },
+                coord! { x: offset + 1.0, y: 0.0 },
+            ]),
+        });
+        network
+    }
+
+    #[test]
+    fn test_merge_remap() {
+        let mut network = create_network(0.0);
+        let other = create_network(5.0);
+
+        let mapping = network.merge(&other, &MergePolicy::default()).unwrap();
+
+        assert_eq!(mapping.nodes, HashMap::from([(1, 3), (2, 4)]));
+        assert_eq!(mapping.edges, HashMap::from([(1, 2)]));
+        assert_eq!(network.physical_graph.graph.node_count(), 4);
+        assert_eq!(network.physical_graph.graph.edge_count(), 2);
+        assert_eq!(network.topology_graph.graph.edge_count(), 4);
+        assert_eq!(network.find_shortest_path(3, 4), Some(vec![3, 4]));
+        assert_eq!(network.find_shortest_path(1, 4), None);
+    }
+
+    #[test]
+    fn test_merge_snap() {
+        let mut network = create_network(0.0);
+        let mut other = create_network(1.05);
+        other.tag_node(2, NodeTag::Station);
+
+        let policy = MergePolicy {
+            node_collision: NodeCollision::Remap,
+            snap_tolerance: Some(0.1),
+        };
+        let mapping = network.merge(&other, &policy).unwrap();
+
+        assert_eq!(mapping.nodes, HashMap::from([(1, 2), (2, 3)]));
+        assert_eq!(network.physical_graph.graph.node_count(), 3);
+        assert_eq!(
+            network.get_edge_by_id(2).unwrap().path,
+            LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 2.05, y: 0.0 }])
+        );
+        assert!((network.get_edge_by_id(2).unwrap().length - 1.05).abs() < 1e-9);
+        assert!(network.has_tag(3, NodeTag::Station));
+        assert_eq!(network.find_shortest_path(1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(network.find_shortest_path(3, 1), Some(vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn test_merge_snap_geographic() {
+        let mut network = create_network(0.0).with_crs(Crs::Wgs84);
+        let other = create_network(1.00005).with_crs(Crs::Wgs84);
+
+        let policy = MergePolicy {
+            node_collision: NodeCollision::Remap,
+            snap_tolerance: Some(10.0),
+        };
+        let mapping = network.merge(&other, &policy).unwrap();
+
+        // Node 1 of `other` lies about 5.6 m from node 2, node 2 about 111 km from it.
+        assert_eq!(mapping.nodes, HashMap::from([(1, 2), (2, 3)]));
+    }
+
+    #[test]
+    fn test_merge_snap_once() {
+        let mut network = create_network(0.0);
+        let mut other = TransitNetwork::new();
+        for (id, x) in [(1, 0.95), (2, 1.02)] {
+            other.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 0.0 },
+            });
+        }
+        other.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 0.07,
+            path: LineString(vec![coord! { x: 0.95, y: 0.0 }, coord! { x: 1.02, y: 0.0 }]),
+        });
+
+        let policy = MergePolicy {
+            node_collision: NodeCollision::Remap,
+            snap_tolerance: Some(0.1),
+        };
+        let mapping = network.merge(&other, &policy).unwrap();
+
+        assert_eq!(mapping.nodes, HashMap::from([(1, 3), (2, 2)]));
+        let edge = network.get_edge_by_id(2).unwrap();
+        assert_eq!((edge.source, edge.target), (3, 2));
+    }
+
+    #[test]
+    fn test_merge_different_crs() {
+        let mut network = create_network(0.0).with_crs(Crs::Wgs84);
+        let other = create_network(5.0).with_crs(Crs::WebMercator);
+
+        assert_eq!(network.merge(&other, &MergePolicy::default()), None);
+        assert_eq!(
+            network.merge(&create_network(5.0), &MergePolicy::default()),
+            None
+        );
+        assert_eq!(network.physical_graph.graph.node_count(), 2);
+    }
+
+    #[test]
+    fn test_merge_join_shared_nodes() {
+        let mut network = create_network(0.0);
+        let mut other = TransitNetwork::new();
+        other.add_node(TransitNode {
+            id: 2,
+            location: coord! { x: 1.0, y: 0.0 },
+        });
+        other.add_node(TransitNode {
+            id: 3,
+            location: coord! { x: 2.0, y: 0.0 },
+        });
+        other.add_edge(TransitEdge {
+            id: 1,
+            source: 2,
+            target: 3,
+            length: 1.0,
+            path: LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]),
+        });
+
+        let policy = MergePolicy {
+            node_collision: NodeCollision::Join,
+            snap_tolerance: None,
+        };
+        let mapping = network.merge(&other, &policy).unwrap();
+
+        assert_eq!(mapping.nodes, HashMap::from([(2, 2), (3, 3)]));
+        assert_eq!(mapping.edges, HashMap::from([(1, 2)]));
+        assert_eq!(network.physical_graph.graph.node_count(), 3);
+        assert_eq!(network.topology_graph.graph.node_count(), 6);
+        assert_eq!(network.find_shortest_path(1, 3), Some(vec![1, 2, 3]));
+    }
+}
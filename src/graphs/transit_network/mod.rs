@@ -1,11 +1,26 @@
 use super::{PhysicalGraph, TopologyGraph};
 use crate::{
-    core::{Accessability, EdgeId, NodeId, TransitEdge, TransitNode},
-    operations::TransitNetworkModifier,
+    core::{Accessability, Crs, EdgeId, NodeId, NodeTag, TransitEdge, TransitNode},
+    operations::{TransitNetworkModifier, TransitNetworkRemover},
 };
 use geo::CoordNum;
+use std::collections::{HashMap, HashSet};
 
+mod contract;
+mod merge;
 pub mod repair;
+mod reproject;
+mod split;
+mod stats;
+mod subnetwork;
+mod timetable;
+
+pub use contract::{ContractionMapping, EdgeMapping};
+pub use merge::{MergeMapping, MergePolicy, NodeCollision};
+pub use split::SplitPoint;
+pub use stats::{NetworkStats, TopologyIssue};
+pub use subnetwork::{BoundaryMode, SubnetworkFilter};
+pub use timetable::{TimetableIssue, TimetableOptions};
 
 /// Represents a transit network as a graph with transit nodes and edges.
 ///
@@ -23,12 +38,21 @@ pub mod repair;
 ///
 /// * `physical_graph: PhysicalGraph<R, T>` - The physical graph representing the transit network.
 /// * `topology_graph: TopologyGraph` - The topological graph representing the transit network.
+/// * `node_tags: HashMap<NodeId, HashSet<NodeTag>>` - The tags describing the operational role of nodes.
+/// * `crs: Option<Crs>` - The coordinate reference system of node locations and edge paths, if known.
+/// * `speed_limits: HashMap<EdgeId, f64>` - The maximum permitted speed on edges, in units of the edge length per second.
 #[derive(Debug, Clone)]
 pub struct TransitNetwork<R: Copy, T: CoordNum> {
     /// The physical graph representing the transit network.
     pub physical_graph: PhysicalGraph<R, T>,
     /// The topological graph representing the transit network.
     pub topology_graph: TopologyGraph,
+    /// The tags describing the operational role of nodes.
+    pub node_tags: HashMap<NodeId, HashSet<NodeTag>>,
+    /// The coordinate reference system of node locations and edge paths, if known.
+    pub crs: Option<Crs>,
+    /// The maximum permitted speed on edges, in units of the edge length per second.
+    pub speed_limits: HashMap<EdgeId, f64>,
 }
 
 impl<R: Copy, T: CoordNum> PartialEq for TransitNetwork<R, T> {
@@ -48,6 +72,9 @@ impl<R: Copy, T: CoordNum> TransitNetwork<R, T> {
         TransitNetwork {
             physical_graph: PhysicalGraph::new(),
             topology_graph: TopologyGraph::new(),
+            node_tags: HashMap::new(),
+            crs: None,
+            speed_limits: HashMap::new(),
         }
     }
 
@@ -55,6 +82,48 @@ impl<R: Copy, T: CoordNum> TransitNetwork<R, T> {
     pub fn get_edge_by_id(&self, edge_id: EdgeId) -> Option<&TransitEdge<T>> {
         self.physical_graph.get_transit_edge_by_id(edge_id)
     }
+
+    /// Adds a `NodeTag` to the node with the given ID.
+    pub fn tag_node(&mut self, node_id: NodeId, tag: NodeTag) {
+        self.node_tags.entry(node_id).or_default().insert(tag);
+    }
+
+    /// Returns `true` if the node with the given ID carries the given `NodeTag`.
+    pub fn has_tag(&self, node_id: NodeId, tag: NodeTag) -> bool {
+        self.node_tags
+            .get(&node_id)
+            .is_some_and(|tags| tags.contains(&tag))
+    }
+
+    /// Sets the maximum permitted speed on the edge with the given ID, in units of the edge length per second.
+    pub fn set_speed_limit(&mut self, edge_id: EdgeId, limit: f64) {
+        self.speed_limits.insert(edge_id, limit);
+    }
+
+    /// Returns the maximum permitted speed on the edge with the given ID, or `None` if it is unrestricted.
+    pub fn speed_limit(&self, edge_id: EdgeId) -> Option<f64> {
+        self.speed_limits.get(&edge_id).copied()
+    }
+
+    /// Returns the smallest `NodeId` that is greater than every `NodeId` in the network.
+    pub fn next_node_id(&self) -> NodeId {
+        self.physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the smallest `EdgeId` that is greater than every `EdgeId` in the network.
+    pub fn next_edge_id(&self) -> EdgeId {
+        self.physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| edge.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 impl<R: Copy, T: CoordNum> Default for TransitNetwork<R, T> {
@@ -105,10 +174,33 @@ impl<R: Copy, T: CoordNum> TransitNetworkModifier<R, T> for TransitNetwork<R, T>
     }
 }
 
+/// Implementation of `TransitNetworkRemover` trait for `TransitNetwork`.
+///
+/// This implementation removes the nodes and edges from the physical and the topology graph together with their
+/// speed limits and tags.
+impl<R: Copy, T: CoordNum> TransitNetworkRemover<R, T> for TransitNetwork<R, T> {
+    fn remove_node(&mut self, node_id: NodeId) -> Option<TransitNode<R>> {
+        if let Some(index) = self.physical_graph.id_to_index(node_id) {
+            for edge in self.physical_graph.graph.edges(*index) {
+                self.speed_limits.remove(&edge.weight().id);
+            }
+        }
+        self.topology_graph.remove_node(node_id);
+        self.node_tags.remove(&node_id);
+        self.physical_graph.remove_transit_node(node_id)
+    }
+
+    fn remove_edge(&mut self, edge_id: EdgeId) -> Option<TransitEdge<T>> {
+        self.topology_graph.remove_edge(edge_id);
+        self.speed_limits.remove(&edge_id);
+        self.physical_graph.remove_transit_edge(edge_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use geo::{coord, point, LineString};
+    use geo::{coord, point, Coord, LineString};
     use petgraph::visit::IntoEdgeReferences;
 
     #[test]
@@ -153,6 +245,10 @@ mod tests {
         // Check that the topology graph was populated correctly
         assert_eq!(network.topology_graph.graph.node_count(), 4);
         assert_eq!(network.topology_graph.graph.edge_count(), 2);
+
+        // Check that the next free IDs follow the largest IDs in use
+        assert_eq!(network.next_node_id(), 3);
+        assert_eq!(network.next_edge_id(), 2);
     }
 
     #[test]
@@ -333,5 +429,101 @@ mod tests {
         assert_eq!(network.physical_graph.graph.edge_count(), 0);
         assert_eq!(network.topology_graph.graph.node_count(), 0);
         assert_eq!(network.topology_graph.graph.edge_count(), 0);
+        assert_eq!(network.next_node_id(), 0);
+        assert_eq!(network.next_edge_id(), 0);
+    }
+
+    #[test]
+    fn test_node_tags() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        network.add_node(TransitNode {
+            id: 1,
+            location: coord! {x: 0.0, y: 0.0},
+        });
+
+        network.tag_node(1, NodeTag::Station);
+
+        assert!(network.has_tag(1, NodeTag::Station));
+        assert!(!network.has_tag(1, NodeTag::ReversalPoint));
+        assert!(!network.has_tag(2, NodeTag::Station));
+
+        network.remove_node(1);
+        assert!(!network.has_tag(1, NodeTag::Station));
+    }
+
+    #[test]
+    fn test_speed_limits() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        for (id, x) in [(1, 0.0), (2, 1.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! {x: x, y: 0.0},
+            });
+        }
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 1.0,
+            path: LineString(vec![coord! {x: 0.0, y: 0.0}, coord! {x: 1.0, y: 0.0}]),
+        });
+
+        network.set_speed_limit(1, 20.0);
+
+        assert_eq!(network.speed_limit(1), Some(20.0));
+        assert_eq!(network.speed_limit(2), None);
+
+        network.remove_edge(1);
+        assert_eq!(network.speed_limit(1), None);
+
+        network.add_edge(TransitEdge {
+            id: 2,
+            source: 1,
+            target: 2,
+            length: 1.0,
+            path: LineString(vec![coord! {x: 0.0, y: 0.0}, coord! {x: 1.0, y: 0.0}]),
+        });
+        network.set_speed_limit(2, 5.0);
+        network.remove_node(2);
+        assert!(network.speed_limits.is_empty());
+    }
+
+    #[test]
+    fn test_remove_node_and_edge() {
+        let mut network = TransitNetwork::new();
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 2.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! {x: x, y: 0.0},
+            });
+        }
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 1.0,
+            path: LineString(vec![coord! {x: 0.0, y: 0.0}, coord! {x: 1.0, y: 0.0}]),
+        });
+        network.add_edge(TransitEdge {
+            id: 2,
+            source: 2,
+            target: 3,
+            length: 1.0,
+            path: LineString(vec![coord! {x: 1.0, y: 0.0}, coord! {x: 2.0, y: 0.0}]),
+        });
+
+        assert_eq!(network.remove_edge(1).map(|edge| edge.id), Some(1));
+        assert_eq!(network.physical_graph.graph.edge_count(), 1);
+        assert_eq!(network.topology_graph.graph.edge_count(), 2);
+
+        assert_eq!(network.remove_node(1).map(|node| node.id), Some(1));
+        assert_eq!(network.physical_graph.graph.node_count(), 2);
+        assert_eq!(network.topology_graph.graph.node_count(), 4);
+        assert!(network.physical_graph.id_to_index(3).is_some());
+
+        assert_eq!(network.remove_node(3).map(|node| node.id), Some(3));
+        assert_eq!(network.physical_graph.graph.edge_count(), 0);
+        assert_eq!(network.topology_graph.graph.edge_count(), 0);
+        assert!(network.remove_edge(2).is_none());
     }
 }
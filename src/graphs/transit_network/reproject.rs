@@ -0,0 +1,201 @@
+use std::iter::Sum;
+
+use geo::{Coord, CoordFloat, CoordNum, LineString};
+use num_traits::FromPrimitive;
+
+use crate::core::{measure, Crs};
+
+use super::TransitNetwork;
+
+impl<R: Copy, T: CoordNum> TransitNetwork<R, T> {
+    /// Tags the network with the coordinate reference system of its node locations and edge paths.
+    ///
+    /// The coordinates are not changed, use `reproject` to transform them into another system.
+    ///
+    /// # Arguments
+    ///
+    /// * `crs` - The `Crs` the coordinates are given in.
+    ///
+    /// # Returns
+    ///
+    /// * `TransitNetwork<R, T>` - The tagged network.
+    pub fn with_crs(mut self, crs: Crs) -> Self {
+        self.crs = Some(crs);
+        self
+    }
+}
+
+impl<R, T> TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>> + From<Coord<T>>,
+    T: CoordFloat + FromPrimitive + Sum,
+{
+    /// Transforms all node locations and edge paths into another coordinate reference system.
+    ///
+    /// The stored `length` of the edges is kept, as it describes the track rather than its coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - The `Crs` to transform to.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<TransitNetwork<R, T>>` - The transformed network tagged with `to`, or `None` if the network
+    ///   has no `Crs` or the transformation between the two systems is not supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new().with_crs(Crs::Wgs84);
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 9.0, y: 48.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 9.0, y: 48.01 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1112.0,
+    ///     path: LineString(vec![coord! { x: 9.0, y: 48.0 }, coord! { x: 9.0, y: 48.01 }]),
+    /// });
+    ///
+    /// let utm = network.reproject(Crs::from_epsg(32632)).unwrap();
+    /// let meters = utm.path_length(&utm.get_edge_by_id(1).unwrap().path);
+    /// assert!((meters - 1112.0).abs() < 1.0);
+    /// ```
+    pub fn reproject(&self, to: Crs) -> Option<TransitNetwork<R, T>> {
+        let from = self.crs?;
+        let transform = |coord: Coord<T>| -> Option<Coord<T>> {
+            let coord = Coord {
+                x: coord.x.to_f64()?,
+                y: coord.y.to_f64()?,
+            };
+            let coord = from.transform(coord, to)?;
+            Some(Coord {
+                x: T::from_f64(coord.x)?,
+                y: T::from_f64(coord.y)?,
+            })
+        };
+
+        let mut network = self.clone();
+        for node in network.physical_graph.graph.node_weights_mut() {
+            node.location = R::from(transform(node.location.into())?);
+        }
+        for edge in network.physical_graph.graph.edge_weights_mut() {
+            for coord in edge.path.0.iter_mut() {
+                *coord = transform(*coord)?;
+            }
+        }
+        network.crs = Some(to);
+        Some(network)
+    }
+
+    /// Measures the length of a path in the coordinate reference system of the network.
+    ///
+    /// Paths in a geographic `Crs` or in Web Mercator are measured with the haversine formula in meters,
+    /// all other paths with the Euclidean length in the units of their coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to measure.
+    ///
+    /// # Returns
+    ///
+    /// * `T` - The length of the path.
+    pub fn path_length(&self, path: &LineString<T>) -> T {
+        measure(self.crs, path)
+    }
+
+    /// Sets the `length` of every edge to the length of its path as measured by `path_length`.
+    pub fn recompute_lengths(&mut self) {
+        let crs = self.crs;
+        for edge in self.physical_graph.graph.edge_weights_mut() {
+            edge.length = measure(crs, &edge.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::coord;
+
+    fn network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let (a, b) = (coord! { x: 13.40, y: 52.52 }, coord! { x: 13.41, y: 52.52 });
+        network.add_node(TransitNode { id: 1, location: a });
+        network.add_node(TransitNode { id: 2, location: b });
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 0.0,
+            path: LineString(vec![a, coord! { x: 13.405, y: 52.521 }, b]),
+        });
+        network
+    }
+
+    #[test]
+    fn test_reproject() {
+        let network = network().with_crs(Crs::Wgs84);
+        let utm = Crs::utm_for(coord! { x: 13.4, y: 52.52 });
+
+        let projected = network.reproject(utm).unwrap();
+
+        assert_eq!(projected.crs, Some(utm));
+        let node = projected
+            .physical_graph
+            .graph
+            .node_weights()
+            .next()
+            .unwrap();
+        assert!(node.location.x > 390_000.0 && node.location.x < 395_000.0);
+        let path = &projected.get_edge_by_id(1).unwrap().path;
+        assert_eq!(path.0.first(), Some(&node.location));
+
+        let back = projected.reproject(Crs::Wgs84).unwrap();
+        let original = &network.get_edge_by_id(1).unwrap().path;
+        for (coord, expected) in back
+            .get_edge_by_id(1)
+            .unwrap()
+            .path
+            .0
+            .iter()
+            .zip(&original.0)
+        {
+            assert!((coord.x - expected.x).abs() < 1e-9 && (coord.y - expected.y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_reproject_unsupported() {
+        assert!(network().reproject(Crs::WebMercator).is_none());
+        assert!(network()
+            .with_crs(Crs::Other(31467))
+            .reproject(Crs::Wgs84)
+            .is_none());
+    }
+
+    #[test]
+    fn test_lengths_follow_crs() {
+        let mut geographic = network().with_crs(Crs::Wgs84);
+        geographic.recompute_lengths();
+        let haversine = geographic.get_edge_by_id(1).unwrap().length;
+        assert!(haversine > 600.0 && haversine < 800.0);
+
+        let mut projected = geographic
+            .reproject(Crs::utm_for(coord! { x: 13.4, y: 52.5 }))
+            .unwrap();
+        projected.recompute_lengths();
+        assert!((projected.get_edge_by_id(1).unwrap().length - haversine).abs() < 2.0);
+
+        let mut untagged = network();
+        untagged.recompute_lengths();
+        assert!(untagged.get_edge_by_id(1).unwrap().length < 0.1);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{iter::Sum, ops::AddAssign};
+
+use geo::{Coord, CoordNum, GeoFloat, LineLocatePoint, Point};
+
+use crate::{
+    algorithms::edge_split::split_path,
+    core::{EdgeId, TransitEdge, TransitNode},
+    operations::TransitNetworkModifier,
+};
+
+use super::TransitNetwork;
+
+/// Location at which `TransitNetwork::split_edge` cuts an edge.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SplitPoint<T: CoordNum> {
+    /// The point on the edge's path closest to the given coordinate.
+    Coordinate(Coord<T>),
+    /// The given offset along the edge, measured from its source in the unit of its `length`.
+    Offset(T),
+}
+
+impl<R: Copy, T: GeoFloat + AddAssign + Sum> TransitNetwork<R, T> {
+    /// Splits an edge in two and inserts a new node at the split point.
+    ///
+    /// The part from the original source to the new node keeps the ID of the original edge,
+    /// the part from the new node to the original target gets the next free `EdgeId`.
+    /// The `length` of the original edge is divided in proportion to the path geometry,
+    /// a speed limit of the original edge applies to both parts.
+    ///
+    /// In the topology graph, each `TopoEdge` of the original edge is replaced by two `TopoEdge`s
+    /// through one of the new node's `TopoNode`s, so that the new node continues straight through
+    /// and the connections at the original endpoints remain unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge_id` - The ID of the edge to be split.
+    /// * `at` - The location of the split point on the edge.
+    /// * `new_node` - The node to be inserted at the split point.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(EdgeId, EdgeId)>` - The IDs of the edges before and after the new node, or `None` if the edge
+    ///   does not exist, the node ID is already in use or the split point is not strictly inside the edge.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 2.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 2.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let new_node = TransitNode { id: 3, location: coord! { x: 0.5, y: 0.0 } };
+    /// let (first, second) = network.split_edge(1, SplitPoint::Offset(0.5), new_node).unwrap();
+    /// assert_eq!(network.get_edge_by_id(first).unwrap().length, 0.5);
+    /// assert_eq!(network.get_edge_by_id(second).unwrap().length, 1.5);
+    /// ```
+    pub fn split_edge(
+        &mut self,
+        edge_id: EdgeId,
+        at: SplitPoint<T>,
+        new_node: TransitNode<R>,
+    ) -> Option<(EdgeId, EdgeId)> {
+        let edge = self.get_edge_by_id(edge_id)?.clone();
+        if self.physical_graph.id_to_index(new_node.id).is_some() {
+            return None;
+        }
+
+        let fraction = match at {
+            SplitPoint::Coordinate(coord) => edge.path.line_locate_point(&Point::from(coord))?,
+            SplitPoint::Offset(offset) if edge.length > T::zero() => offset / edge.length,
+            SplitPoint::Offset(_) => return None,
+        };
+        if fraction <= T::zero() || fraction >= T::one() {
+            return None;
+        }
+
+        let (first_path, second_path) = split_path(&edge.path, fraction);
+        let first_length = edge.length * fraction;
+        let new_edge_id = self.next_edge_id();
+
+        let topo_edges = self.topology_graph.remove_edge(edge_id);
+        self.physical_graph.remove_transit_edge(edge_id);
+
+        let node_id = self.add_node(new_node);
+        let (node_index1, node_index2) = *self.topology_graph.id_to_index(node_id)?;
+
+        self.physical_graph.add_transit_edge(TransitEdge {
+            id: edge_id,
+            source: edge.source,
+            target: node_id,
+            length: first_length,
+            path: first_path,
+        });
+        self.physical_graph.add_transit_edge(TransitEdge {
+            id: new_edge_id,
+            source: node_id,
+            target: edge.target,
+            length: edge.length - first_length,
+            path: second_path,
+        });
+
+        if let Some(limit) = self.speed_limit(edge_id) {
+            self.set_speed_limit(new_edge_id, limit);
+        }
+
+        for ((from, to), via) in topo_edges.into_iter().zip([node_index1, node_index2]) {
+            let forward = self.topology_graph.index_to_id(from) == Some(&edge.source);
+            let (first_id, second_id) = if forward {
+                (edge_id, new_edge_id)
+            } else {
+                (new_edge_id, edge_id)
+            };
+            self.topology_graph.add_topo_edge(first_id, from, via);
+            self.topology_graph.add_topo_edge(second_id, via, to);
+        }
+
+        Some((edge_id, new_edge_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::ShortestPath,
+        core::{Accessability, NodeId},
+    };
+    use geo::{coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0)];
+        for (id, (x, y)) in locations.iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: *x, y: *y },
+            });
+        }
+
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 0,
+            target: 1,
+            length: 1.0,
+            path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+        });
+        network.add_edge_with_accessibility(
+            TransitEdge {
+                id: 2,
+                source: 1,
+                target: 2,
+                length: 1.0,
+                path: LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]),
+            },
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            TransitEdge {
+                id: 3,
+                source: 1,
+                target: 3,
+                length: 2.0,
+                path: LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 2.0, y: 1.0 }]),
+            },
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network
+    }
+
+    #[test]
+    fn test_split_edge_at_offset() {
+        let mut network = create_network();
+        network.set_speed_limit(1, 10.0);
+
+        let new_node = TransitNode {
+            id: 4,
+            location: coord! { x: 0.25, y: 0.0 },
+        };
+        let result = network.split_edge(1, SplitPoint::Offset(0.25), new_node);
+
+        assert_eq!(result, Some((1, 4)));
+        assert_eq!(network.physical_graph.graph.node_count(), 5);
+        assert_eq!(network.physical_graph.graph.edge_count(), 4);
+        assert_eq!(network.topology_graph.graph.edge_count(), 8);
+
+        let first = network.get_edge_by_id(1).unwrap();
+        assert_eq!((first.source, first.target), (0, 4));
+        assert_eq!(first.length, 0.25);
+        assert_eq!(
+            first.path,
+            LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 0.25, y: 0.0 }])
+        );
+        let second = network.get_edge_by_id(4).unwrap();
+        assert_eq!((second.source, second.target), (4, 1));
+        assert_eq!(second.length, 0.75);
+        assert_eq!(network.speed_limit(4), Some(10.0));
+
+        // The switch at node 1 is still only passable coming from node 0.
+        assert_eq!(network.find_shortest_path(0, 2), Some(vec![0, 4, 1, 2]));
+        assert_eq!(network.find_shortest_path(0, 3), Some(vec![0, 4, 1, 3]));
+        assert_eq!(network.find_shortest_path(2, 0), Some(vec![2, 1, 4, 0]));
+        assert_eq!(network.find_shortest_path(2, 3), None);
+    }
+
+    #[test]
+    fn test_split_edge_at_coordinate() {
+        let mut network = create_network();
+
+        let new_node = TransitNode {
+            id: 4,
+            location: coord! { x: 1.5, y: 0.5 },
+        };
+        let result = network.split_edge(
+            3,
+            SplitPoint::Coordinate(coord! { x: 1.5, y: 0.5 }),
+            new_node,
+        );
+
+        assert_eq!(result, Some((3, 4)));
+        assert_eq!(network.get_edge_by_id(3).unwrap().length, 1.0);
+        assert_eq!(network.get_edge_by_id(4).unwrap().length, 1.0);
+        assert_eq!(network.find_shortest_path(0, 3), Some(vec![0, 1, 4, 3]));
+        assert_eq!(network.find_shortest_path(2, 4), None);
+    }
+
+    #[test]
+    fn test_split_edge_invalid() {
+        let mut network = create_network();
+        let new_node = TransitNode {
+            id: 4,
+            location: coord! { x: 0.0, y: 0.0 },
+        };
+
+        assert_eq!(
+            network.split_edge(9, SplitPoint::Offset(0.5), new_node),
+            None
+        );
+        assert_eq!(
+            network.split_edge(1, SplitPoint::Offset(1.0), new_node),
+            None
+        );
+        assert_eq!(
+            network.split_edge(
+                1,
+                SplitPoint::Offset(0.5),
+                TransitNode {
+                    id: 2,
+                    location: coord! { x: 0.5, y: 0.0 },
+                }
+            ),
+            None
+        );
+        assert_eq!(network.physical_graph.graph.edge_count(), 3);
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    iter::Sum,
+};
+
+use geo::{Coord, CoordFloat, CoordNum, EuclideanLength, HaversineLength, Rect};
+use num_traits::FromPrimitive;
+use petgraph::stable_graph::NodeIndex;
+
+use crate::{
+    core::{measure, EdgeId, NodeId},
+    graphs::{AuditIssue, AuditOptions},
+};
+
+use super::TransitNetwork;
+
+/// Summary of the size and the data quality of a `TransitNetwork`, as returned by `TransitNetwork::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStats<T: CoordNum> {
+    /// The number of nodes.
+    pub node_count: usize,
+    /// The number of edges.
+    pub edge_count: usize,
+    /// The sum of the Euclidean lengths of all edge paths.
+    pub euclidean_length: T,
+    /// The sum of the haversine lengths of all edge paths in meters, for paths in longitude and latitude.
+    pub haversine_length: T,
+    /// The sum of the lengths of all edge paths as measured by `TransitNetwork::path_length`.
+    pub path_length: T,
+    /// The number of nodes for each number of incident edges. Loops count twice at their node.
+    pub degree_histogram: BTreeMap<usize, usize>,
+    /// The number of nodes with more than two incident edges.
+    pub switches: usize,
+    /// The number of nodes with exactly one incident edge.
+    pub dead_ends: usize,
+    /// The number of edges connecting the same two nodes as an edge with a lower ID.
+    pub parallel_edges: usize,
+    /// The number of edges whose path has fewer than two points or zero length.
+    pub degenerate_paths: usize,
+    /// The bounding box of all node locations and edge paths, `None` for an empty network.
+    pub bounding_box: Option<Rect<T>>,
+    /// The number of toponodes in the topology graph without any incoming or outgoing edge.
+    pub unused_toponodes: usize,
+}
+
+/// Enum `TopologyIssue` describing an inconsistency between the physical and the topology graph, as found by
+/// `TransitNetwork::audit_topology`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopologyIssue {
+    /// The node has no toponodes in the topology graph.
+    MissingNode {
+        /// The ID of the node.
+        node_id: NodeId,
+    },
+    /// The edge has no `TopoEdge`s in the topology graph.
+    MissingEdge {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The edge has another number of `TopoEdge`s than one for each direction.
+    TopoEdgeCount {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+        /// The number of `TopoEdge`s of the edge.
+        count: usize,
+    },
+    /// The `TopoEdge`s of the edge connect the toponodes of other nodes than the edge.
+    OtherNodes {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The two `TopoEdge`s of the edge do not connect the opposite toponodes in opposite directions.
+    MissingTwin {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// A `TopoEdge` belongs to an edge that is not in the physical graph.
+    OrphanTopoEdge {
+        /// The ID of the missing edge.
+        edge_id: EdgeId,
+    },
+}
+
+impl fmt::Display for TopologyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TopologyIssue::MissingNode { node_id } => {
+                write!(f, "node {} is missing in the topology graph", node_id)
+            }
+            TopologyIssue::MissingEdge { edge_id } => {
+                write!(f, "edge {} is missing in the topology graph", edge_id)
+            }
+            TopologyIssue::TopoEdgeCount { edge_id, count } => write!(
+                f,
+                "edge {} has {} topology edges instead of 2",
+                edge_id, count
+            ),
+            TopologyIssue::OtherNodes { edge_id } => write!(
+                f,
+                "edge {} connects other nodes in the topology graph",
+                edge_id
+            ),
+            TopologyIssue::MissingTwin { edge_id } => write!(
+                f,
+                "edge {} has no skew-symmetric twin in the topology graph",
+                edge_id
+            ),
+            TopologyIssue::OrphanTopoEdge { edge_id } => {
+                write!(f, "topology edge {} has no physical edge", edge_id)
+            }
+        }
+    }
+}
+
+impl<R, T> TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordFloat + FromPrimitive + Sum,
+{
+    /// Computes a summary of the size and the data quality of the network.
+    ///
+    /// All values except `unused_toponodes` are taken from the physical graph.
+    /// Besides the Euclidean and the haversine length, paths are measured in the `Crs` of the network like
+    /// `path_length` does.
+    ///
+    /// # Returns
+    ///
+    /// * `NetworkStats<T>` - The statistics of the network.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 3.0, y: 4.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 5.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 3.0, y: 4.0 }]),
+    /// });
+    ///
+    /// let stats = network.stats();
+    /// assert_eq!(stats.euclidean_length, 5.0);
+    /// assert_eq!(stats.path_length, 5.0);
+    /// assert_eq!(stats.dead_ends, 2);
+    /// assert_eq!(stats.degree_histogram.get(&1), Some(&2));
+    /// ```
+    pub fn stats(&self) -> NetworkStats<T> {
+        let graph = &self.physical_graph.graph;
+        let mut degrees: HashMap<NodeId, usize> =
+            graph.node_weights().map(|node| (node.id, 0)).collect();
+        let mut node_pairs: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+        let mut euclidean_length = T::zero();
+        let mut haversine_length = T::zero();
+        let mut path_length = T::zero();
+        let mut degenerate_paths = 0;
+        for edge in graph.edge_weights() {
+            *degrees.entry(edge.source).or_default() += 1;
+            *degrees.entry(edge.target).or_default() += 1;
+            *node_pairs
+                .entry((edge.source.min(edge.target), edge.source.max(edge.target)))
+                .or_default() += 1;
+
+            let length = measure(self.crs, &edge.path);
+            euclidean_length = euclidean_length + edge.path.euclidean_length();
+            haversine_length = haversine_length + edge.path.haversine_length();
+            path_length = path_length + length;
+            if edge.path.0.len() < 2 || length.is_zero() {
+                degenerate_paths += 1;
+            }
+        }
+
+        let mut degree_histogram = BTreeMap::new();
+        for degree in degrees.values() {
+            *degree_histogram.entry(*degree).or_default() += 1;
+        }
+
+        let coords = graph.node_weights().map(|node| node.location.into()).chain(
+            graph
+                .edge_weights()
+                .flat_map(|edge| edge.path.0.iter().copied()),
+        );
+        let bounding_box = coords.fold(None, |rect: Option<Rect<T>>, coord: Coord<T>| {
+            Some(match rect {
+                Some(rect) => Rect::new(
+                    Coord {
+                        x: rect.min().x.min(coord.x),
+                        y: rect.min().y.min(coord.y),
+                    },
+                    Coord {
+                        x: rect.max().x.max(coord.x),
+                        y: rect.max().y.max(coord.y),
+                    },
+                ),
+                None => Rect::new(coord, coord),
+            })
+        });
+
+        let topology = &self.topology_graph.graph;
+        let unused_toponodes = topology
+            .node_indices()
+            .filter(|index| topology.neighbors_undirected(*index).next().is_none())
+            .count();
+
+        NetworkStats {
+            node_count: graph.node_count(),
+            edge_count: graph.edge_count(),
+            euclidean_length,
+            haversine_length,
+            path_length,
+            switches: degrees.values().filter(|degree| **degree > 2).count(),
+            dead_ends: degrees.values().filter(|degree| **degree == 1).count(),
+            degree_histogram,
+            parallel_edges: node_pairs.values().map(|count| count - 1).sum(),
+            degenerate_paths,
+            bounding_box,
+            unused_toponodes,
+        }
+    }
+
+    /// Checks all edges for inconsistencies between their path, their length and their nodes.
+    ///
+    /// Runs `PhysicalGraph::audit` with the `Crs` of the network, so that the paths of a geographic
+    /// network are measured in meters.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `AuditOptions` with the tolerances of the checks.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<AuditIssue<T>>` - The issues found, ordered by edge ID.
+    pub fn audit(&self, options: &AuditOptions<T>) -> Vec<AuditIssue<T>> {
+        self.physical_graph.audit(options, self.crs)
+    }
+
+    /// Fixes the issues found by `audit` where possible, measuring lengths in the `Crs` of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `issues` - The issues to fix, as returned by `audit`.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of issues fixed.
+    pub fn apply_fixes(&mut self, issues: &[AuditIssue<T>]) -> usize {
+        self.physical_graph.apply_fixes(issues, self.crs)
+    }
+
+    /// Checks that the topology graph matches the physical graph.
+    ///
+    /// Every node needs its toponodes, and every edge needs one `TopoEdge` for each direction between the
+    /// toponodes of its nodes, the second running opposite to the first between the other toponodes.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<TopologyIssue>` - The issues found, the missing nodes first, then the issues of the edges and the
+    ///   orphaned `TopoEdge`s, each ordered by ID.
+    pub fn audit_topology(&self) -> Vec<TopologyIssue> {
+        let topology = &self.topology_graph;
+        let mut topo_edges: HashMap<EdgeId, Vec<(NodeIndex, NodeIndex)>> = HashMap::new();
+        for edge in topology.graph.edge_indices() {
+            if let Some(endpoints) = topology.graph.edge_endpoints(edge) {
+                topo_edges
+                    .entry(topology.graph[edge].edge_id)
+                    .or_default()
+                    .push(endpoints);
+            }
+        }
+
+        let mut node_ids: Vec<NodeId> = self
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .collect();
+        node_ids.sort_unstable();
+        let mut issues: Vec<TopologyIssue> = node_ids
+            .into_iter()
+            .filter(|node_id| topology.id_to_index(*node_id).is_none())
+            .map(|node_id| TopologyIssue::MissingNode { node_id })
+            .collect();
+
+        let mut edges: Vec<_> = self.physical_graph.graph.edge_weights().collect();
+        edges.sort_by_key(|edge| edge.id);
+        let node_id = |index: NodeIndex| topology.graph[index].node_id;
+        for edge in edges {
+            let edge_id = edge.id;
+            let Some(twins) = topo_edges.remove(&edge_id) else {
+                issues.push(TopologyIssue::MissingEdge { edge_id });
+                continue;
+            };
+            if twins.len() != 2 {
+                issues.push(TopologyIssue::TopoEdgeCount {
+                    edge_id,
+                    count: twins.len(),
+                });
+                continue;
+            }
+            let (source, target) = twins[0];
+            let mut nodes = [node_id(source), node_id(target)];
+            nodes.sort_unstable();
+            let mut expected = [edge.source, edge.target];
+            expected.sort_unstable();
+            if nodes != expected {
+                issues.push(TopologyIssue::OtherNodes { edge_id });
+            }
+            let twin = (
+                topology.get_other_toponode(target),
+                topology.get_other_toponode(source),
+            );
+            if twin != (Some(twins[1].0), Some(twins[1].1)) {
+                issues.push(TopologyIssue::MissingTwin { edge_id });
+            }
+        }
+
+        let mut orphans: Vec<EdgeId> = topo_edges.into_keys().collect();
+        orphans.sort_unstable();
+        issues.extend(
+            orphans
+                .into_iter()
+                .map(|edge_id| TopologyIssue::OrphanTopoEdge { edge_id }),
+        );
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Crs, TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::{coord, LineString};
+
+    fn edge(id: u64, source: u64, target: u64, path: Vec<Coord>) -> TransitEdge<f64> {
+        TransitEdge {
+            id,
+            source,
+            target,
+            length: 1.0,
+            path: LineString(path),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        let locations = [
+            (1, 0.0, 0.0),
+            (2, 1.0, 0.0),
+            (3, 2.0, 1.0),
+            (4, 2.0, -1.0),
+            (5, 9.0, 9.0),
+        ];
+        for (id, x, y) in locations {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let a = coord! { x: 0.0, y: 0.0 };
+        let b = coord! { x: 1.0, y: 0.0 };
+        network.add_edge(edge(1, 1, 2, vec![a, b]));
+        network.add_edge(edge(2, 2, 3, vec![b, coord! { x: 2.0, y: 1.0 }]));
+        network.add_edge(edge(3, 2, 4, vec![b, coord! { x: 2.0, y: -1.0 }]));
+        network.add_edge(edge(4, 2, 1, vec![b, coord! { x: 0.5, y: -2.0 }, a]));
+        network.add_edge(edge(5, 1, 2, vec![a]));
+
+        let stats = network.stats();
+
+        assert_eq!(stats.node_count, 5);
+        assert_eq!(stats.edge_count, 5);
+        let euclidean = 1.0 + 2.0 * 2f64.sqrt() + 2.0 * 4.25f64.sqrt();
+        assert!((stats.euclidean_length - euclidean).abs() < 1e-12);
+        assert!(stats.haversine_length > 100_000.0 * stats.euclidean_length);
+        assert_eq!(stats.path_length, stats.euclidean_length);
+        let geographic = network.clone().with_crs(Crs::Wgs84).stats();
+        assert_eq!(geographic.euclidean_length, stats.euclidean_length);
+        assert_eq!(geographic.path_length, geographic.haversine_length);
+        assert_eq!(
+            stats.degree_histogram,
+            BTreeMap::from([(0, 1), (1, 2), (3, 1), (5, 1)])
+        );
+        assert_eq!(stats.switches, 2);
+        assert_eq!(stats.dead_ends, 2);
+        assert_eq!(stats.parallel_edges, 2);
+        assert_eq!(stats.degenerate_paths, 1);
+        assert_eq!(
+            stats.bounding_box,
+            Some(Rect::new(
+                coord! { x: 0.0, y: -2.0 },
+                coord! { x: 9.0, y: 9.0 }
+            ))
+        );
+        assert_eq!(stats.unused_toponodes, 2);
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+
+        let stats = network.stats();
+
+        assert_eq!(stats.node_count, 0);
+        assert_eq!(stats.euclidean_length, 0.0);
+        assert_eq!(stats.haversine_length, 0.0);
+        assert_eq!(stats.path_length, 0.0);
+        assert!(stats.degree_histogram.is_empty());
+        assert_eq!(stats.bounding_box, None);
+    }
+
+    #[test]
+    fn test_audit_geographic() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new().with_crs(Crs::Wgs84);
+        let a = coord! { x: 13.0, y: 52.0 };
+        let b = coord! { x: 13.01, y: 52.0 };
+        network.add_node(TransitNode { id: 1, location: a });
+        network.add_node(TransitNode { id: 2, location: b });
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 1,
+            target: 2,
+            length: 684.585,
+            path: LineString(vec![a, b]),
+        });
+        let options = AuditOptions::default();
+
+        assert!(network.audit(&options).is_empty());
+
+        network
+            .physical_graph
+            .graph
+            .edge_weights_mut()
+            .for_each(|edge| edge.length = 700.0);
+        let issues = network.audit(&options);
+        assert!(matches!(
+            issues[..],
+            [AuditIssue::LengthMismatch { computed, .. }] if (computed - 684.585).abs() < 1e-3
+        ));
+        assert_eq!(network.apply_fixes(&issues), 1);
+        assert!((network.get_edge_by_id(1).unwrap().length - 684.585).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_audit_topology() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 2.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 0.0 },
+            });
+        }
+        let a = coord! { x: 0.0, y: 0.0 };
+        let b = coord! { x: 1.0, y: 0.0 };
+        let c = coord! { x: 2.0, y: 0.0 };
+        network.add_edge(edge(1, 1, 2, vec![a, b]));
+        assert!(network.audit_topology().is_empty());
+
+        network
+            .physical_graph
+            .add_transit_edge(edge(2, 2, 3, vec![b, c]));
+        let (first, second) = *network.topology_graph.id_to_index(1).unwrap();
+        network.topology_graph.add_topo_edge(9, first, second);
+
+        let issues = network.audit_topology();
+
+        assert_eq!(
+            issues,
+            vec![
+                TopologyIssue::MissingEdge { edge_id: 2 },
+                TopologyIssue::OrphanTopoEdge { edge_id: 9 },
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "edge 2 is missing in the topology graph"
+        );
+    }
+}
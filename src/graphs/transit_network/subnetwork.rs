@@ -0,0 +1,329 @@
+use std::{collections::HashSet, iter::Sum, ops::AddAssign};
+
+use geo::{
+    line_intersection::{line_intersection, LineIntersection},
+    Coord, CoordNum, EuclideanDistance, GeoFloat, Intersects, Line, Polygon, Rect,
+};
+
+use crate::{
+    core::{NodeId, TransitEdge, TransitNode},
+    operations::TransitNetworkRemover,
+};
+
+use super::{SplitPoint, TransitNetwork};
+
+/// Enum `SubnetworkFilter` defining the region of a network extracted by `TransitNetwork::subnetwork`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubnetworkFilter<T: CoordNum> {
+    /// Keeps the nodes inside or on the border of a bounding box.
+    BoundingBox(Rect<T>),
+    /// Keeps the nodes inside or on the border of a polygon.
+    Polygon(Polygon<T>),
+    /// Keeps the nodes with the given IDs.
+    Nodes(HashSet<NodeId>),
+}
+
+/// Enum `BoundaryMode` defining how `TransitNetwork::subnetwork` treats edges crossing the boundary of the region.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BoundaryMode {
+    /// Cuts the edge where it leaves the region and inserts a new node there.
+    /// Edges are only clipped for geometric filters, for `SubnetworkFilter::Nodes` they are dropped.
+    Clip,
+    /// Removes the edge.
+    Drop,
+}
+
+impl<R, T> TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>> + From<Coord<T>>,
+    T: GeoFloat + AddAssign + Sum,
+{
+    /// Extracts the part of the network inside a region as a standalone network.
+    ///
+    /// The subnetwork contains the nodes selected by the `filter` and all edges between them.
+    /// Edges with one node inside the region are clipped or dropped according to `boundary`,
+    /// edges with both nodes outside the region are dropped even if their path passes through it.
+    /// The wiring of the topology graph is preserved for all nodes that are kept, and clipped edges
+    /// continue straight through their new boundary node.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The `SubnetworkFilter` selecting the nodes to keep.
+    /// * `boundary` - The `BoundaryMode` for edges crossing the boundary of the region.
+    ///
+    /// # Returns
+    ///
+    /// * `TransitNetwork<R, T>` - The extracted network.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString, Rect};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 2.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 2.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 2.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let region = Rect::new(coord! { x: -1.0, y: -1.0 }, coord! { x: 1.0, y: 1.0 });
+    /// let clipped = network.subnetwork(&SubnetworkFilter::BoundingBox(region), BoundaryMode::Clip);
+    /// assert_eq!(clipped.get_edge_by_id(1).unwrap().length, 1.0);
+    ///
+    /// let dropped = network.subnetwork(&SubnetworkFilter::BoundingBox(region), BoundaryMode::Drop);
+    /// assert!(dropped.get_edge_by_id(1).is_none());
+    /// ```
+    pub fn subnetwork(
+        &self,
+        filter: &SubnetworkFilter<T>,
+        boundary: BoundaryMode,
+    ) -> TransitNetwork<R, T> {
+        let region = match filter {
+            SubnetworkFilter::BoundingBox(rect) => Some(rect.to_polygon()),
+            SubnetworkFilter::Polygon(polygon) => Some(polygon.clone()),
+            SubnetworkFilter::Nodes(_) => None,
+        };
+        let mut inside: HashSet<NodeId> = self
+            .physical_graph
+            .graph
+            .node_weights()
+            .filter(|node| match (filter, &region) {
+                (SubnetworkFilter::Nodes(node_ids), _) => node_ids.contains(&node.id),
+                (_, Some(region)) => region.intersects(&node.location.into()),
+                _ => false,
+            })
+            .map(|node| node.id)
+            .collect();
+
+        let mut network = self.clone();
+
+        if let (BoundaryMode::Clip, Some(region)) = (boundary, &region) {
+            let mut crossing: Vec<TransitEdge<T>> = self
+                .physical_graph
+                .graph
+                .edge_weights()
+                .filter(|edge| inside.contains(&edge.source) != inside.contains(&edge.target))
+                .cloned()
+                .collect();
+            crossing.sort_by_key(|edge| edge.id);
+
+            for edge in crossing {
+                let from_source = inside.contains(&edge.source);
+                let Some(exit) = boundary_crossing(&edge, from_source, region) else {
+                    continue;
+                };
+                let node_id = network.next_node_id();
+                let new_node = TransitNode {
+                    id: node_id,
+                    location: R::from(exit),
+                };
+                if network
+                    .split_edge(edge.id, SplitPoint::Coordinate(exit), new_node)
+                    .is_some()
+                {
+                    inside.insert(node_id);
+                }
+            }
+        }
+
+        let outside: Vec<NodeId> = network
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .filter(|node_id| !inside.contains(node_id))
+            .collect();
+        for node_id in outside {
+            network.remove_node(node_id);
+        }
+        network
+    }
+}
+
+/// Returns the first point where the path of an edge leaves the region, through its exterior or into one of its
+/// holes, walking from the source if `from_source` is set and from the target otherwise.
+fn boundary_crossing<T: GeoFloat>(
+    edge: &TransitEdge<T>,
+    from_source: bool,
+    region: &Polygon<T>,
+) -> Option<Coord<T>> {
+    let mut coords = edge.path.0.clone();
+    if !from_source {
+        coords.reverse();
+    }
+    let two = T::one() + T::one();
+    for segment in coords.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let segment = Line::new(start, end);
+        let mut crossings: Vec<Coord<T>> = std::iter::once(region.exterior())
+            .chain(region.interiors())
+            .flat_map(|ring| ring.lines())
+            .filter_map(|border| line_intersection(segment, border))
+            .flat_map(|intersection| match intersection {
+                LineIntersection::SinglePoint { intersection, .. } => vec![intersection],
+                LineIntersection::Collinear { intersection } => {
+                    vec![intersection.start, intersection.end]
+                }
+            })
+            .collect();
+        crossings.sort_by(|a, b| {
+            start
+                .euclidean_distance(a)
+                .partial_cmp(&start.euclidean_distance(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        crossings.dedup();
+        // A crossing is an exit if the path runs outside the region right after it.
+        for (index, crossing) in crossings.iter().enumerate() {
+            let next = crossings.get(index + 1).copied().unwrap_or(end);
+            if !region.intersects(&((*crossing + next) / two)) {
+                return Some(*crossing);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::ShortestPath, core::Accessability, operations::TransitNetworkModifier,
+    };
+    use geo::{coord, polygon, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (1.0, 1.0)];
+        for (id, (x, y)) in locations.into_iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let edge = |id, source: NodeId, target: NodeId| TransitEdge {
+            id,
+            source,
+            target,
+            length: 1.0,
+            path: LineString(vec![
+                locations[source as usize].into(),
+                locations[target as usize].into(),
+            ]),
+        };
+        network.add_edge(edge(1, 0, 1));
+        network.add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0]));
+        network.add_edge_with_accessibility(edge(3, 2, 3), Accessability::ReachableNodes(vec![1]));
+        network.add_edge_with_accessibility(edge(4, 1, 4), Accessability::ReachableNodes(vec![0]));
+        network
+    }
+
+    fn region() -> SubnetworkFilter<f64> {
+        SubnetworkFilter::BoundingBox(Rect::new(
+            coord! { x: -0.5, y: -0.5 },
+            coord! { x: 1.5, y: 0.5 },
+        ))
+    }
+
+    #[test]
+    fn test_subnetwork_clip() {
+        let network = create_network();
+
+        let subnetwork = network.subnetwork(&region(), BoundaryMode::Clip);
+
+        assert_eq!(subnetwork.physical_graph.graph.node_count(), 4);
+        assert_eq!(subnetwork.physical_graph.graph.edge_count(), 3);
+        assert_eq!(subnetwork.topology_graph.graph.node_count(), 8);
+        assert_eq!(subnetwork.topology_graph.graph.edge_count(), 6);
+
+        let clipped = subnetwork.get_edge_by_id(2).unwrap();
+        assert_eq!((clipped.source, clipped.target), (1, 5));
+        assert_eq!(clipped.length, 0.5);
+        assert_eq!(
+            clipped.path,
+            LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 1.5, y: 0.0 }])
+        );
+        let clipped = subnetwork.get_edge_by_id(4).unwrap();
+        assert_eq!((clipped.source, clipped.target), (1, 6));
+
+        assert_eq!(subnetwork.find_shortest_path(0, 5), Some(vec![0, 1, 5]));
+        assert_eq!(subnetwork.find_shortest_path(0, 6), Some(vec![0, 1, 6]));
+        assert_eq!(subnetwork.find_shortest_path(5, 6), None);
+    }
+
+    #[test]
+    fn test_subnetwork_drop() {
+        let network = create_network();
+
+        let subnetwork = network.subnetwork(&region(), BoundaryMode::Drop);
+
+        assert_eq!(subnetwork.physical_graph.graph.node_count(), 2);
+        assert_eq!(subnetwork.physical_graph.graph.edge_count(), 1);
+        assert_eq!(subnetwork.topology_graph.graph.edge_count(), 2);
+        assert_eq!(subnetwork.find_shortest_path(0, 1), Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_subnetwork_polygon() {
+        let network = create_network();
+        let filter = SubnetworkFilter::Polygon(polygon![
+            (x: 0.5, y: -1.0),
+            (x: 3.5, y: -1.0),
+            (x: 3.5, y: 1.0),
+            (x: 0.5, y: 1.0),
+        ]);
+
+        let subnetwork = network.subnetwork(&filter, BoundaryMode::Clip);
+
+        assert_eq!(subnetwork.physical_graph.graph.node_count(), 5);
+        let clipped = subnetwork.get_edge_by_id(5).unwrap();
+        assert_eq!((clipped.source, clipped.target), (5, 1));
+        assert_eq!(subnetwork.find_shortest_path(5, 3), Some(vec![5, 1, 2, 3]));
+        assert_eq!(subnetwork.find_shortest_path(5, 4), Some(vec![5, 1, 4]));
+    }
+
+    #[test]
+    fn test_subnetwork_polygon_with_hole() {
+        let network = create_network();
+        let filter = SubnetworkFilter::Polygon(Polygon::new(
+            LineString::from(vec![(-0.5, -0.5), (1.5, -0.5), (1.5, 0.5), (-0.5, 0.5)]),
+            vec![LineString::from(vec![
+                (1.2, -0.2),
+                (1.4, -0.2),
+                (1.4, 0.2),
+                (1.2, 0.2),
+            ])],
+        ));
+
+        let subnetwork = network.subnetwork(&filter, BoundaryMode::Clip);
+
+        let clipped = subnetwork.get_edge_by_id(2).unwrap();
+        assert_eq!(
+            clipped.path,
+            LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 1.2, y: 0.0 }])
+        );
+        let clipped = subnetwork.get_edge_by_id(4).unwrap();
+        assert_eq!(
+            clipped.path,
+            LineString(vec![coord! { x: 1.0, y: 0.0 }, coord! { x: 1.0, y: 0.5 }])
+        );
+    }
+
+    #[test]
+    fn test_subnetwork_nodes() {
+        let network = create_network();
+        let filter = SubnetworkFilter::Nodes(HashSet::from([1, 2, 3]));
+
+        let subnetwork = network.subnetwork(&filter, BoundaryMode::Clip);
+
+        assert_eq!(subnetwork.physical_graph.graph.node_count(), 3);
+        assert_eq!(subnetwork.physical_graph.graph.edge_count(), 2);
+        assert_eq!(subnetwork.find_shortest_path(1, 3), Some(vec![1, 2, 3]));
+    }
+}
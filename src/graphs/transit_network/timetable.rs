@@ -0,0 +1,684 @@
+use std::{collections::HashSet, fmt};
+
+use geo::CoordNum;
+use petgraph::{algo::astar, visit::EdgeRef};
+
+use crate::{
+    core::{EdgeId, NodeId, Service, ServiceId, Timetable, TransitEdge},
+    graphs::{TopoEdge, TopologyGraph},
+};
+
+use super::TransitNetwork;
+
+/// Options for `TransitNetwork::validate_timetable`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimetableOptions {
+    /// The highest speed of any vehicle, in units of the edge length per second.
+    /// Edges with a lower speed limit are passed at their limit.
+    pub max_speed: f64,
+    /// The tolerance in seconds by which a scheduled running time may fall below the minimum running time.
+    pub time_tolerance: f64,
+}
+
+impl Default for TimetableOptions {
+    fn default() -> Self {
+        TimetableOptions {
+            max_speed: f64::INFINITY,
+            time_tolerance: 1e-6,
+        }
+    }
+}
+
+/// Enum `TimetableIssue` describing a problem of a service found by `TransitNetwork::validate_timetable`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimetableIssue {
+    /// Another service with a lower index has the same ID.
+    DuplicateService {
+        /// The ID of the service.
+        service_id: ServiceId,
+    },
+    /// The service has fewer than two stops.
+    TooFewStops {
+        /// The ID of the service.
+        service_id: ServiceId,
+    },
+    /// A stop refers to a node that does not exist in the network.
+    UnknownNode {
+        /// The ID of the service.
+        service_id: ServiceId,
+        /// The index of the stop.
+        stop_index: usize,
+        /// The ID of the missing node.
+        node_id: NodeId,
+    },
+    /// The departure at a stop is scheduled before the arrival.
+    NegativeDwell {
+        /// The ID of the service.
+        service_id: ServiceId,
+        /// The index of the stop.
+        stop_index: usize,
+        /// The scheduled dwell time in seconds.
+        dwell: f64,
+    },
+    /// The stop cannot be reached from the previous stop in the topology, or its bound path is not traversable.
+    Unreachable {
+        /// The ID of the service.
+        service_id: ServiceId,
+        /// The index of the stop.
+        stop_index: usize,
+    },
+    /// The running time from the previous stop is shorter than the distance allows at the permitted speeds.
+    InfeasibleRunningTime {
+        /// The ID of the service.
+        service_id: ServiceId,
+        /// The index of the stop.
+        stop_index: usize,
+        /// The scheduled running time in seconds.
+        scheduled: f64,
+        /// The minimum running time in seconds.
+        minimum: f64,
+    },
+}
+
+impl TimetableIssue {
+    /// Returns the ID of the service the issue was found at.
+    pub fn service_id(&self) -> ServiceId {
+        match self {
+            TimetableIssue::DuplicateService { service_id }
+            | TimetableIssue::TooFewStops { service_id }
+            | TimetableIssue::UnknownNode { service_id, .. }
+            | TimetableIssue::NegativeDwell { service_id, .. }
+            | TimetableIssue::Unreachable { service_id, .. }
+            | TimetableIssue::InfeasibleRunningTime { service_id, .. } => *service_id,
+        }
+    }
+}
+
+impl fmt::Display for TimetableIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimetableIssue::DuplicateService { service_id } => {
+                write!(f, "service {} is defined more than once", service_id)
+            }
+            TimetableIssue::TooFewStops { service_id } => {
+                write!(f, "service {} has fewer than two stops", service_id)
+            }
+            TimetableIssue::UnknownNode {
+                service_id,
+                stop_index,
+                node_id,
+            } => write!(
+                f,
+                "stop {} of service {} refers to unknown node {}",
+                stop_index, service_id, node_id
+            ),
+            TimetableIssue::NegativeDwell {
+                service_id,
+                stop_index,
+                dwell,
+            } => write!(
+                f,
+                "stop {} of service {} has a negative dwell time of {} s",
+                stop_index, service_id, dwell
+            ),
+            TimetableIssue::Unreachable {
+                service_id,
+                stop_index,
+            } => write!(
+                f,
+                "stop {} of service {} cannot be reached from the previous stop",
+                stop_index, service_id
+            ),
+            TimetableIssue::InfeasibleRunningTime {
+                service_id,
+                stop_index,
+                scheduled,
+                minimum,
+            } => write!(
+                f,
+                "stop {} of service {} is scheduled {} s after the previous stop but needs at least {} s",
+                stop_index, service_id, scheduled, minimum
+            ),
+        }
+    }
+}
+
+impl<R: Copy, T: CoordNum> TransitNetwork<R, T> {
+    /// Returns `true` if a vehicle can follow the path in the topology graph,
+    /// passing each intermediate node through a single `TopoNode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The IDs of the nodes along the path.
+    pub fn is_traversable(&self, path: &[NodeId]) -> bool {
+        let topology = &self.topology_graph;
+        let Some((first, second)) = path.first().and_then(|id| topology.id_to_index(*id)) else {
+            return false;
+        };
+        let mut current = vec![*first, *second];
+        for node_id in &path[1..] {
+            current = current
+                .iter()
+                .flat_map(|toponode| topology.graph.neighbors(*toponode))
+                .filter(|toponode| topology.graph[*toponode].node_id == *node_id)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            if current.is_empty() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if a vehicle can follow the path through the given edges in the topology graph,
+    /// passing each intermediate node through a single `TopoNode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The IDs of the nodes along the path.
+    /// * `edges` - The IDs of the edges between consecutive nodes of the path.
+    pub fn is_traversable_along(&self, path: &[NodeId], edges: &[EdgeId]) -> bool {
+        let topology = &self.topology_graph;
+        let Some((first, second)) = path.first().and_then(|id| topology.id_to_index(*id)) else {
+            return false;
+        };
+        if path.len() != edges.len() + 1 {
+            return false;
+        }
+        let mut current = vec![*first, *second];
+        for (node_id, edge_id) in path[1..].iter().zip(edges) {
+            current = current
+                .iter()
+                .flat_map(|toponode| topology.graph.edges(*toponode))
+                .filter(|edge| {
+                    edge.weight().edge_id == *edge_id
+                        && topology.graph[edge.target()].node_id == *node_id
+                })
+                .map(|edge| edge.target())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            if current.is_empty() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Binds each run between consecutive stops of a service to the shortest path by edge length
+    /// in the topology graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - The service to bind, its `paths` and `edges` are replaced.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` if a stop cannot be reached from the previous stop, the service is unchanged in that case.
+    pub fn bind_service(&self, service: &mut Service) -> bool {
+        let paths: Option<Vec<_>> = service
+            .stops
+            .windows(2)
+            .map(|pair| {
+                self.path_between(pair[0].node_id, pair[1].node_id, |edge| {
+                    edge.length.to_f64().unwrap_or(f64::INFINITY)
+                })
+            })
+            .collect();
+        match paths {
+            Some(paths) if !service.stops.is_empty() => {
+                (service.paths, service.edges) = paths.into_iter().unzip();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks a timetable against the network.
+    ///
+    /// Each service needs at least two stops at existing nodes, non-negative dwell times and, for every
+    /// consecutive pair of stops, a path in the direction-aware topology graph. Bound services are checked along
+    /// their paths and edges, unbound services along the fastest path. The scheduled running time between two stops must
+    /// not be shorter than the time needed to cover the path at the speed limits and `TimetableOptions::max_speed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timetable` - The timetable to check.
+    /// * `options` - The speeds and tolerances of the check.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<TimetableIssue>` - The issues found, ordered by service and stop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1000.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+    /// });
+    /// network.set_speed_limit(1, 20.0);
+    ///
+    /// let mut timetable = Timetable::new();
+    /// timetable.add_service(Service::new(1, vec![
+    ///     Stop { node_id: 1, arrival: 0.0, departure: 0.0 },
+    ///     Stop { node_id: 2, arrival: 30.0, departure: 30.0 },
+    /// ]));
+    ///
+    /// let issues = network.validate_timetable(&timetable, &TimetableOptions::default());
+    /// assert!(matches!(
+    ///     issues[0],
+    ///     TimetableIssue::InfeasibleRunningTime { stop_index: 1, minimum, .. } if minimum == 50.0
+    /// ));
+    /// ```
+    pub fn validate_timetable(
+        &self,
+        timetable: &Timetable,
+        options: &TimetableOptions,
+    ) -> Vec<TimetableIssue> {
+        let mut issues = Vec::new();
+        let mut seen = HashSet::new();
+        for service in &timetable.services {
+            let service_id = service.id;
+            if !seen.insert(service_id) {
+                issues.push(TimetableIssue::DuplicateService { service_id });
+                continue;
+            }
+            if service.stops.len() < 2 {
+                issues.push(TimetableIssue::TooFewStops { service_id });
+                continue;
+            }
+
+            let mut known = true;
+            for (stop_index, stop) in service.stops.iter().enumerate() {
+                if self.physical_graph.id_to_index(stop.node_id).is_none() {
+                    known = false;
+                    issues.push(TimetableIssue::UnknownNode {
+                        service_id,
+                        stop_index,
+                        node_id: stop.node_id,
+                    });
+                }
+                if stop.departure < stop.arrival {
+                    issues.push(TimetableIssue::NegativeDwell {
+                        service_id,
+                        stop_index,
+                        dwell: stop.departure - stop.arrival,
+                    });
+                }
+            }
+            if !known {
+                continue;
+            }
+
+            for stop_index in 1..service.stops.len() {
+                let (previous, stop) = (&service.stops[stop_index - 1], &service.stops[stop_index]);
+                let edges = if service.is_bound() {
+                    let path = &service.paths[stop_index - 1];
+                    let edges = &service.edges[stop_index - 1];
+                    let connects = path.first() == Some(&previous.node_id)
+                        && path.last() == Some(&stop.node_id);
+                    (connects
+                        && self.follows_edges(path, edges)
+                        && self.is_traversable_along(path, edges))
+                    .then(|| edges.clone())
+                } else {
+                    self.path_between(previous.node_id, stop.node_id, |edge| {
+                        self.edge_time(edge, options.max_speed)
+                    })
+                    .map(|(_, edges)| edges)
+                };
+                let Some(minimum) =
+                    edges.and_then(|edges| self.running_time(&edges, options.max_speed))
+                else {
+                    issues.push(TimetableIssue::Unreachable {
+                        service_id,
+                        stop_index,
+                    });
+                    continue;
+                };
+                let scheduled = stop.arrival - previous.departure;
+                if scheduled + options.time_tolerance < minimum {
+                    issues.push(TimetableIssue::InfeasibleRunningTime {
+                        service_id,
+                        stop_index,
+                        scheduled,
+                        minimum,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Returns the time needed to pass an edge at its speed limit and the given maximum speed.
+    fn edge_time(&self, edge: &TransitEdge<T>, max_speed: f64) -> f64 {
+        let speed = self
+            .speed_limit(edge.id)
+            .map_or(max_speed, |limit| limit.min(max_speed));
+        edge.length.to_f64().unwrap_or(f64::INFINITY) / speed
+    }
+
+    /// Finds the path between two nodes with the lowest total cost of its edges in the topology graph.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(Vec<NodeId>, Vec<EdgeId>)>` - The IDs of the nodes and of the edges along the path, so that
+    ///   parallel edges are told apart, or `None` if `to` cannot be reached from `from`.
+    pub(crate) fn path_between<F>(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        edge_cost: F,
+    ) -> Option<(Vec<NodeId>, Vec<EdgeId>)>
+    where
+        F: FnMut(&TransitEdge<T>) -> f64,
+    {
+        self.path_through(&self.topology_graph, from, to, edge_cost)
+    }
+
+    /// Finds the path between two nodes with the lowest total cost of its edges in a topology graph of this
+    /// network, such as one restricted to some of its movements.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(Vec<NodeId>, Vec<EdgeId>)>` - The IDs of the nodes and of the edges along the path, or `None`
+    ///   if `to` cannot be reached from `from`.
+    pub(crate) fn path_through<F>(
+        &self,
+        topology: &TopologyGraph,
+        from: NodeId,
+        to: NodeId,
+        mut edge_cost: F,
+    ) -> Option<(Vec<NodeId>, Vec<EdgeId>)>
+    where
+        F: FnMut(&TransitEdge<T>) -> f64,
+    {
+        if from == to {
+            return self
+                .physical_graph
+                .id_to_index(from)
+                .map(|_| (vec![from], Vec::new()));
+        }
+        let start = *topology.id_to_index(from)?;
+        let goal = *topology.id_to_index(to)?;
+        let mut cost = |topo_edge: &TopoEdge| {
+            self.transit_edge(topo_edge)
+                .map_or(f64::INFINITY, &mut edge_cost)
+        };
+        let (_, path) = [start.0, start.1]
+            .into_iter()
+            .filter_map(|start| {
+                astar(
+                    &topology.graph,
+                    start,
+                    |finish| finish == goal.0 || finish == goal.1,
+                    |edge| cost(edge.weight()),
+                    |_| 0.0,
+                )
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))?;
+
+        let nodes = path
+            .iter()
+            .map(|index| topology.graph[*index].node_id)
+            .collect();
+        let edges = path
+            .windows(2)
+            .map(|pair| {
+                topology
+                    .graph
+                    .edges_connecting(pair[0], pair[1])
+                    .map(|edge| edge.weight())
+                    .min_by(|a, b| cost(a).total_cmp(&cost(b)))
+                    .map(|edge| edge.edge_id)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some((nodes, edges))
+    }
+
+    /// Returns the `TransitEdge` a `TopoEdge` belongs to.
+    fn transit_edge(&self, topo_edge: &TopoEdge) -> Option<&TransitEdge<T>> {
+        let from = self.physical_graph.id_to_index(topo_edge.from)?;
+        let to = self.physical_graph.id_to_index(topo_edge.to)?;
+        self.physical_graph
+            .graph
+            .edges_connecting(*from, *to)
+            .map(|edge| edge.weight())
+            .find(|edge| edge.id == topo_edge.edge_id)
+    }
+
+    /// Returns `true` if the edges connect the consecutive nodes of the path.
+    fn follows_edges(&self, path: &[NodeId], edges: &[EdgeId]) -> bool {
+        path.len() == edges.len() + 1
+            && path.windows(2).zip(edges).all(|(pair, edge_id)| {
+                self.physical_graph
+                    .get_transit_edge_by_id(*edge_id)
+                    .is_some_and(|edge| {
+                        (edge.source, edge.target) == (pair[0], pair[1])
+                            || (edge.target, edge.source) == (pair[0], pair[1])
+                    })
+            })
+    }
+
+    /// Returns the minimum running time over a sequence of edges, or `None` if an edge does not exist.
+    fn running_time(&self, edges: &[EdgeId], max_speed: f64) -> Option<f64> {
+        edges
+            .iter()
+            .map(|edge_id| {
+                let edge = self.physical_graph.get_transit_edge_by_id(*edge_id)?;
+                Some(self.edge_time(edge, max_speed))
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Stop, TransitNode},
+        operations::TransitNetworkModifier,
+        simulation::fixtures::create_network,
+    };
+    use geo::{coord, Coord, LineString};
+
+    fn stop(node_id: NodeId, arrival: f64, departure: f64) -> Stop {
+        Stop {
+            node_id,
+            arrival,
+            departure,
+        }
+    }
+
+    #[test]
+    fn test_is_traversable() {
+        let network = create_network();
+
+        assert!(network.is_traversable(&[0, 1, 2]));
+        assert!(network.is_traversable(&[3, 1, 0]));
+        assert!(!network.is_traversable(&[2, 1, 3]));
+        assert!(!network.is_traversable(&[0, 2]));
+        assert!(!network.is_traversable(&[]));
+    }
+
+    #[test]
+    fn test_is_traversable_along() {
+        let network = create_network();
+
+        assert!(network.is_traversable_along(&[0, 1, 2], &[1, 2]));
+        assert!(network.is_traversable_along(&[3, 1, 0], &[3, 1]));
+        assert!(!network.is_traversable_along(&[2, 1, 3], &[2, 3]));
+        assert!(!network.is_traversable_along(&[0, 1, 2], &[1, 3]));
+        assert!(!network.is_traversable_along(&[0, 1, 2], &[1]));
+        assert!(!network.is_traversable_along(&[], &[]));
+    }
+
+    #[test]
+    fn test_bind_service() {
+        let network = create_network();
+        let mut service = Service::new(
+            1,
+            vec![stop(0, 0.0, 0.0), stop(2, 20.0, 30.0), stop(0, 50.0, 50.0)],
+        );
+
+        assert!(network.bind_service(&mut service));
+
+        assert_eq!(service.paths, vec![vec![0, 1, 2], vec![2, 1, 0]]);
+        assert_eq!(service.edges, vec![vec![1, 2], vec![2, 1]]);
+        assert_eq!(service.nodes(), vec![0, 1, 2, 1, 0]);
+
+        let mut unreachable = Service::new(2, vec![stop(2, 0.0, 0.0), stop(3, 60.0, 60.0)]);
+        assert!(!network.bind_service(&mut unreachable));
+        assert!(unreachable.paths.is_empty());
+    }
+
+    #[test]
+    fn test_bind_service_parallel_edges() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        for (id, x) in [(0, 0.0), (1, 100.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 0.0 },
+            });
+        }
+        network.add_edge(TransitEdge {
+            id: 1,
+            source: 0,
+            target: 1,
+            length: 150.0,
+            path: LineString::from(vec![(0.0, 0.0), (50.0, 50.0), (100.0, 0.0)]),
+        });
+        network.add_edge(TransitEdge {
+            id: 2,
+            source: 0,
+            target: 1,
+            length: 100.0,
+            path: LineString::from(vec![(0.0, 0.0), (100.0, 0.0)]),
+        });
+        network.set_speed_limit(1, 1.0);
+        let mut service = Service::new(1, vec![stop(0, 0.0, 0.0), stop(1, 20.0, 20.0)]);
+
+        assert!(network.bind_service(&mut service));
+        assert_eq!(service.edges, vec![vec![2]]);
+
+        let mut timetable = Timetable::new();
+        timetable.add_service(service.clone());
+        service.edges = vec![vec![1]];
+        timetable.add_service(Service { id: 2, ..service });
+        let options = TimetableOptions {
+            max_speed: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            network.validate_timetable(&timetable, &options),
+            vec![TimetableIssue::InfeasibleRunningTime {
+                service_id: 2,
+                stop_index: 1,
+                scheduled: 20.0,
+                minimum: 150.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_timetable_bound_edges() {
+        let mut network = create_network();
+        network.add_edge(TransitEdge {
+            id: 4,
+            source: 0,
+            target: 1,
+            length: 100.0,
+            path: LineString::from(vec![(0.0, 0.0), (50.0, 10.0), (100.0, 0.0)]),
+        });
+        // Edge 4 can only be used from node 1 to node 0.
+        network.topology_graph.graph.retain_edges(|graph, edge| {
+            let (source, _) = graph.edge_endpoints(edge).unwrap();
+            graph[edge].edge_id != 4 || graph[source].node_id != 0
+        });
+        let mut timetable = Timetable::new();
+        for (id, edge_id) in [(1, 1), (2, 4)] {
+            let mut service = Service::new(id, vec![stop(0, 0.0, 0.0), stop(1, 60.0, 60.0)]);
+            service.paths = vec![vec![0, 1]];
+            service.edges = vec![vec![edge_id]];
+            timetable.add_service(service);
+        }
+
+        assert_eq!(
+            network.validate_timetable(&timetable, &TimetableOptions::default()),
+            vec![TimetableIssue::Unreachable {
+                service_id: 2,
+                stop_index: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_timetable() {
+        let mut network = create_network();
+        network.set_speed_limit(2, 5.0);
+        let options = TimetableOptions {
+            max_speed: 10.0,
+            ..Default::default()
+        };
+        let mut timetable = Timetable::new();
+        let mut bound = Service::new(
+            1,
+            vec![stop(0, 0.0, 0.0), stop(2, 30.0, 30.0), stop(3, 60.0, 50.0)],
+        );
+        bound.paths = vec![vec![0, 1, 2], vec![2, 1, 3]];
+        bound.edges = vec![vec![1, 2], vec![2, 3]];
+        timetable.add_service(bound);
+        timetable.add_service(Service::new(
+            2,
+            vec![stop(0, 0.0, 0.0), stop(3, 15.0, 15.0)],
+        ));
+        timetable.add_service(Service::new(2, vec![]));
+        timetable.add_service(Service::new(3, vec![stop(0, 0.0, 0.0)]));
+        timetable.add_service(Service::new(
+            4,
+            vec![stop(0, 0.0, 0.0), stop(9, 10.0, 10.0)],
+        ));
+
+        let issues = network.validate_timetable(&timetable, &options);
+
+        assert_eq!(
+            issues,
+            vec![
+                TimetableIssue::NegativeDwell {
+                    service_id: 1,
+                    stop_index: 2,
+                    dwell: -10.0
+                },
+                TimetableIssue::Unreachable {
+                    service_id: 1,
+                    stop_index: 2
+                },
+                TimetableIssue::InfeasibleRunningTime {
+                    service_id: 2,
+                    stop_index: 1,
+                    scheduled: 15.0,
+                    minimum: 20.0
+                },
+                TimetableIssue::DuplicateService { service_id: 2 },
+                TimetableIssue::TooFewStops { service_id: 3 },
+                TimetableIssue::UnknownNode {
+                    service_id: 4,
+                    stop_index: 1,
+                    node_id: 9
+                },
+            ]
+        );
+        assert_eq!(issues[0].service_id(), 1);
+    }
+}
@@ -0,0 +1,534 @@
+//! Bulk loading and export of networks as a pair of CSV files.
+//!
+//! The nodes file has the columns `id`, `x` and `y`. The edges file has the columns `id`, `source`, `target`,
+//! an optional `length` and `wkt` with the path as WKT `LINESTRING`. Columns are matched by their header,
+//! so their order does not matter and additional columns are ignored.
+//!
+//! `read_csv` streams both files row by row. Rows that cannot be loaded are skipped and reported as `RowError`s
+//! with their line number, so that a few bad rows do not prevent loading the rest of the network.
+//!
+//! The file does not say which edges a train can continue onto at a node, so it is derived from the geometry:
+//! edges leaving a node in roughly the same direction lie on the same side of it, and trains pass from one side
+//! to the other.
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use geo::{Coord, EuclideanDistance, LineString};
+
+use super::{sides::wire, Error};
+use crate::{
+    core::{measure, Crs, EdgeId, NodeId, TransitEdge, TransitNode},
+    graphs::TransitNetwork,
+    operations::{TransitNetworkModifier, TransitNetworkRepairer},
+};
+
+/// Options for `read_csv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The coordinate reference system of the files, stored in the network. Edges without a length are
+    /// measured in it like `TransitNetwork::path_length` does, so in meters for `Crs::Wgs84`.
+    pub crs: Option<Crs>,
+    /// The field delimiter of both files.
+    pub delimiter: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            crs: None,
+            delimiter: b',',
+        }
+    }
+}
+
+/// A row of an input file that could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// The file of the row, `nodes` or `edges`.
+    pub file: &'static str,
+    /// The line number of the row, starting at 1 for the header.
+    pub line: u64,
+    /// The reason the row was skipped.
+    pub message: String,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} line {}: {}", self.file, self.line, self.message)
+    }
+}
+
+/// The result of `read_csv`.
+#[derive(Debug, Clone)]
+pub struct CsvImport<R: Copy> {
+    /// The loaded network.
+    pub network: TransitNetwork<R, f64>,
+    /// The rows that were skipped, in the order they were read.
+    pub errors: Vec<RowError>,
+}
+
+/// Parses a WKT `LINESTRING`, ignoring Z and M values.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, LineString};
+/// use transit_grid::io::csv_wkt::parse_wkt_linestring;
+///
+/// let path = parse_wkt_linestring("LINESTRING (0 0, 1.5 2)").unwrap();
+/// assert_eq!(path, LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.5, y: 2.0 }]));
+/// ```
+pub fn parse_wkt_linestring(wkt: &str) -> Result<LineString<f64>, String> {
+    let wkt = wkt.trim();
+    let body = wkt
+        .get(..10)
+        .filter(|keyword| keyword.eq_ignore_ascii_case("LINESTRING"))
+        .map(|_| wkt[10..].trim_start())
+        .ok_or_else(|| format!("expected LINESTRING, found {}", wkt))?;
+    let body = body
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .trim();
+    let coords = body
+        .strip_prefix('(')
+        .and_then(|body| body.strip_suffix(')'))
+        .ok_or_else(|| format!("invalid LINESTRING {}", wkt))?;
+    let coords = coords
+        .split(',')
+        .map(|coord| {
+            let mut values = coord.split_whitespace().map(str::parse::<f64>);
+            match (values.next(), values.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Ok(Coord { x, y }),
+                _ => Err(format!("invalid coordinate {}", coord.trim())),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if coords.len() < 2 {
+        return Err("LINESTRING needs at least two coordinates".to_string());
+    }
+    Ok(LineString(coords))
+}
+
+/// Formats a path as WKT `LINESTRING`.
+pub fn to_wkt_linestring(path: &LineString<f64>) -> String {
+    let coords: Vec<String> = path
+        .0
+        .iter()
+        .map(|coord| format!("{} {}", coord.x, coord.y))
+        .collect();
+    format!("LINESTRING ({})", coords.join(", "))
+}
+
+/// The positions of the columns of a CSV file.
+struct Columns(Vec<Option<usize>>);
+
+impl Columns {
+    fn new(headers: &csv::StringRecord, names: &[&str], required: usize) -> Result<Self, Error> {
+        let columns: Vec<Option<usize>> = names
+            .iter()
+            .map(|name| headers.iter().position(|header| header.trim() == *name))
+            .collect();
+        if let Some(missing) = names[..required]
+            .iter()
+            .zip(&columns)
+            .find(|(_, column)| column.is_none())
+        {
+            return Err(Error::Invalid(format!("missing column {}", missing.0)));
+        }
+        Ok(Columns(columns))
+    }
+
+    fn get<'a>(&self, record: &'a csv::StringRecord, column: usize) -> &'a str {
+        self.0[column]
+            .and_then(|index| record.get(index))
+            .unwrap_or_default()
+            .trim()
+    }
+}
+
+fn parse<V: std::str::FromStr>(value: &str, name: &str) -> Result<V, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {} {:?}", name, value))
+}
+
+fn csv_reader<D: Read>(data: D, options: &CsvOptions) -> csv::Reader<D> {
+    csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_reader(data)
+}
+
+/// Reads a network from a nodes and an edges file and repairs the direction of the edge paths.
+///
+/// # Arguments
+///
+/// * `nodes` - The reader of the nodes file.
+/// * `edges` - The reader of the edges file.
+/// * `options` - The `CsvOptions` to apply.
+///
+/// # Returns
+///
+/// * `Result<CsvImport<R>, Error>` - The network and the skipped rows, or an error if a file cannot be read
+///   or lacks a required column.
+///
+/// # Example
+///
+/// ```
+/// use geo::Coord;
+/// use transit_grid::io::csv_wkt::{read_csv, CsvOptions};
+///
+/// let nodes = "id,x,y\n1,0,0\n2,3,4\n";
+/// let edges = "id,source,target,wkt\n1,1,2,\"LINESTRING (0 0, 3 4)\"\n2,1,3,\"LINESTRING (0 0, 1 1)\"\n";
+///
+/// let import = read_csv::<Coord, _, _>(nodes.as_bytes(), edges.as_bytes(), &CsvOptions::default()).unwrap();
+/// assert_eq!(import.network.get_edge_by_id(1).unwrap().length, 5.0);
+/// assert_eq!(import.errors[0].to_string(), "edges line 3: unknown target node 3");
+/// ```
+pub fn read_csv<R, N, E>(nodes: N, edges: E, options: &CsvOptions) -> Result<CsvImport<R>, Error>
+where
+    R: Copy + From<Coord<f64>> + EuclideanDistance<f64, Coord<f64>>,
+    N: Read,
+    E: Read,
+{
+    let mut network = TransitNetwork::new();
+    network.crs = options.crs;
+    let mut errors = Vec::new();
+    let mut node_ids = std::collections::HashSet::new();
+    let mut edge_ids = std::collections::HashSet::new();
+
+    let mut reader = csv_reader(nodes, options);
+    let columns = Columns::new(reader.headers()?, &["id", "x", "y"], 3)?;
+    for record in reader.records() {
+        let row = record.map_err(|error| (error.position().map(|p| p.line()), error.to_string()));
+        let result = row.and_then(|record| {
+            let line = record.position().map(|position| position.line());
+            let node = (|| {
+                let id: NodeId = parse(columns.get(&record, 0), "id")?;
+                let x: f64 = parse(columns.get(&record, 1), "x")?;
+                let y: f64 = parse(columns.get(&record, 2), "y")?;
+                if !node_ids.insert(id) {
+                    return Err(format!("duplicate node {}", id));
+                }
+                Ok(TransitNode {
+                    id,
+                    location: R::from(Coord { x, y }),
+                })
+            })();
+            node.map_err(|message| (line, message))
+        });
+        match result {
+            Ok(node) => {
+                network.add_node(node);
+            }
+            Err((line, message)) => errors.push(RowError {
+                file: "nodes",
+                line: line.unwrap_or_default(),
+                message,
+            }),
+        }
+    }
+
+    let mut reader = csv_reader(edges, options);
+    let columns = Columns::new(
+        reader.headers()?,
+        &["id", "source", "target", "wkt", "length"],
+        4,
+    )?;
+    for record in reader.records() {
+        let row = record.map_err(|error| (error.position().map(|p| p.line()), error.to_string()));
+        let result = row.and_then(|record| {
+            let line = record.position().map(|position| position.line());
+            let edge = (|| {
+                let id: EdgeId = parse(columns.get(&record, 0), "id")?;
+                let source: NodeId = parse(columns.get(&record, 1), "source")?;
+                let target: NodeId = parse(columns.get(&record, 2), "target")?;
+                let path = parse_wkt_linestring(columns.get(&record, 3))?;
+                for (node_id, name) in [(source, "source"), (target, "target")] {
+                    if !node_ids.contains(&node_id) {
+                        return Err(format!("unknown {} node {}", name, node_id));
+                    }
+                }
+                let length = match columns.get(&record, 4) {
+                    "" => None,
+                    length => Some(parse::<f64>(length, "length")?),
+                };
+                if edge_ids.contains(&id) {
+                    return Err(format!("duplicate edge {}", id));
+                }
+                Ok(TransitEdge {
+                    id,
+                    source,
+                    target,
+                    length: length.unwrap_or_else(|| measure(options.crs, &path)),
+                    path,
+                })
+            })();
+            edge.map_err(|message| (line, message))
+        });
+        match result {
+            Ok(edge) => {
+                edge_ids.insert(edge.id);
+                network.physical_graph.add_transit_edge(edge);
+            }
+            Err((line, message)) => errors.push(RowError {
+                file: "edges",
+                line: line.unwrap_or_default(),
+                message,
+            }),
+        }
+    }
+
+    network.repair();
+    let sides = geometric_sides(&network);
+    wire(&mut network, &sides);
+    Ok(CsvImport { network, errors })
+}
+
+/// Assigns the edges at each node to a side by comparing the direction in which they leave the node
+/// with the first non-degenerate direction of the edge with the lowest ID.
+///
+/// The two edges of a node with exactly two edges always lie on opposite sides, however sharp the curve.
+fn geometric_sides<R: Copy>(network: &TransitNetwork<R, f64>) -> HashMap<(NodeId, EdgeId), bool> {
+    let mut directions: Vec<(NodeId, EdgeId, Coord<f64>)> = Vec::new();
+    for edge in network.physical_graph.graph.edge_weights() {
+        let ends = [
+            (edge.source, edge.path.0.clone()),
+            (edge.target, edge.path.0.iter().rev().copied().collect()),
+        ];
+        for (node, path) in ends {
+            let start = path[0];
+            let direction = path
+                .iter()
+                .find(|coord| **coord != start)
+                .map(|coord| *coord - start)
+                .unwrap_or_default();
+            directions.push((node, edge.id, direction));
+        }
+    }
+    directions.sort_by_key(|(node, edge, _)| (*node, *edge));
+
+    let mut sides = HashMap::new();
+    for incident in directions.chunk_by(|a, b| a.0 == b.0) {
+        if let [(node, first, _), (_, second, _)] = incident {
+            sides.insert((*node, *first), false);
+            sides.insert((*node, *second), true);
+            continue;
+        }
+        let reference = incident
+            .iter()
+            .map(|(_, _, direction)| *direction)
+            .find(|direction| *direction != Coord::default())
+            .unwrap_or_default();
+        for (node, edge, direction) in incident {
+            let dot = direction.x * reference.x + direction.y * reference.y;
+            sides.insert((*node, *edge), dot < 0.0);
+        }
+    }
+    sides
+}
+
+/// Writes a network as a nodes and an edges file in the layout read by `read_csv`.
+///
+/// # Arguments
+///
+/// * `network` - The network to write.
+/// * `nodes` - The writer of the nodes file.
+/// * `edges` - The writer of the edges file.
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - An error if writing fails.
+pub fn write_csv<R, N, E>(network: &TransitNetwork<R, f64>, nodes: N, edges: E) -> Result<(), Error>
+where
+    R: Copy + Into<Coord<f64>>,
+    N: Write,
+    E: Write,
+{
+    let mut node_list: Vec<&TransitNode<R>> = network.physical_graph.graph.node_weights().collect();
+    node_list.sort_by_key(|node| node.id);
+    let mut writer = csv::Writer::from_writer(nodes);
+    writer.write_record(["id", "x", "y"])?;
+    for node in node_list {
+        let location: Coord<f64> = node.location.into();
+        writer.write_record([
+            node.id.to_string(),
+            location.x.to_string(),
+            location.y.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    let mut edge_list: Vec<&TransitEdge<f64>> =
+        network.physical_graph.graph.edge_weights().collect();
+    edge_list.sort_by_key(|edge| edge.id);
+    let mut writer = csv::Writer::from_writer(edges);
+    writer.write_record(["id", "source", "target", "length", "wkt"])?;
+    for edge in edge_list {
+        writer.write_record([
+            edge.id.to_string(),
+            edge.source.to_string(),
+            edge.target.to_string(),
+            edge.length.to_string(),
+            to_wkt_linestring(&edge.path),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::ShortestPath;
+    use geo::coord;
+
+    const NODES: &str = "id,x,y,name
+1,0,0,A
+2,1,0,B
+3,2,0,C
+x,3,0,broken
+2,5,5,duplicate
+";
+
+    const EDGES: &str = "id,source,target,wkt,length
+10,1,2,\"LINESTRING (1 0, 0.5 0.1, 0 0)\",
+11,2,3,\"LINESTRING (1 0, 2 0)\",7.5
+12,3,4,\"LINESTRING (2 0, 3 0)\",
+13,1,3,POINT (0 0),
+10,2,3,\"LINESTRING (1 0, 2 0)\",
+";
+
+    #[test]
+    fn test_read_csv() {
+        let import =
+            read_csv::<Coord, _, _>(NODES.as_bytes(), EDGES.as_bytes(), &CsvOptions::default())
+                .unwrap();
+        let network = &import.network;
+
+        assert_eq!(network.physical_graph.graph.node_count(), 3);
+        assert_eq!(network.physical_graph.graph.edge_count(), 2);
+
+        let edge = network.get_edge_by_id(10).unwrap();
+        assert_eq!(edge.path.0.first(), Some(&coord! { x: 0.0, y: 0.0 }));
+        assert!((edge.length - 2.0 * 0.5f64.hypot(0.1)).abs() < 1e-12);
+        assert_eq!(network.get_edge_by_id(11).unwrap().length, 7.5);
+        assert_eq!(network.find_shortest_path(1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(network.find_shortest_path(3, 1), Some(vec![3, 2, 1]));
+
+        let errors: Vec<String> = import.errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "nodes line 5: invalid id \"x\"",
+                "nodes line 6: duplicate node 2",
+                "edges line 4: unknown target node 4",
+                "edges line 5: expected LINESTRING, found POINT (0 0)",
+                "edges line 6: duplicate edge 10",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_csv_switch_sides() {
+        let nodes = "id,x,y\n1,0,0\n2,1,0\n3,2,1\n4,2,-1\n";
+        let edges = "id,source,target,wkt
+1,1,2,\"LINESTRING (0 0, 1 0)\"
+2,3,2,\"LINESTRING (2 1, 1 0)\"
+3,2,4,\"LINESTRING (1 0, 2 -1)\"
+";
+
+        let import =
+            read_csv::<Coord, _, _>(nodes.as_bytes(), edges.as_bytes(), &CsvOptions::default())
+                .unwrap();
+
+        assert_eq!(import.network.find_shortest_path(1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(import.network.find_shortest_path(4, 1), Some(vec![4, 2, 1]));
+        assert_eq!(import.network.find_shortest_path(3, 4), None);
+    }
+
+    #[test]
+    fn test_read_csv_sharp_curve() {
+        let nodes = "id,x,y\n1,0,0\n2,1,0\n3,1,1\n4,1,-1\n";
+        let edges = "id,source,target,wkt
+1,1,2,\"LINESTRING (0 0, 1 0)\"
+2,2,3,\"LINESTRING (1 0, 1 0, 1 1)\"
+3,3,4,\"LINESTRING (1 1, 0.5 0.5, 1 -1)\"
+";
+
+        let import =
+            read_csv::<Coord, _, _>(nodes.as_bytes(), edges.as_bytes(), &CsvOptions::default())
+                .unwrap();
+
+        // Node 2 is a 90 degree curve and node 3 a curve of more than 90 degrees.
+        assert_eq!(
+            import.network.find_shortest_path(1, 4),
+            Some(vec![1, 2, 3, 4])
+        );
+        assert_eq!(
+            import.network.find_shortest_path(4, 1),
+            Some(vec![4, 3, 2, 1])
+        );
+    }
+
+    #[test]
+    fn test_read_csv_geographic_and_delimiter() {
+        let nodes = "id;x;y\n1;0;0\n2;0;1\n";
+        let edges = "id;source;target;wkt\n1;1;2;LINESTRING Z (0 0 5, 0 1 5)\n";
+        let options = CsvOptions {
+            crs: Some(Crs::Wgs84),
+            delimiter: b';',
+        };
+
+        let import = read_csv::<Coord, _, _>(nodes.as_bytes(), edges.as_bytes(), &options).unwrap();
+
+        let length = import.network.get_edge_by_id(1).unwrap().length;
+        assert!((length - 111_195.0).abs() < 100.0);
+        assert_eq!(import.network.crs, Some(Crs::Wgs84));
+        assert!(import.errors.is_empty());
+    }
+
+    #[test]
+    fn test_read_csv_missing_column() {
+        let result = read_csv::<Coord, _, _>(
+            "id,x\n1,0\n".as_bytes(),
+            EDGES.as_bytes(),
+            &CsvOptions::default(),
+        );
+        assert!(matches!(result, Err(Error::Invalid(message)) if message == "missing column y"));
+    }
+
+    #[test]
+    fn test_write_csv_round_trip() {
+        let import =
+            read_csv::<Coord, _, _>(NODES.as_bytes(), EDGES.as_bytes(), &CsvOptions::default())
+                .unwrap();
+        let (mut nodes, mut edges) = (Vec::new(), Vec::new());
+
+        write_csv(&import.network, &mut nodes, &mut edges).unwrap();
+
+        let edges_text = String::from_utf8(edges.clone()).unwrap();
+        assert!(edges_text.starts_with("id,source,target,length,wkt\n"));
+        assert!(edges_text.contains("11,2,3,7.5,\"LINESTRING (1 0, 2 0)\"\n"));
+
+        let reloaded =
+            read_csv::<Coord, _, _>(nodes.as_slice(), edges.as_slice(), &CsvOptions::default())
+                .unwrap();
+        assert!(reloaded.errors.is_empty());
+        for edge_id in [10, 11] {
+            assert_eq!(
+                reloaded.network.get_edge_by_id(edge_id),
+                import.network.get_edge_by_id(edge_id)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_wkt_linestring() {
+        assert!(parse_wkt_linestring("linestring(0 0,1 1)").is_ok());
+        assert!(parse_wkt_linestring("LINESTRING EMPTY").is_err());
+        assert!(parse_wkt_linestring("LINESTRING (0 0)").is_err());
+        assert!(parse_wkt_linestring("LINESTRING (0 a, 1 1)").is_err());
+    }
+}
@@ -0,0 +1,445 @@
+//! Export of the physical and the topology graph to GraphViz DOT and GraphML.
+//!
+//! Unlike `petgraph::dot::Dot`, the exporters label nodes with their `NodeId` and edges with their `EdgeId`.
+//! In the topology graph, the first `TopoNode` of a node is labelled `entry` and the second `exit`,
+//! following the `v_entry`/`v_exit` notation of `TopologyGraph`, and the pairing `σ` between them is drawn
+//! as an undirected dashed edge.
+use std::{collections::HashMap, fmt::Write};
+
+use geo::{Coord, CoordNum};
+use petgraph::{
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+};
+
+use crate::{
+    core::NodeId,
+    graphs::{PhysicalGraph, TopologyGraph, TransitNetwork},
+};
+
+/// Options for `GraphExport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Whether node locations are written as positions, `pos` in DOT and `x`/`y` in GraphML.
+    /// Positions are only available for graphs that know the node locations.
+    pub positions: bool,
+    /// Whether the pairing `σ` between the two `TopoNode`s of a node is drawn.
+    pub twins: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            positions: false,
+            twins: true,
+        }
+    }
+}
+
+/// The `GraphExport` trait writes a graph in formats understood by graph visualisation tools.
+pub trait GraphExport {
+    /// Writes the graph in the GraphViz DOT language.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `ExportOptions` to apply.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The DOT document.
+    fn to_dot(&self, options: &ExportOptions) -> String;
+
+    /// Writes the graph as GraphML.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `ExportOptions` to apply.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The GraphML document.
+    fn to_graphml(&self, options: &ExportOptions) -> String;
+}
+
+struct ExportNode {
+    id: String,
+    label: String,
+    position: Option<(f64, f64)>,
+}
+
+struct ExportEdge {
+    source: String,
+    target: String,
+    label: String,
+    twin: bool,
+}
+
+/// A graph prepared for export, independent of the graph type.
+struct ExportGraph {
+    directed: bool,
+    nodes: Vec<ExportNode>,
+    edges: Vec<ExportEdge>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl ExportGraph {
+    fn dot(&self) -> String {
+        let (keyword, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = format!("{} {{\n", keyword);
+        for node in &self.nodes {
+            let position = node
+                .position
+                .map(|(x, y)| format!(", pos=\"{},{}!\"", x, y))
+                .unwrap_or_default();
+            writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\"{}];",
+                node.id,
+                node.label.replace('"', "\\\""),
+                position
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            let style = if edge.twin {
+                ", style=dashed, dir=none, constraint=false"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    \"{}\" {} \"{}\" [label=\"{}\"{}];",
+                edge.source,
+                arrow,
+                edge.target,
+                edge.label.replace('"', "\\\""),
+                style
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn graphml(&self) -> String {
+        let mut graphml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20 <key id=\"label\" for=\"all\" attr.name=\"label\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"twin\" for=\"edge\" attr.name=\"twin\" attr.type=\"boolean\">\n\
+             \x20   <default>false</default>\n\
+             \x20 </key>\n",
+        );
+        let edge_default = if self.directed {
+            "directed"
+        } else {
+            "undirected"
+        };
+        writeln!(
+            graphml,
+            "  <graph id=\"G\" edgedefault=\"{}\">",
+            edge_default
+        )
+        .unwrap();
+        for node in &self.nodes {
+            writeln!(graphml, "    <node id=\"{}\">", escape(&node.id)).unwrap();
+            writeln!(
+                graphml,
+                "      <data key=\"label\">{}</data>",
+                escape(&node.label)
+            )
+            .unwrap();
+            if let Some((x, y)) = node.position {
+                writeln!(graphml, "      <data key=\"x\">{}</data>", x).unwrap();
+                writeln!(graphml, "      <data key=\"y\">{}</data>", y).unwrap();
+            }
+            graphml.push_str("    </node>\n");
+        }
+        for edge in &self.edges {
+            let directed = if edge.twin { " directed=\"false\"" } else { "" };
+            writeln!(
+                graphml,
+                "    <edge source=\"{}\" target=\"{}\"{}>",
+                escape(&edge.source),
+                escape(&edge.target),
+                directed
+            )
+            .unwrap();
+            writeln!(
+                graphml,
+                "      <data key=\"label\">{}</data>",
+                escape(&edge.label)
+            )
+            .unwrap();
+            if edge.twin {
+                graphml.push_str("      <data key=\"twin\">true</data>\n");
+            }
+            graphml.push_str("    </edge>\n");
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+}
+
+fn position<T: CoordNum>(coord: Coord<T>) -> Option<(f64, f64)> {
+    Some((coord.x.to_f64()?, coord.y.to_f64()?))
+}
+
+fn physical_export<R, T>(graph: &PhysicalGraph<R, T>, options: &ExportOptions) -> ExportGraph
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordNum,
+{
+    let mut nodes: Vec<_> = graph.graph.node_weights().collect();
+    nodes.sort_by_key(|node| node.id);
+    let mut edges: Vec<_> = graph.graph.edge_weights().collect();
+    edges.sort_by_key(|edge| edge.id);
+    ExportGraph {
+        directed: false,
+        nodes: nodes
+            .into_iter()
+            .map(|node| ExportNode {
+                id: format!("n{}", node.id),
+                label: node.id.to_string(),
+                position: options
+                    .positions
+                    .then(|| position(node.location.into()))
+                    .flatten(),
+            })
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(|edge| ExportEdge {
+                source: format!("n{}", edge.source),
+                target: format!("n{}", edge.target),
+                label: edge.id.to_string(),
+                twin: false,
+            })
+            .collect(),
+    }
+}
+
+fn topology_export(
+    graph: &TopologyGraph,
+    locations: Option<&HashMap<NodeId, (f64, f64)>>,
+    options: &ExportOptions,
+) -> ExportGraph {
+    let toponode_id = |index: NodeIndex| {
+        let node_id = graph.graph[index].node_id;
+        let entry = graph.id_to_index(node_id).map(|pair| pair.0) == Some(index);
+        let name = if entry { "entry" } else { "exit" };
+        (node_id, entry, format!("n{}_{}", node_id, name))
+    };
+
+    // Twins share the location of their node and are shifted apart slightly to stay visible.
+    let offset = locations
+        .map(|locations| {
+            let xs = locations.values().map(|(x, _)| *x);
+            let extent =
+                xs.clone().fold(f64::NEG_INFINITY, f64::max) - xs.fold(f64::INFINITY, f64::min);
+            if extent > 0.0 {
+                extent * 0.01
+            } else {
+                0.5
+            }
+        })
+        .unwrap_or_default();
+
+    let mut nodes: Vec<_> = graph.graph.node_indices().map(toponode_id).collect();
+    nodes.sort_by_key(|(node_id, entry, _)| (*node_id, !*entry));
+    let mut export = ExportGraph {
+        directed: true,
+        nodes: nodes
+            .iter()
+            .map(|(node_id, entry, id)| ExportNode {
+                id: id.clone(),
+                label: format!("{} {}", node_id, if *entry { "entry" } else { "exit" }),
+                position: locations
+                    .filter(|_| options.positions)
+                    .and_then(|locations| locations.get(node_id))
+                    .map(|(x, y)| {
+                        if *entry {
+                            (x - offset, *y)
+                        } else {
+                            (x + offset, *y)
+                        }
+                    }),
+            })
+            .collect(),
+        edges: graph
+            .graph
+            .edge_references()
+            .map(|edge| ExportEdge {
+                source: toponode_id(edge.source()).2,
+                target: toponode_id(edge.target()).2,
+                label: edge.weight().edge_id.to_string(),
+                twin: false,
+            })
+            .collect(),
+    };
+    if options.twins {
+        for (node_id, entry, id) in &nodes {
+            if *entry {
+                export.edges.push(ExportEdge {
+                    source: id.clone(),
+                    target: format!("n{}_exit", node_id),
+                    label: "σ".to_string(),
+                    twin: true,
+                });
+            }
+        }
+    }
+    export
+}
+
+impl<R, T> GraphExport for PhysicalGraph<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordNum,
+{
+    fn to_dot(&self, options: &ExportOptions) -> String {
+        physical_export(self, options).dot()
+    }
+
+    fn to_graphml(&self, options: &ExportOptions) -> String {
+        physical_export(self, options).graphml()
+    }
+}
+
+/// Exports the topology graph without positions, as it does not know the node locations.
+impl GraphExport for TopologyGraph {
+    fn to_dot(&self, options: &ExportOptions) -> String {
+        topology_export(self, None, options).dot()
+    }
+
+    fn to_graphml(&self, options: &ExportOptions) -> String {
+        topology_export(self, None, options).graphml()
+    }
+}
+
+/// Exports the topology graph of the network, positioned at the locations of the physical nodes.
+impl<R, T> GraphExport for TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordNum,
+{
+    fn to_dot(&self, options: &ExportOptions) -> String {
+        topology_export(&self.topology_graph, Some(&self.locations()), options).dot()
+    }
+
+    fn to_graphml(&self, options: &ExportOptions) -> String {
+        topology_export(&self.topology_graph, Some(&self.locations()), options).graphml()
+    }
+}
+
+impl<R, T> TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordNum,
+{
+    fn locations(&self) -> HashMap<NodeId, (f64, f64)> {
+        self.physical_graph
+            .graph
+            .node_weights()
+            .filter_map(|node| Some((node.id, position(node.location.into())?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::{coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        for (id, x) in [(1, 0.0), (2, 10.0)] {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: x, y: 5.0 },
+            });
+        }
+        network.add_edge(TransitEdge {
+            id: 7,
+            source: 1,
+            target: 2,
+            length: 10.0,
+            path: LineString(vec![coord! { x: 0.0, y: 5.0 }, coord! { x: 10.0, y: 5.0 }]),
+        });
+        network
+    }
+
+    #[test]
+    fn test_physical_dot() {
+        let network = create_network();
+        let options = ExportOptions {
+            positions: true,
+            ..Default::default()
+        };
+
+        let dot = network.physical_graph.to_dot(&options);
+
+        assert!(dot.starts_with("graph {"));
+        assert!(dot.contains("\"n1\" [label=\"1\", pos=\"0,5!\"];"));
+        assert!(dot.contains("\"n1\" -- \"n2\" [label=\"7\"];"));
+    }
+
+    #[test]
+    fn test_topology_dot() {
+        let network = create_network();
+
+        let dot = network.topology_graph.to_dot(&ExportOptions::default());
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("\"n1_entry\" [label=\"1 entry\"];"));
+        assert!(dot.contains("\"n1_entry\" -> \"n2_entry\" [label=\"7\"];"));
+        assert!(dot.contains("\"n2_exit\" -> \"n1_exit\" [label=\"7\"];"));
+        assert!(dot.contains(
+            "\"n1_entry\" -> \"n1_exit\" [label=\"σ\", style=dashed, dir=none, constraint=false];"
+        ));
+
+        let options = ExportOptions {
+            positions: true,
+            twins: false,
+        };
+        let dot = network.to_dot(&options);
+        assert!(dot.contains("\"n2_exit\" [label=\"2 exit\", pos=\"10.1,5!\"];"));
+        assert!(!dot.contains("σ"));
+    }
+
+    #[test]
+    fn test_graphml() {
+        let network = create_network();
+        let options = ExportOptions {
+            positions: true,
+            ..Default::default()
+        };
+
+        let graphml = network.physical_graph.to_graphml(&options);
+        assert!(graphml.contains("<graph id=\"G\" edgedefault=\"undirected\">"));
+        assert!(graphml.contains("<data key=\"x\">10</data>"));
+        assert_eq!(graphml.matches("<edge ").count(), 1);
+
+        let graphml = network.to_graphml(&options);
+        assert!(graphml.contains("<graph id=\"G\" edgedefault=\"directed\">"));
+        assert!(graphml.contains("<data key=\"label\">2 exit</data>"));
+        assert!(graphml.contains("<data key=\"twin\">true</data>"));
+        assert_eq!(graphml.matches("<edge ").count(), 4);
+    }
+}
@@ -0,0 +1,590 @@
+//! Import of GTFS static feeds.
+//!
+//! `read_gtfs` reads the files `stops.txt`, `trips.txt`, `stop_times.txt` and, if present, `shapes.txt`
+//! from the directory of an extracted GTFS feed:
+//!
+//! * Every stop with coordinates becomes a `TransitNode` tagged as `NodeTag::Station`,
+//!   located at `(stop_lon, stop_lat)`.
+//! * Every pair of consecutive stops of a trip becomes a `TransitEdge`. Its path is the part of the trip's shape
+//!   between the two stops or, without a shape, the straight line between them. The length is the haversine length
+//!   of the path in meters. Edges are shared by all trips serving the same pair of stops in either direction.
+//! * Every trip becomes a `ServicePattern` with its stop times and the sequence of edges it uses.
+//!
+//! In the topology graph, the edges at each stop are split into two sides so that every trip can pass from the
+//! edge it arrives on to the edge it departs on. Movements that contradict the sides required by other trips,
+//! such as trips passing between all three pairs of edges at a stop, and reversals are not wired. They are
+//! returned in `GtfsFeed::unwired`.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use geo::{Closest, ClosestPoint, Coord, Line, LineString, Point};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{
+    sides::{assign_sides, wire},
+    Error,
+};
+use crate::{
+    core::{measure, Crs, EdgeId, NodeId, NodeTag, TransitEdge, TransitNode},
+    graphs::TransitNetwork,
+    operations::TransitNetworkModifier,
+};
+
+/// A stop of a `ServicePattern` with its scheduled times in seconds after midnight of the service day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternStop {
+    /// The node of the stop.
+    pub node_id: NodeId,
+    /// The scheduled arrival time, if given.
+    pub arrival: Option<u32>,
+    /// The scheduled departure time, if given.
+    pub departure: Option<u32>,
+}
+
+/// A trip of a GTFS feed attached to the imported network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServicePattern {
+    /// The `trip_id` of the trip.
+    pub trip_id: String,
+    /// The `route_id` of the trip.
+    pub route_id: String,
+    /// The `service_id` of the trip.
+    pub service_id: String,
+    /// The stops of the trip in order of their `stop_sequence`.
+    pub stops: Vec<PatternStop>,
+    /// The edges between consecutive stops.
+    pub edges: Vec<EdgeId>,
+}
+
+/// The result of importing a GTFS feed.
+#[derive(Debug, Clone)]
+pub struct GtfsFeed<R: Copy> {
+    /// The network of stops and the edges between them.
+    pub network: TransitNetwork<R, f64>,
+    /// The `NodeId` of every imported `stop_id`.
+    pub stops: HashMap<String, NodeId>,
+    /// The trips of the feed.
+    pub patterns: Vec<ServicePattern>,
+    /// The movements of trips that the topology graph does not allow, ordered by node and edges.
+    pub unwired: Vec<Movement>,
+}
+
+/// A trip passing a stop from the edge it arrives on to the edge it departs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Movement {
+    /// The node of the stop.
+    pub node_id: NodeId,
+    /// The edge the trip arrives on.
+    pub arrival: EdgeId,
+    /// The edge the trip departs on, equal to `arrival` for a reversal.
+    pub departure: EdgeId,
+}
+
+#[derive(Debug, Deserialize)]
+struct StopRecord {
+    stop_id: String,
+    #[serde(default)]
+    stop_lat: Option<f64>,
+    #[serde(default)]
+    stop_lon: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TripRecord {
+    route_id: String,
+    service_id: String,
+    trip_id: String,
+    #[serde(default)]
+    shape_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StopTimeRecord {
+    trip_id: String,
+    #[serde(default)]
+    arrival_time: Option<String>,
+    #[serde(default)]
+    departure_time: Option<String>,
+    stop_id: String,
+    stop_sequence: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShapeRecord {
+    shape_id: String,
+    shape_pt_lat: f64,
+    shape_pt_lon: f64,
+    shape_pt_sequence: u32,
+}
+
+fn read_records<D: DeserializeOwned>(path: &Path) -> Result<Vec<D>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let records = reader.deserialize().collect::<Result<Vec<D>, _>>()?;
+    Ok(records)
+}
+
+/// Parses a GTFS time of the form `H:MM:SS` into seconds. Hours may exceed 24.
+fn parse_time(time: &Option<String>) -> Result<Option<u32>, Error> {
+    let Some(time) = time.as_deref().filter(|time| !time.is_empty()) else {
+        return Ok(None);
+    };
+    let parts: Vec<u32> = time
+        .split(':')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| Error::Invalid(format!("invalid time {}", time)))?;
+    match parts[..] {
+        [hours, minutes, seconds] => Ok(Some(hours * 3600 + minutes * 60 + seconds)),
+        _ => Err(Error::Invalid(format!("invalid time {}", time))),
+    }
+}
+
+/// Returns the closest point of the shape to the given point, searching from the segment `start` onwards.
+fn project(shape: &[Coord<f64>], start: usize, point: Coord<f64>) -> (usize, Coord<f64>) {
+    let mut best = (start, shape[start], f64::INFINITY);
+    for i in start..shape.len() - 1 {
+        let closest = match Line::new(shape[i], shape[i + 1]).closest_point(&Point::from(point)) {
+            Closest::Intersection(closest) | Closest::SinglePoint(closest) => closest.0,
+            Closest::Indeterminate => shape[i],
+        };
+        let distance = (closest - point).x.hypot((closest - point).y);
+        if distance < best.2 {
+            best = (i, closest, distance);
+        }
+    }
+    (best.0, best.1)
+}
+
+/// Returns the part of the shape between two projected stops.
+fn shape_path(
+    shape: &[Coord<f64>],
+    from: (usize, Coord<f64>),
+    to: (usize, Coord<f64>),
+) -> LineString<f64> {
+    let mut coords = vec![from.1];
+    coords.extend(shape.iter().take(to.0 + 1).skip(from.0 + 1));
+    coords.push(to.1);
+    coords.dedup();
+    if coords.len() == 1 {
+        coords.push(to.1);
+    }
+    LineString(coords)
+}
+
+/// Reads an extracted GTFS feed from a directory.
+///
+/// The network is tagged with `Crs::Wgs84`, as GTFS locations are longitude and latitude.
+///
+/// # Arguments
+///
+/// * `directory` - The directory containing the feed's text files.
+///
+/// # Returns
+///
+/// * `Result<GtfsFeed<R>, Error>` - The imported network and trips, or an error if a file cannot be read or
+///   references unknown stops.
+///
+/// # Example
+///
+/// ```no_run
+/// use geo::Coord;
+/// use transit_grid::io::gtfs::read_gtfs;
+///
+/// let feed = read_gtfs::<Coord, _>("data/gtfs").unwrap();
+/// println!("{} trips", feed.patterns.len());
+/// ```
+pub fn read_gtfs<R, P>(directory: P) -> Result<GtfsFeed<R>, Error>
+where
+    R: Copy + From<Coord<f64>>,
+    P: AsRef<Path>,
+{
+    let directory = directory.as_ref();
+    let file = |name: &str| -> PathBuf { directory.join(name) };
+
+    let mut network = TransitNetwork::new().with_crs(Crs::Wgs84);
+    let mut stops: HashMap<String, NodeId> = HashMap::new();
+    let mut locations: HashMap<NodeId, Coord<f64>> = HashMap::new();
+    for stop in read_records::<StopRecord>(&file("stops.txt"))? {
+        let (Some(lat), Some(lon)) = (stop.stop_lat, stop.stop_lon) else {
+            continue;
+        };
+        let node_id = stops.len() as NodeId;
+        let location = Coord { x: lon, y: lat };
+        network.add_node(TransitNode {
+            id: node_id,
+            location: R::from(location),
+        });
+        network.tag_node(node_id, NodeTag::Station);
+        stops.insert(stop.stop_id, node_id);
+        locations.insert(node_id, location);
+    }
+
+    let mut shapes: HashMap<String, Vec<(u32, Coord<f64>)>> = HashMap::new();
+    if file("shapes.txt").exists() {
+        for point in read_records::<ShapeRecord>(&file("shapes.txt"))? {
+            shapes.entry(point.shape_id).or_default().push((
+                point.shape_pt_sequence,
+                Coord {
+                    x: point.shape_pt_lon,
+                    y: point.shape_pt_lat,
+                },
+            ));
+        }
+    }
+    let shapes: HashMap<String, Vec<Coord<f64>>> = shapes
+        .into_iter()
+        .map(|(shape_id, mut points)| {
+            points.sort_by_key(|(sequence, _)| *sequence);
+            (
+                shape_id,
+                points.into_iter().map(|(_, coord)| coord).collect(),
+            )
+        })
+        .collect();
+
+    let mut stop_times: HashMap<String, Vec<StopTimeRecord>> = HashMap::new();
+    for stop_time in read_records::<StopTimeRecord>(&file("stop_times.txt"))? {
+        stop_times
+            .entry(stop_time.trip_id.clone())
+            .or_default()
+            .push(stop_time);
+    }
+
+    let mut edges: HashMap<(NodeId, NodeId), EdgeId> = HashMap::new();
+    let mut movements: HashMap<NodeId, Vec<(EdgeId, EdgeId, bool)>> = HashMap::new();
+    let mut patterns = Vec::new();
+    for trip in read_records::<TripRecord>(&file("trips.txt"))? {
+        let mut times = stop_times.remove(&trip.trip_id).unwrap_or_default();
+        times.sort_by_key(|stop_time| stop_time.stop_sequence);
+
+        let mut pattern_stops = Vec::with_capacity(times.len());
+        for stop_time in &times {
+            let node_id = *stops.get(&stop_time.stop_id).ok_or_else(|| {
+                Error::Invalid(format!(
+                    "trip {} references unknown stop {}",
+                    trip.trip_id, stop_time.stop_id
+                ))
+            })?;
+            pattern_stops.push(PatternStop {
+                node_id,
+                arrival: parse_time(&stop_time.arrival_time)?,
+                departure: parse_time(&stop_time.departure_time)?,
+            });
+        }
+
+        let shape = trip
+            .shape_id
+            .as_ref()
+            .and_then(|shape_id| shapes.get(shape_id))
+            .filter(|shape| shape.len() > 1);
+        let mut projections = Vec::with_capacity(pattern_stops.len());
+        if let Some(shape) = shape {
+            let mut start = 0;
+            for stop in &pattern_stops {
+                let projection = project(shape, start, locations[&stop.node_id]);
+                start = projection.0;
+                projections.push(projection);
+            }
+        }
+
+        let mut pattern_edges = Vec::new();
+        for (i, pair) in pattern_stops.windows(2).enumerate() {
+            let (from, to) = (pair[0].node_id, pair[1].node_id);
+            if from == to {
+                continue;
+            }
+            let key = (from.min(to), from.max(to));
+            let edge_id = match edges.get(&key) {
+                Some(edge_id) => *edge_id,
+                None => {
+                    let edge_id = edges.len() as EdgeId;
+                    let path = match shape {
+                        Some(shape) => shape_path(shape, projections[i], projections[i + 1]),
+                        None => LineString(vec![locations[&from], locations[&to]]),
+                    };
+                    network.physical_graph.add_transit_edge(TransitEdge {
+                        id: edge_id,
+                        source: from,
+                        target: to,
+                        length: measure(network.crs, &path),
+                        path,
+                    });
+                    edges.insert(key, edge_id);
+                    edge_id
+                }
+            };
+            if let Some(&previous) = pattern_edges.last() {
+                movements
+                    .entry(from)
+                    .or_default()
+                    .push((previous, edge_id, false));
+            }
+            pattern_edges.push(edge_id);
+        }
+
+        patterns.push(ServicePattern {
+            trip_id: trip.trip_id,
+            route_id: trip.route_id,
+            service_id: trip.service_id,
+            stops: pattern_stops,
+            edges: pattern_edges,
+        });
+    }
+
+    let sides = assign_sides(&network, &movements);
+    wire(&mut network, &sides);
+
+    let mut unwired: Vec<Movement> = movements
+        .into_iter()
+        .flat_map(|(node_id, node_movements)| {
+            node_movements
+                .into_iter()
+                .map(move |(arrival, departure, _)| Movement {
+                    node_id,
+                    arrival,
+                    departure,
+                })
+        })
+        .filter(|movement| {
+            sides[&(movement.node_id, movement.arrival)]
+                == sides[&(movement.node_id, movement.departure)]
+        })
+        .collect();
+    unwired.sort();
+    unwired.dedup();
+
+    Ok(GtfsFeed {
+        network,
+        stops,
+        patterns,
+        unwired,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::ShortestPath;
+    use std::fs;
+
+    fn write_feed(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("transit-grid-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (file, content) in files {
+            fs::write(directory.join(file), content).unwrap();
+        }
+        directory
+    }
+
+    const STOPS: &str = "stop_id,stop_name,stop_lat,stop_lon
+A,Alpha,0.0,0.0
+B,Beta,0.01,0.0
+C,Gamma,0.02,0.0
+D,Delta,0.02,0.01
+P,Parent station,,
+";
+
+    const TRIPS: &str = "route_id,service_id,trip_id,shape_id
+R1,WD,T1,S1
+R1,WD,T2,
+R2,WD,T3,
+";
+
+    const STOP_TIMES: &str = "trip_id,arrival_time,departure_time,stop_id,stop_sequence
+T1,08:00:00,08:00:00,A,1
+T1,08:05:00,08:06:00,B,2
+T1,08:10:00,08:10:00,C,3
+T2,25:10:00,25:10:00,C,1
+T2,25:15:00,25:15:00,B,2
+T2,25:20:00,25:20:00,A,3
+T3,09:00:00,09:00:00,A,1
+T3,,,B,2
+T3,09:10:00,09:10:00,D,3
+";
+
+    const SHAPES: &str = "shape_id,shape_pt_lat,shape_pt_lon,shape_pt_sequence
+S1,0.0,0.0,1
+S1,0.005,0.001,2
+S1,0.01,0.0,3
+S1,0.02,0.0,4
+";
+
+    #[test]
+    fn test_read_gtfs() {
+        let directory = write_feed(
+            "gtfs",
+            &[
+                ("stops.txt", STOPS),
+                ("trips.txt", TRIPS),
+                ("stop_times.txt", STOP_TIMES),
+                ("shapes.txt", SHAPES),
+            ],
+        );
+
+        let feed = read_gtfs::<Coord, _>(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let network = &feed.network;
+        assert_eq!(network.crs, Some(Crs::Wgs84));
+        assert_eq!(feed.stops.len(), 4);
+        assert_eq!(network.physical_graph.graph.node_count(), 4);
+        assert_eq!(network.physical_graph.graph.edge_count(), 3);
+        assert_eq!(network.topology_graph.graph.edge_count(), 6);
+        assert!(network.has_tag(feed.stops["A"], NodeTag::Station));
+
+        let (a, b, c, d) = (
+            feed.stops["A"],
+            feed.stops["B"],
+            feed.stops["C"],
+            feed.stops["D"],
+        );
+        let edge = network.get_edge_by_id(0).unwrap();
+        assert_eq!((edge.source, edge.target), (a, b));
+        assert_eq!(edge.path.0.len(), 3);
+        assert_eq!(edge.path.0[1], Coord { x: 0.001, y: 0.005 });
+        assert!(edge.length > 1111.0);
+
+        assert_eq!(feed.patterns.len(), 3);
+        assert_eq!(feed.patterns[0].edges, vec![0, 1]);
+        assert_eq!(feed.patterns[1].edges, vec![1, 0]);
+        assert_eq!(feed.patterns[1].stops[0].arrival, Some(90600));
+        assert_eq!(feed.patterns[2].stops[1].arrival, None);
+        assert_eq!(feed.patterns[2].edges, vec![0, 2]);
+
+        assert_eq!(network.find_shortest_path(a, c), Some(vec![a, b, c]));
+        assert_eq!(network.find_shortest_path(c, a), Some(vec![c, b, a]));
+        assert_eq!(network.find_shortest_path(a, d), Some(vec![a, b, d]));
+        assert_eq!(network.find_shortest_path(c, d), None);
+    }
+
+    #[test]
+    fn test_read_gtfs_branches() {
+        let directory = write_feed(
+            "gtfs-branches",
+            &[
+                ("stops.txt", STOPS),
+                (
+                    "trips.txt",
+                    "route_id,service_id,trip_id\nR1,WD,T1\nR2,WD,T2\n",
+                ),
+                (
+                    "stop_times.txt",
+                    "trip_id,arrival_time,departure_time,stop_id,stop_sequence
+T1,08:00:00,08:00:00,A,1
+T1,08:05:00,08:05:00,B,2
+T1,08:10:00,08:10:00,C,3
+T2,09:00:00,09:00:00,D,1
+T2,09:05:00,09:05:00,B,2
+T2,09:10:00,09:10:00,A,3
+",
+                ),
+            ],
+        );
+
+        let feed = read_gtfs::<Coord, _>(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let network = &feed.network;
+        let (a, b, c, d) = (
+            feed.stops["A"],
+            feed.stops["B"],
+            feed.stops["C"],
+            feed.stops["D"],
+        );
+        assert_eq!(network.find_shortest_path(a, c), Some(vec![a, b, c]));
+        assert_eq!(network.find_shortest_path(d, a), Some(vec![d, b, a]));
+        assert_eq!(network.find_shortest_path(a, d), Some(vec![a, b, d]));
+        assert_eq!(network.find_shortest_path(c, d), None);
+    }
+
+    #[test]
+    fn test_read_gtfs_unwired() {
+        let directory = write_feed(
+            "gtfs-unwired",
+            &[
+                ("stops.txt", STOPS),
+                (
+                    "trips.txt",
+                    "route_id,service_id,trip_id\nR1,WD,T1\nR1,WD,T2\nR1,WD,T3\nR2,WD,T4\n",
+                ),
+                (
+                    "stop_times.txt",
+                    "trip_id,arrival_time,departure_time,stop_id,stop_sequence
+T1,08:00:00,08:00:00,A,1
+T1,08:05:00,08:05:00,B,2
+T1,08:10:00,08:10:00,C,3
+T2,08:20:00,08:20:00,C,1
+T2,08:25:00,08:25:00,B,2
+T2,08:30:00,08:30:00,D,3
+T3,08:40:00,08:40:00,D,1
+T3,08:45:00,08:45:00,B,2
+T3,08:50:00,08:50:00,A,3
+T4,09:00:00,09:00:00,A,1
+T4,09:05:00,09:05:00,B,2
+T4,09:10:00,09:10:00,A,3
+",
+                ),
+            ],
+        );
+
+        let feed = read_gtfs::<Coord, _>(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let b = feed.stops["B"];
+        let reversal = Movement {
+            node_id: b,
+            arrival: feed.patterns[3].edges[0],
+            departure: feed.patterns[3].edges[1],
+        };
+        assert_eq!(feed.unwired.len(), 2);
+        assert!(feed.unwired.contains(&reversal));
+        for pattern in &feed.patterns[..3] {
+            let movement = Movement {
+                node_id: b,
+                arrival: pattern.edges[0],
+                departure: pattern.edges[1],
+            };
+            let path = [pattern.stops[0].node_id, b, pattern.stops[2].node_id];
+            assert_eq!(
+                feed.network.is_traversable(&path),
+                !feed.unwired.contains(&movement)
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_gtfs_unknown_stop() {
+        let directory = write_feed(
+            "gtfs-unknown-stop",
+            &[
+                ("stops.txt", STOPS),
+                ("trips.txt", "route_id,service_id,trip_id\nR1,WD,T1\n"),
+                (
+                    "stop_times.txt",
+                    "trip_id,arrival_time,departure_time,stop_id,stop_sequence\nT1,08:00:00,08:00:00,X,1\n",
+                ),
+            ],
+        );
+
+        let result = read_gtfs::<Coord, _>(&directory);
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(matches!(result, Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(
+            parse_time(&Some("8:05:30".to_string())).unwrap(),
+            Some(29130)
+        );
+        assert_eq!(parse_time(&Some(String::new())).unwrap(), None);
+        assert_eq!(parse_time(&None).unwrap(), None);
+        assert!(parse_time(&Some("8:05".to_string())).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! This module provides readers and writers for exchanging transit networks with other tools.
+//!
+//! * `csv_wkt` - Loads and writes nodes and edges as CSV files with WKT edge paths.
+//! * `graph_export` - Writes the physical and the topology graph as GraphViz DOT or GraphML.
+//! * `gtfs` - Imports stops, shapes and trips of a GTFS static feed.
+//! * `railml` - Reads and writes the track topology of railML 2.x infrastructure files.
+//! * `snapshot` - Writes versioned binary snapshots and queries them through a memory map.
+//! * `svg` - Renders a network with routes, isochrones or edge metrics as an SVG map.
+//!
+//! All readers and writers report failures with the `Error` type of this module.
+
+use std::fmt;
+
+pub mod csv_wkt;
+pub mod graph_export;
+pub mod gtfs;
+pub mod railml;
+mod sides;
+pub mod snapshot;
+pub mod svg;
+
+/// Error raised while reading or writing a transit network.
+#[derive(Debug)]
+pub enum Error {
+    /// An error of the underlying file system or stream.
+    Io(std::io::Error),
+    /// An error while parsing or writing CSV data.
+    Csv(csv::Error),
+    /// An error while parsing or writing XML data.
+    Xml(quick_xml::Error),
+    /// The input is well-formed but does not describe a valid network.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Csv(error) => write!(f, "CSV error: {}", error),
+            Error::Xml(error) => write!(f, "XML error: {}", error),
+            Error::Invalid(message) => write!(f, "invalid input: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Csv(error) => Some(error),
+            Error::Xml(error) => Some(error),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Self {
+        Error::Csv(error)
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(error: quick_xml::Error) -> Self {
+        Error::Xml(error)
+    }
+}
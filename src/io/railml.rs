@@ -0,0 +1,870 @@
+//! Import and export of railML 2.x infrastructure.
+//!
+//! `read_railml` reads the `track` elements of a railML 2.x file with their `trackBegin`, `trackEnd`,
+//! `switch` and `crossing` elements and the `connection`s between them:
+//!
+//! * Every track position carrying one of these elements becomes a `TransitNode`. Positions joined by a
+//!   `connection` become the same node, located at the first `geoCoord` given for it.
+//! * The track between two consecutive positions becomes a `TransitEdge` with the difference of the `pos`
+//!   attributes as length. Its path runs from node to node through the `geoMapping`s of the track's
+//!   `trackElements` that lie between the two positions.
+//! * The legs of a switch are added with `add_edge_with_accessibility`, so that a train can only continue onto
+//!   the legs on the other side of the switch. The side of a leg follows from the `orientation` of its
+//!   `connection`: `outgoing` legs branch off in the direction of increasing `pos`, `incoming` legs against it.
+//!
+//! `write_railml` writes one `track` per `TransitEdge` with the inner vertices of its path as `geoMapping`s.
+//! Nodes joining more than two edges become a `crossing` if two edges lie on each side of the node and a `switch`
+//! otherwise, so that reading the file again restores the paths and the topology graph.
+//!
+//! railML 3 describes the topology with `netElement`s and `netRelation`s instead and is not supported.
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use geo::{Coord, EuclideanLength, LineString};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use super::{
+    sides::{assign_sides, wire},
+    Error,
+};
+use crate::{
+    core::{EdgeId, NodeId, TransitEdge, TransitNode},
+    graphs::TransitNetwork,
+    operations::TransitNetworkModifier,
+};
+
+/// The result of reading a railML file.
+#[derive(Debug, Clone)]
+pub struct RailmlInfrastructure<R: Copy> {
+    /// The network of the track topology.
+    pub network: TransitNetwork<R, f64>,
+    /// The `NodeId` of every `trackBegin`, `trackEnd`, `switch` and `crossing` element by its `id`.
+    pub nodes: HashMap<String, NodeId>,
+    /// The `id` of the `track` every edge belongs to.
+    pub tracks: HashMap<EdgeId, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElementKind {
+    Begin,
+    End,
+    Switch,
+}
+
+#[derive(Debug)]
+struct Connection {
+    id: String,
+    reference: String,
+    orientation: Option<String>,
+}
+
+#[derive(Debug)]
+struct Element {
+    kind: ElementKind,
+    id: String,
+    pos: f64,
+    coord: Option<Coord<f64>>,
+    connections: Vec<Connection>,
+}
+
+#[derive(Debug)]
+struct Track {
+    id: String,
+    elements: Vec<Element>,
+    geo_mappings: Vec<(f64, Coord<f64>)>,
+}
+
+/// A switch or crossing written at the end of a track, with the IDs, references and orientations of its connections.
+#[derive(Debug)]
+struct Switch {
+    node_id: NodeId,
+    at_end: bool,
+    crossing: bool,
+    legs: Vec<(String, String, &'static str)>,
+}
+
+#[derive(Debug, Default)]
+struct Parser {
+    tracks: Vec<Track>,
+    track: Option<Track>,
+    element: Option<Element>,
+    geo_mapping: Option<f64>,
+}
+
+impl Parser {
+    fn open(&mut self, start: &BytesStart) -> Result<(), Error> {
+        match start.local_name().as_ref() {
+            b"railml"
+                if attribute(start, "version")?.is_some_and(|version| version.starts_with('3')) =>
+            {
+                return Err(Error::Invalid("railML 3 is not supported".to_string()));
+            }
+            b"track" => {
+                self.track = Some(Track {
+                    id: required_attribute(start, "id")?,
+                    elements: Vec::new(),
+                    geo_mappings: Vec::new(),
+                });
+            }
+            name @ (b"trackBegin" | b"trackEnd" | b"switch" | b"crossing")
+                if self.track.is_some() =>
+            {
+                let kind = match name {
+                    b"trackBegin" => ElementKind::Begin,
+                    b"trackEnd" => ElementKind::End,
+                    _ => ElementKind::Switch,
+                };
+                let id = required_attribute(start, "id")?;
+                let pos = parse_pos(start, &id)?;
+                self.element = Some(Element {
+                    kind,
+                    id,
+                    pos,
+                    coord: None,
+                    connections: Vec::new(),
+                });
+            }
+            b"geoMapping" if self.track.is_some() => {
+                let id = required_attribute(start, "id")?;
+                self.geo_mapping = Some(parse_pos(start, &id)?);
+            }
+            b"geoCoord" => {
+                if let Some(element) = self.element.as_mut() {
+                    element.coord = Some(parse_coord(&required_attribute(start, "coord")?)?);
+                } else if let (Some(track), Some(pos)) = (self.track.as_mut(), self.geo_mapping) {
+                    let coord = parse_coord(&required_attribute(start, "coord")?)?;
+                    track.geo_mappings.push((pos, coord));
+                }
+            }
+            b"connection" => {
+                if let Some(element) = self.element.as_mut() {
+                    element.connections.push(Connection {
+                        id: required_attribute(start, "id")?,
+                        reference: required_attribute(start, "ref")?,
+                        orientation: attribute(start, "orientation")?,
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, name: &[u8]) {
+        match name {
+            b"trackBegin" | b"trackEnd" | b"switch" | b"crossing" => {
+                if let (Some(track), Some(element)) = (self.track.as_mut(), self.element.take()) {
+                    track.elements.push(element);
+                }
+            }
+            b"geoMapping" => self.geo_mapping = None,
+            b"track" => {
+                if let Some(track) = self.track.take() {
+                    self.tracks.push(track);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn attribute(start: &BytesStart, name: &str) -> Result<Option<String>, Error> {
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.local_name().as_ref() == name.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required_attribute(start: &BytesStart, name: &str) -> Result<String, Error> {
+    attribute(start, name)?.ok_or_else(|| {
+        Error::Invalid(format!(
+            "<{}> without {} attribute",
+            String::from_utf8_lossy(start.local_name().as_ref()),
+            name
+        ))
+    })
+}
+
+fn parse_pos(start: &BytesStart, id: &str) -> Result<f64, Error> {
+    required_attribute(start, "pos")?
+        .parse::<f64>()
+        .ok()
+        .filter(|pos| pos.is_finite())
+        .ok_or_else(|| Error::Invalid(format!("invalid pos of {}", id)))
+}
+
+fn parse_coord(coord: &str) -> Result<Coord<f64>, Error> {
+    let values: Vec<f64> = coord
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| Error::Invalid(format!("invalid coordinate {}", coord)))?;
+    match values[..] {
+        [x, y, ..] => Ok(Coord { x, y }),
+        _ => Err(Error::Invalid(format!("invalid coordinate {}", coord))),
+    }
+}
+
+fn parse_tracks<B: BufRead>(reader: B) -> Result<Vec<Track>, Error> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+    let mut parser = Parser::default();
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(start) => parser.open(&start)?,
+            Event::Empty(start) => {
+                parser.open(&start)?;
+                parser.close(start.local_name().as_ref());
+            }
+            Event::End(end) => parser.close(end.local_name().as_ref()),
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(parser.tracks)
+}
+
+fn find(parent: &mut [usize], mut key: usize) -> usize {
+    while parent[key] != key {
+        parent[key] = parent[parent[key]];
+        key = parent[key];
+    }
+    key
+}
+
+/// Reads the track topology of a railML 2.x file.
+///
+/// # Arguments
+///
+/// * `reader` - The reader providing the railML document.
+///
+/// # Returns
+///
+/// * `Result<RailmlInfrastructure<R>, Error>` - The imported network, or an error if the document is malformed,
+///   a `connection` references an unknown connection or a node has no `geoCoord`.
+///
+/// # Example
+///
+/// ```
+/// use geo::Coord;
+/// use transit_grid::io::railml::read_railml;
+///
+/// let railml = r#"<railml version="2.2"><infrastructure id="is"><tracks>
+///   <track id="tr1"><trackTopology>
+///     <trackBegin id="tb1" pos="0"><geoCoord coord="0 0"/></trackBegin>
+///     <trackEnd id="te1" pos="1000"><geoCoord coord="1000 0"/></trackEnd>
+///   </trackTopology></track>
+/// </tracks></infrastructure></railml>"#;
+///
+/// let infrastructure = read_railml::<Coord, _>(railml.as_bytes()).unwrap();
+/// assert_eq!(infrastructure.network.get_edge_by_id(0).unwrap().length, 1000.0);
+/// ```
+pub fn read_railml<R, B>(reader: B) -> Result<RailmlInfrastructure<R>, Error>
+where
+    R: Copy + From<Coord<f64>>,
+    B: BufRead,
+{
+    let tracks = parse_tracks(reader)?;
+
+    // Every distinct position of a track is a station, connected stations form a node.
+    let mut stations: Vec<f64> = Vec::new();
+    let mut track_stations: Vec<Vec<usize>> = Vec::new();
+    let mut element_station: HashMap<&str, usize> = HashMap::new();
+    let mut connection_station: HashMap<&str, usize> = HashMap::new();
+    for track in &tracks {
+        let mut positions: Vec<f64> = track.elements.iter().map(|element| element.pos).collect();
+        positions.sort_by(f64::total_cmp);
+        positions.dedup();
+        let keys: Vec<usize> = positions
+            .iter()
+            .map(|pos| {
+                stations.push(*pos);
+                stations.len() - 1
+            })
+            .collect();
+        for element in &track.elements {
+            let key = keys[positions
+                .iter()
+                .position(|pos| *pos == element.pos)
+                .unwrap()];
+            element_station.insert(&element.id, key);
+            for connection in &element.connections {
+                connection_station.insert(&connection.id, key);
+            }
+        }
+        track_stations.push(keys);
+    }
+
+    let mut parent: Vec<usize> = (0..stations.len()).collect();
+    for element in tracks.iter().flat_map(|track| &track.elements) {
+        for connection in &element.connections {
+            let other = *connection_station
+                .get(connection.reference.as_str())
+                .ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "connection {} references unknown connection {}",
+                        connection.id, connection.reference
+                    ))
+                })?;
+            let root = find(&mut parent, element_station[element.id.as_str()]);
+            let other_root = find(&mut parent, other);
+            parent[root] = other_root;
+        }
+    }
+
+    let mut root_nodes: HashMap<usize, NodeId> = HashMap::new();
+    let station_node: Vec<NodeId> = (0..stations.len())
+        .map(|key| {
+            let next = root_nodes.len() as NodeId;
+            *root_nodes.entry(find(&mut parent, key)).or_insert(next)
+        })
+        .collect();
+
+    let mut nodes: HashMap<String, NodeId> = HashMap::new();
+    let mut locations: HashMap<NodeId, Coord<f64>> = HashMap::new();
+    for element in tracks.iter().flat_map(|track| &track.elements) {
+        let node_id = station_node[element_station[element.id.as_str()]];
+        nodes.insert(element.id.clone(), node_id);
+        if let Some(coord) = element.coord {
+            locations.entry(node_id).or_insert(coord);
+        }
+    }
+    let mut network = TransitNetwork::new();
+    for node_id in 0..root_nodes.len() as NodeId {
+        let location = *locations.get(&node_id).ok_or_else(|| {
+            let mut elements: Vec<&str> = nodes
+                .iter()
+                .filter(|(_, node)| **node == node_id)
+                .map(|(element, _)| element.as_str())
+                .collect();
+            elements.sort();
+            Error::Invalid(format!("no geoCoord for {}", elements.join(", ")))
+        })?;
+        network.add_node(TransitNode {
+            id: node_id,
+            location: R::from(location),
+        });
+    }
+
+    let mut edge_tracks: HashMap<EdgeId, String> = HashMap::new();
+    let mut before: HashMap<usize, EdgeId> = HashMap::new();
+    let mut after: HashMap<usize, EdgeId> = HashMap::new();
+    for (track, keys) in tracks.iter().zip(&track_stations) {
+        let mut geo_mappings = track.geo_mappings.clone();
+        geo_mappings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for pair in keys.windows(2) {
+            let id = edge_tracks.len() as EdgeId;
+            let (source, target) = (station_node[pair[0]], station_node[pair[1]]);
+            let (start, end) = (stations[pair[0]], stations[pair[1]]);
+            let mut path = vec![locations[&source]];
+            path.extend(
+                geo_mappings
+                    .iter()
+                    .filter(|(pos, _)| start < *pos && *pos < end)
+                    .map(|(_, coord)| *coord),
+            );
+            path.push(locations[&target]);
+            network.physical_graph.add_transit_edge(TransitEdge {
+                id,
+                source,
+                target,
+                length: end - start,
+                path: LineString(path),
+            });
+            after.insert(pair[0], id);
+            before.insert(pair[1], id);
+            edge_tracks.insert(id, track.id.clone());
+        }
+    }
+
+    // Collects which edges meeting at a node lie on the same side of it.
+    let leg = |key: usize| match (before.get(&key), after.get(&key)) {
+        (Some(edge), None) | (None, Some(edge)) => Some(*edge),
+        _ => None,
+    };
+    let mut relations: HashMap<NodeId, Vec<(EdgeId, EdgeId, bool)>> = HashMap::new();
+    for element in tracks.iter().flat_map(|track| &track.elements) {
+        let key = element_station[element.id.as_str()];
+        let node_relations = relations.entry(station_node[key]).or_default();
+        let (before, after) = (before.get(&key).copied(), after.get(&key).copied());
+        if let (Some(before), Some(after)) = (before, after) {
+            node_relations.push((before, after, false));
+        }
+        for connection in &element.connections {
+            let Some(leg) = leg(connection_station[connection.reference.as_str()]) else {
+                continue;
+            };
+            let incoming = connection.orientation.as_deref() == Some("incoming");
+            let relation = match (element.kind, incoming, before, after) {
+                (ElementKind::End, _, Some(edge), _) => Some((edge, leg, false)),
+                (ElementKind::Begin, _, _, Some(edge)) => Some((edge, leg, false)),
+                (ElementKind::Switch, true, Some(edge), _) => Some((edge, leg, true)),
+                (ElementKind::Switch, true, None, Some(edge)) => Some((edge, leg, false)),
+                (ElementKind::Switch, false, _, Some(edge)) => Some((edge, leg, true)),
+                (ElementKind::Switch, false, Some(edge), None) => Some((edge, leg, false)),
+                _ => None,
+            };
+            node_relations.extend(relation.filter(|(edge, leg, _)| edge != leg));
+        }
+    }
+
+    let sides = assign_sides(&network, &relations);
+    wire(&mut network, &sides);
+
+    Ok(RailmlInfrastructure {
+        network,
+        nodes,
+        tracks: edge_tracks,
+    })
+}
+
+/// Writes a network as railML 2.2 infrastructure.
+///
+/// Every edge becomes a `track` with the IDs `tr<edge id>`, `tb<edge id>` and `te<edge id>`. The inner vertices
+/// of its path become `geoMapping`s with the IDs `gm<edge id>_<index>`, positioned in proportion to their distance
+/// along the path. Nodes joining two edges connect the ends of their tracks, nodes joining more edges become a
+/// `crossing` with the ID `cr<node id>` if two edges lie on each side of the node and a `switch` with the ID
+/// `sw<node id>` otherwise, at the end of one of their tracks.
+///
+/// # Arguments
+///
+/// * `network` - The network to write.
+/// * `writer` - The writer receiving the railML document.
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - An error if writing fails.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::io::railml::write_railml;
+/// use transit_grid::prelude::*;
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 1.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 1.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+/// });
+///
+/// let mut railml = Vec::new();
+/// write_railml(&network, &mut railml).unwrap();
+/// assert!(String::from_utf8(railml).unwrap().contains(r#"<track id="tr1">"#));
+/// ```
+pub fn write_railml<R, W>(network: &TransitNetwork<R, f64>, mut writer: W) -> Result<(), Error>
+where
+    R: Copy + Into<Coord<f64>>,
+    W: Write,
+{
+    let topology = &network.topology_graph;
+    let mut edges: Vec<&TransitEdge<f64>> = network.physical_graph.graph.edge_weights().collect();
+    edges.sort_by_key(|edge| edge.id);
+    let mut locations: Vec<(NodeId, Coord<f64>)> = network
+        .physical_graph
+        .graph
+        .node_weights()
+        .map(|node| (node.id, node.location.into()))
+        .collect();
+    locations.sort_by_key(|(node_id, _)| *node_id);
+
+    // Whether an edge departs from the second toponode of a node.
+    let mut departs_second: HashMap<(NodeId, EdgeId), bool> = HashMap::new();
+    for index in topology.graph.edge_indices() {
+        let (from, _) = topology.graph.edge_endpoints(index).unwrap();
+        let node_id = topology.graph[from].node_id;
+        if let Some(&(first, _)) = topology.id_to_index(node_id) {
+            departs_second.insert((node_id, topology.graph[index].edge_id), from != first);
+        }
+    }
+
+    // Track ends are identified by their edge and whether they are the `trackEnd`.
+    let end_id =
+        |(edge, at_end): (EdgeId, bool)| format!("{}{}", if at_end { "te" } else { "tb" }, edge);
+    let mut connections: HashMap<(EdgeId, bool), String> = HashMap::new();
+    let mut incident: HashMap<NodeId, Vec<(EdgeId, bool)>> = HashMap::new();
+    for edge in &edges {
+        incident
+            .entry(edge.source)
+            .or_default()
+            .push((edge.id, false));
+        incident
+            .entry(edge.target)
+            .or_default()
+            .push((edge.id, true));
+    }
+    let mut switches: HashMap<EdgeId, Vec<Switch>> = HashMap::new();
+    for &(node_id, _) in &locations {
+        let mut sides: [Vec<(EdgeId, bool)>; 2] = [Vec::new(), Vec::new()];
+        for &(edge_id, at_end) in incident.get(&node_id).into_iter().flatten() {
+            let side = departs_second
+                .get(&(node_id, edge_id))
+                .copied()
+                .unwrap_or(false);
+            sides[side as usize].push((edge_id, at_end));
+        }
+        let [mut main_side, mut other_side] = sides;
+        if main_side.is_empty() {
+            std::mem::swap(&mut main_side, &mut other_side);
+        }
+        if main_side.len() + other_side.len() < 2 {
+            continue;
+        }
+        let main = main_side[0];
+        if let Some(&other) = other_side.first() {
+            connections.insert(main, format!("{}c", end_id(other)));
+            connections.insert(other, format!("{}c", end_id(main)));
+        }
+        let (main_orientation, other_orientation) = if main.1 {
+            ("incoming", "outgoing")
+        } else {
+            ("outgoing", "incoming")
+        };
+        let crossing = main_side.len() == 2 && other_side.len() == 2;
+        let prefix = if crossing { "cr" } else { "sw" };
+        let legs: Vec<_> = main_side
+            .iter()
+            .skip(1)
+            .map(|leg| (*leg, main_orientation))
+            .chain(
+                other_side
+                    .iter()
+                    .skip(1)
+                    .map(|leg| (*leg, other_orientation)),
+            )
+            .enumerate()
+            .map(|(i, (leg, orientation))| {
+                let id = format!("{}{}c{}", prefix, node_id, i);
+                connections.insert(leg, id.clone());
+                (id, format!("{}c", end_id(leg)), orientation)
+            })
+            .collect();
+        if !legs.is_empty() {
+            switches.entry(main.0).or_default().push(Switch {
+                node_id,
+                at_end: main.1,
+                crossing,
+                legs,
+            });
+        }
+    }
+
+    let location = |node_id: NodeId| {
+        let index = locations
+            .binary_search_by_key(&node_id, |(id, _)| *id)
+            .unwrap();
+        locations[index].1
+    };
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<railml xmlns="http://www.railml.org/schemas/2013" version="2.2">"#
+    )?;
+    writeln!(writer, r#"  <infrastructure id="infrastructure">"#)?;
+    writeln!(writer, "    <tracks>")?;
+    for edge in &edges {
+        writeln!(writer, r#"      <track id="tr{}">"#, edge.id)?;
+        writeln!(writer, "        <trackTopology>")?;
+        for (at_end, node_id, pos) in [(false, edge.source, 0.0), (true, edge.target, edge.length)]
+        {
+            let tag = if at_end { "trackEnd" } else { "trackBegin" };
+            let id = end_id((edge.id, at_end));
+            let coord = location(node_id);
+            writeln!(writer, r#"          <{} id="{}" pos="{}">"#, tag, id, pos)?;
+            writeln!(
+                writer,
+                r#"            <geoCoord coord="{} {}"/>"#,
+                coord.x, coord.y
+            )?;
+            if let Some(reference) = connections.get(&(edge.id, at_end)) {
+                writeln!(
+                    writer,
+                    r#"            <connection id="{}c" ref="{}"/>"#,
+                    id, reference
+                )?;
+            }
+            writeln!(writer, "          </{}>", tag)?;
+        }
+        if let Some(edge_switches) = switches.get(&edge.id) {
+            writeln!(writer, "          <connections>")?;
+            for switch in edge_switches {
+                let pos = if switch.at_end { edge.length } else { 0.0 };
+                let coord = location(switch.node_id);
+                let (tag, prefix) = if switch.crossing {
+                    ("crossing", "cr")
+                } else {
+                    ("switch", "sw")
+                };
+                writeln!(
+                    writer,
+                    r#"            <{} id="{}{}" pos="{}">"#,
+                    tag, prefix, switch.node_id, pos
+                )?;
+                writeln!(
+                    writer,
+                    r#"              <geoCoord coord="{} {}"/>"#,
+                    coord.x, coord.y
+                )?;
+                for (id, reference, orientation) in &switch.legs {
+                    writeln!(
+                        writer,
+                        r#"              <connection id="{}" ref="{}" orientation="{}"/>"#,
+                        id, reference, orientation
+                    )?;
+                }
+                writeln!(writer, "            </{}>", tag)?;
+            }
+            writeln!(writer, "          </connections>")?;
+        }
+        writeln!(writer, "        </trackTopology>")?;
+        let inner = edge.path.0.len().saturating_sub(2);
+        if inner > 0 {
+            let total = edge.path.euclidean_length();
+            let mut distance = 0.0;
+            writeln!(writer, "        <trackElements>")?;
+            writeln!(writer, "          <geoMappings>")?;
+            for (i, line) in edge.path.lines().take(inner).enumerate() {
+                distance += line.euclidean_length();
+                let pos = if total > 0.0 {
+                    edge.length * distance / total
+                } else {
+                    edge.length * (i + 1) as f64 / (inner + 1) as f64
+                };
+                writeln!(
+                    writer,
+                    r#"            <geoMapping id="gm{}_{}" pos="{}">"#,
+                    edge.id,
+                    i + 1,
+                    pos
+                )?;
+                writeln!(
+                    writer,
+                    r#"              <geoCoord coord="{} {}"/>"#,
+                    line.end.x, line.end.y
+                )?;
+                writeln!(writer, "            </geoMapping>")?;
+            }
+            writeln!(writer, "          </geoMappings>")?;
+            writeln!(writer, "        </trackElements>")?;
+        }
+        writeln!(writer, "      </track>")?;
+    }
+    writeln!(writer, "    </tracks>")?;
+    writeln!(writer, "  </infrastructure>")?;
+    writeln!(writer, "</railml>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithms::ShortestPath, core::Accessability};
+    use geo::coord;
+
+    const RAILML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<railml xmlns="http://www.railml.org/schemas/2013" version="2.2">
+  <infrastructure id="is">
+    <tracks>
+      <track id="main">
+        <trackTopology>
+          <trackBegin id="A" pos="0"><geoCoord coord="0 0"/><openEnd id="oe1"/></trackBegin>
+          <trackEnd id="B" pos="2000"><geoCoord coord="2000 0"/></trackEnd>
+          <connections>
+            <switch id="sw1" pos="1000">
+              <geoCoord coord="1000 0"/>
+              <connection id="sw1c" ref="branch-begin-c" orientation="outgoing" course="left"/>
+            </switch>
+          </connections>
+        </trackTopology>
+      </track>
+      <track id="branch">
+        <trackTopology>
+          <trackBegin id="branch-begin" pos="0">
+            <connection id="branch-begin-c" ref="sw1c"/>
+          </trackBegin>
+          <trackEnd id="C" pos="1500"><geoCoord coord="2000 1000"/></trackEnd>
+        </trackTopology>
+      </track>
+    </tracks>
+  </infrastructure>
+</railml>"#;
+
+    #[test]
+    fn test_read_railml() {
+        let infrastructure = read_railml::<Coord, _>(RAILML.as_bytes()).unwrap();
+        let network = &infrastructure.network;
+        let node = |id: &str| infrastructure.nodes[id];
+
+        assert_eq!(network.physical_graph.graph.node_count(), 4);
+        assert_eq!(network.physical_graph.graph.edge_count(), 3);
+        assert_eq!(network.topology_graph.graph.edge_count(), 6);
+        assert_eq!(node("sw1"), node("branch-begin"));
+        assert_eq!(infrastructure.tracks[&2], "branch");
+
+        let branch = network.get_edge_by_id(2).unwrap();
+        assert_eq!(branch.length, 1500.0);
+        assert_eq!(
+            branch.path,
+            LineString(vec![
+                coord! { x: 1000.0, y: 0.0 },
+                coord! { x: 2000.0, y: 1000.0 }
+            ])
+        );
+
+        let (a, sw, b, c) = (node("A"), node("sw1"), node("B"), node("C"));
+        assert_eq!(network.find_shortest_path(a, b), Some(vec![a, sw, b]));
+        assert_eq!(network.find_shortest_path(a, c), Some(vec![a, sw, c]));
+        assert_eq!(network.find_shortest_path(c, a), Some(vec![c, sw, a]));
+        assert_eq!(network.find_shortest_path(b, c), None);
+    }
+
+    #[test]
+    fn test_read_railml_errors() {
+        let unknown = RAILML.replace(r#"ref="sw1c""#, r#"ref="sw2c""#);
+        assert!(matches!(
+            read_railml::<Coord, _>(unknown.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+
+        let missing = RAILML.replace(r#"<geoCoord coord="2000 1000"/>"#, "");
+        assert!(matches!(
+            read_railml::<Coord, _>(missing.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+
+        let railml3 = r#"<railml version="3.1"><infrastructure/></railml>"#;
+        assert!(matches!(
+            read_railml::<Coord, _>(railml3.as_bytes()),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_railml_round_trip() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (3.0, 0.0)];
+        for (id, (x, y)) in locations.into_iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let edge = |id, source: NodeId, target: NodeId| TransitEdge {
+            id,
+            source,
+            target,
+            length: 1.0,
+            path: LineString(vec![
+                locations[source as usize].into(),
+                locations[target as usize].into(),
+            ]),
+        };
+        network.add_edge(edge(0, 0, 1));
+        network.add_edge_with_accessibility(edge(1, 1, 2), Accessability::ReachableNodes(vec![0]));
+        let mut curve = edge(2, 1, 3);
+        curve.path = LineString::from(vec![(1.0, 0.0), (1.5, 0.2), (1.8, 0.6), (2.0, 1.0)]);
+        network.add_edge_with_accessibility(curve, Accessability::ReachableNodes(vec![0]));
+        network.add_edge_with_accessibility(edge(3, 2, 4), Accessability::ReachableNodes(vec![1]));
+
+        let mut railml = Vec::new();
+        write_railml(&network, &mut railml).unwrap();
+        let text = String::from_utf8(railml.clone()).unwrap();
+        assert!(text.contains(r#"<switch id="sw1""#));
+
+        let infrastructure = read_railml::<Coord, _>(railml.as_slice()).unwrap();
+        let imported = &infrastructure.network;
+        let node = |id: &str| infrastructure.nodes[id];
+        let (n0, n1, n2, n3, n4) = (
+            node("tb0"),
+            node("tb1"),
+            node("te1"),
+            node("te2"),
+            node("te3"),
+        );
+
+        assert_eq!(imported.physical_graph.graph.node_count(), 5);
+        assert_eq!(imported.physical_graph.graph.edge_count(), 4);
+        assert_eq!(node("te0"), n1);
+        assert_eq!(node("tb3"), n2);
+        for id in 0..4 {
+            assert_eq!(
+                imported.get_edge_by_id(id).unwrap().path,
+                network.get_edge_by_id(id).unwrap().path
+            );
+        }
+        assert_eq!(
+            imported.find_shortest_path(n0, n4),
+            Some(vec![n0, n1, n2, n4])
+        );
+        assert_eq!(imported.find_shortest_path(n3, n0), Some(vec![n3, n1, n0]));
+        assert_eq!(imported.find_shortest_path(n2, n3), None);
+        assert_eq!(imported.find_shortest_path(n4, n3), None);
+    }
+
+    #[test]
+    fn test_write_railml_crossing() {
+        let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (0.0, 1.0), (2.0, -1.0)];
+        for (id, (x, y)) in locations.into_iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let edge = |id, source: NodeId, target: NodeId| TransitEdge {
+            id,
+            source,
+            target,
+            length: 1.0,
+            path: LineString(vec![
+                locations[source as usize].into(),
+                locations[target as usize].into(),
+            ]),
+        };
+        network.add_edge(edge(0, 0, 1));
+        network.add_edge_with_accessibility(edge(1, 3, 1), Accessability::ReachableNodes(vec![]));
+        network
+            .add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0, 3]));
+        network
+            .add_edge_with_accessibility(edge(3, 1, 4), Accessability::ReachableNodes(vec![0, 3]));
+
+        let mut railml = Vec::new();
+        write_railml(&network, &mut railml).unwrap();
+        let text = String::from_utf8(railml.clone()).unwrap();
+        assert!(text.contains(r#"<crossing id="cr1""#));
+        assert!(!text.contains("<switch"));
+
+        let infrastructure = read_railml::<Coord, _>(railml.as_slice()).unwrap();
+        let imported = &infrastructure.network;
+        let node = |id: &str| infrastructure.nodes[id];
+        let (n0, n1, n2, n3, n4) = (
+            node("tb0"),
+            node("te0"),
+            node("te2"),
+            node("tb1"),
+            node("te3"),
+        );
+
+        assert_eq!(imported.physical_graph.graph.node_count(), 5);
+        assert_eq!(imported.physical_graph.graph.edge_count(), 4);
+        assert_eq!(imported.find_shortest_path(n0, n2), Some(vec![n0, n1, n2]));
+        assert_eq!(imported.find_shortest_path(n3, n4), Some(vec![n3, n1, n4]));
+        assert_eq!(imported.find_shortest_path(n0, n3), None);
+        assert_eq!(imported.find_shortest_path(n2, n4), None);
+    }
+}
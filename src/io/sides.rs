@@ -0,0 +1,149 @@
+//! Wiring of the topology graph for imported networks.
+//!
+//! Most exchange formats only describe which edges meet at a node, not which of them a train can continue onto.
+//! The importers derive this by splitting the edges at each node into two sides with `assign_sides` and wire the
+//! `TopoEdge`s with `wire`.
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    core::{Accessability, EdgeId, NodeId},
+    graphs::TransitNetwork,
+};
+
+/// Splits the edges at each node into two sides.
+///
+/// `relations` lists for each node pairs of edges with `true` if they lie on the same side and `false` if trains
+/// pass from one to the other. The sides are assigned per node by a breadth-first search along the relations, so
+/// relations that contradict earlier ones are ignored. Edges without relations form a side of their own.
+///
+/// # Returns
+///
+/// * `HashMap<(NodeId, EdgeId), bool>` - The side of every edge at each of its nodes, as expected by `wire`.
+pub(super) fn assign_sides<R: Copy>(
+    network: &TransitNetwork<R, f64>,
+    relations: &HashMap<NodeId, Vec<(EdgeId, EdgeId, bool)>>,
+) -> HashMap<(NodeId, EdgeId), bool> {
+    let mut incident: Vec<(NodeId, EdgeId)> = network
+        .physical_graph
+        .graph
+        .edge_weights()
+        .flat_map(|edge| [(edge.source, edge.id), (edge.target, edge.id)])
+        .collect();
+    incident.sort();
+    let mut sides: HashMap<(NodeId, EdgeId), bool> = HashMap::new();
+    for (node, start) in incident {
+        if sides.contains_key(&(node, start)) {
+            continue;
+        }
+        sides.insert((node, start), false);
+        let node_relations = relations.get(&node).map(Vec::as_slice).unwrap_or_default();
+        let mut queue = VecDeque::from([start]);
+        while let Some(edge) = queue.pop_front() {
+            let side = sides[&(node, edge)];
+            for &(first, second, same) in node_relations {
+                let other = match edge {
+                    edge if edge == first => second,
+                    edge if edge == second => first,
+                    _ => continue,
+                };
+                sides.entry((node, other)).or_insert_with(|| {
+                    queue.push_back(other);
+                    side == same
+                });
+            }
+        }
+    }
+    sides
+}
+
+/// Adds the `TopoEdge`s of all edges of the network.
+///
+/// `sides` splits the edges at each node into two sides, trains can only pass a node from one side to the other.
+/// Edges are added with `add_edge_with_accessibility` where the sides of their nodes allow it and wired directly
+/// otherwise, for example in loops with an odd number of reversals.
+pub(super) fn wire<R: Copy>(
+    network: &mut TransitNetwork<R, f64>,
+    sides: &HashMap<(NodeId, EdgeId), bool>,
+) {
+    let mut edges: Vec<(EdgeId, NodeId, NodeId)> = network
+        .physical_graph
+        .graph
+        .edge_weights()
+        .map(|edge| (edge.id, edge.source, edge.target))
+        .collect();
+    edges.sort();
+    let mut incident: HashMap<NodeId, Vec<(EdgeId, NodeId)>> = HashMap::new();
+    for &(edge, source, target) in &edges {
+        incident.entry(source).or_default().push((edge, target));
+        if source != target {
+            incident.entry(target).or_default().push((edge, source));
+        }
+    }
+    let side = |node: NodeId, edge: EdgeId| sides.get(&(node, edge)).copied().unwrap_or(false);
+
+    // Chooses for every node which side departs from its first toponode, so that every edge departs
+    // from the first toponode at one of its nodes and from the second toponode at the other.
+    let mut flip: HashMap<NodeId, bool> = HashMap::new();
+    let mut nodes: Vec<NodeId> = incident.keys().copied().collect();
+    nodes.sort();
+    for start in nodes {
+        if flip.contains_key(&start) {
+            continue;
+        }
+        flip.insert(start, false);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let node_flip = flip[&node];
+            for &(edge, other) in &incident[&node] {
+                let departs_first = side(node, edge) == node_flip;
+                flip.entry(other).or_insert_with(|| {
+                    queue.push_back(other);
+                    side(other, edge) != departs_first
+                });
+            }
+        }
+    }
+    let departs_first = |node: NodeId, edge: EdgeId| side(node, edge) == flip[&node];
+
+    let topology = &mut network.topology_graph;
+    for (edge, source, target) in edges {
+        let ends = match (departs_first(source, edge), departs_first(target, edge)) {
+            (true, false) => Some((source, target)),
+            (false, true) => Some((target, source)),
+            _ => None,
+        };
+        if let Some((from, to)) = ends {
+            let (behind, ahead): (Vec<_>, Vec<_>) = incident[&from]
+                .iter()
+                .partition(|(other_edge, _)| !departs_first(from, *other_edge));
+            let reachable: Vec<NodeId> = behind.iter().map(|(_, node)| *node).collect();
+            let ambiguous = from == to
+                || reachable.contains(&to)
+                || ahead.iter().any(|(_, node)| reachable.contains(node));
+            if !ambiguous {
+                topology.add_edge_with_accessibility(
+                    edge,
+                    from,
+                    to,
+                    Accessability::ReachableNodes(reachable),
+                );
+                continue;
+            }
+        }
+
+        let source_pair = *topology.id_to_index(source).unwrap();
+        let target_pair = *topology.id_to_index(target).unwrap();
+        let (source_departure, source_arrival) = if departs_first(source, edge) {
+            source_pair
+        } else {
+            (source_pair.1, source_pair.0)
+        };
+        let (target_departure, target_arrival) = if departs_first(target, edge) {
+            target_pair
+        } else {
+            (target_pair.1, target_pair.0)
+        };
+        topology.add_topo_edge(edge, source_departure, target_arrival);
+        topology.add_topo_edge(edge, target_departure, source_arrival);
+    }
+}
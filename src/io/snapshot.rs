@@ -0,0 +1,868 @@
+//! Versioned binary snapshots of a `TransitNetwork` for fast start-up.
+//!
+//! `write_snapshot` stores the `Crs`, the nodes with their tags, the edges with their paths and speed limits and the
+//! adjacency of the topology graph in a compact little-endian format. `Snapshot` reads such a file through a memory map and answers queries directly on
+//! the mapped bytes, including the `ShortestPath` traits, without building petgraph structures.
+//! `Snapshot::to_network` loads a snapshot back into a `TransitNetwork`.
+//!
+//! # Format
+//!
+//! | Section    | Content                                                                        |
+//! |------------|--------------------------------------------------------------------------------|
+//! | header     | magic `TGSNAP\0\0`, version `u32`, EPSG code of the `Crs` `u32` or 0, five section lengths `u64` |
+//! | nodes      | `id u64`, `x f64`, `y f64`, `tags u64` per node, sorted by id                  |
+//! | edges      | `id u64`, `source u64`, `target u64`, `length f64`, `path start u64`, `path length u64`, `speed limit f64` per edge, sorted by id |
+//! | coords     | `x f64`, `y f64` per path coordinate                                           |
+//! | offsets    | `u64` per toponode plus one, the start of its outgoing edges in `adjacency`    |
+//! | adjacency  | `target toponode u64`, `edge index u64` per `TopoEdge`                         |
+//!
+//! The toponodes of the node at index `i` have the indices `2 * i` and `2 * i + 1`. Bit 0 of the tags of a node marks
+//! a `NodeTag::Station` and bit 1 a `NodeTag::ReversalPoint`. An unrestricted edge has a speed limit of NaN.
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    fs::File,
+    io::Write,
+    ops::Deref,
+    path::Path,
+};
+
+use geo::{Coord, LineString};
+use memmap2::Mmap;
+use petgraph::{
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+};
+
+use super::Error;
+use crate::{
+    algorithms::{ShortestPath, ShortestPathWithAccessability},
+    core::{Accessability, Crs, EdgeId, NodeId, NodeTag, TransitEdge, TransitNode},
+    graphs::TransitNetwork,
+    operations::TransitNetworkModifier,
+};
+
+/// The version of the snapshot format written by `write_snapshot`. Snapshots of other versions are rejected.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+const MAGIC: &[u8; 8] = b"TGSNAP\0\0";
+const HEADER_LEN: usize = 56;
+const NODE_LEN: usize = 32;
+const EDGE_LEN: usize = 56;
+const COORD_LEN: usize = 16;
+const OFFSET_LEN: usize = 8;
+const ADJACENCY_LEN: usize = 16;
+const NODE_TAGS: [NodeTag; 2] = [NodeTag::Station, NodeTag::ReversalPoint];
+
+/// Writes a network as a binary snapshot.
+///
+/// # Arguments
+///
+/// * `network` - The network to write.
+/// * `writer` - The writer receiving the snapshot.
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - An error if writing fails.
+pub fn write_snapshot<R, W>(network: &TransitNetwork<R, f64>, mut writer: W) -> Result<(), Error>
+where
+    R: Copy + Into<Coord<f64>>,
+    W: Write,
+{
+    let mut nodes: Vec<(NodeId, Coord<f64>)> = network
+        .physical_graph
+        .graph
+        .node_weights()
+        .map(|node| (node.id, node.location.into()))
+        .collect();
+    nodes.sort_by_key(|(node_id, _)| *node_id);
+    let node_index: HashMap<NodeId, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, (node_id, _))| (*node_id, index))
+        .collect();
+    let mut edges: Vec<&TransitEdge<f64>> = network.physical_graph.graph.edge_weights().collect();
+    edges.sort_by_key(|edge| edge.id);
+    let edge_index: HashMap<EdgeId, usize> = edges
+        .iter()
+        .enumerate()
+        .map(|(index, edge)| (edge.id, index))
+        .collect();
+
+    let topology = &network.topology_graph;
+    let toponode = |index: NodeIndex| {
+        let node_id = topology.graph[index].node_id;
+        let first = topology.id_to_index(node_id)?.0 == index;
+        Some(2 * node_index.get(&node_id)? + usize::from(!first))
+    };
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![Vec::new(); 2 * nodes.len()];
+    for edge in topology.graph.edge_references() {
+        if let (Some(from), Some(to), Some(index)) = (
+            toponode(edge.source()),
+            toponode(edge.target()),
+            edge_index.get(&edge.weight().edge_id),
+        ) {
+            adjacency[from].push((to, *index));
+        }
+    }
+    adjacency.iter_mut().for_each(|targets| targets.sort());
+    let coord_count: usize = edges.iter().map(|edge| edge.path.0.len()).sum();
+    let topo_edge_count: usize = adjacency.iter().map(Vec::len).sum();
+
+    let mut bytes = Vec::with_capacity(
+        HEADER_LEN
+            + nodes.len() * NODE_LEN
+            + edges.len() * EDGE_LEN
+            + coord_count * COORD_LEN
+            + (adjacency.len() + 1) * OFFSET_LEN
+            + topo_edge_count * ADJACENCY_LEN,
+    );
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&network.crs.map_or(0, |crs| crs.epsg()).to_le_bytes());
+    for count in [
+        nodes.len(),
+        edges.len(),
+        coord_count,
+        adjacency.len(),
+        topo_edge_count,
+    ] {
+        bytes.extend_from_slice(&(count as u64).to_le_bytes());
+    }
+    for (node_id, location) in &nodes {
+        bytes.extend_from_slice(&node_id.to_le_bytes());
+        bytes.extend_from_slice(&location.x.to_le_bytes());
+        bytes.extend_from_slice(&location.y.to_le_bytes());
+        let tags: u64 = NODE_TAGS
+            .iter()
+            .enumerate()
+            .filter(|(_, tag)| network.has_tag(*node_id, **tag))
+            .map(|(bit, _)| 1 << bit)
+            .sum();
+        bytes.extend_from_slice(&tags.to_le_bytes());
+    }
+    let mut path_start = 0u64;
+    for edge in &edges {
+        let path_len = edge.path.0.len() as u64;
+        for value in [edge.id, edge.source, edge.target] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&edge.length.to_le_bytes());
+        bytes.extend_from_slice(&path_start.to_le_bytes());
+        bytes.extend_from_slice(&path_len.to_le_bytes());
+        let speed_limit = network.speed_limit(edge.id).unwrap_or(f64::NAN);
+        bytes.extend_from_slice(&speed_limit.to_le_bytes());
+        path_start += path_len;
+    }
+    for coord in edges.iter().flat_map(|edge| &edge.path.0) {
+        bytes.extend_from_slice(&coord.x.to_le_bytes());
+        bytes.extend_from_slice(&coord.y.to_le_bytes());
+    }
+    let mut offset = 0u64;
+    for targets in &adjacency {
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        offset += targets.len() as u64;
+    }
+    bytes.extend_from_slice(&offset.to_le_bytes());
+    for (to, index) in adjacency.iter().flatten() {
+        bytes.extend_from_slice(&(*to as u64).to_le_bytes());
+        bytes.extend_from_slice(&(*index as u64).to_le_bytes());
+    }
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+#[derive(Debug)]
+enum Storage {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Mapped(map) => map,
+            Storage::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/// A read-only network backed by the bytes of a snapshot written by `write_snapshot`.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::algorithms::ShortestPath;
+/// use transit_grid::io::snapshot::{write_snapshot, Snapshot};
+/// use transit_grid::prelude::*;
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 1.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 1.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+/// });
+///
+/// let mut bytes = Vec::new();
+/// write_snapshot(&network, &mut bytes).unwrap();
+///
+/// let snapshot = Snapshot::from_bytes(bytes).unwrap();
+/// assert_eq!(snapshot.find_shortest_path(1, 2), Some(vec![1, 2]));
+/// ```
+#[derive(Debug)]
+pub struct Snapshot {
+    data: Storage,
+    node_count: usize,
+    edge_count: usize,
+    coords: usize,
+    offsets: usize,
+    adjacency: usize,
+}
+
+/// An entry of the priority queue of `Snapshot::dijkstra`, ordered by increasing cost.
+#[derive(Debug, PartialEq)]
+struct QueueEntry {
+    cost: f64,
+    toponode: usize,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.toponode.cmp(&self.toponode))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Snapshot {
+    /// Opens a snapshot file through a memory map.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the snapshot file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Snapshot, Error>` - The snapshot, or an error if the file cannot be mapped,
+    ///   is not a snapshot or has a different `SNAPSHOT_VERSION`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        // SAFETY: The map is read-only. Modifying the file while it is mapped is not supported,
+        // as with any memory-mapped file, but all reads are bounds-checked against the validated lengths.
+        let map = unsafe { Mmap::map(&file)? };
+        Self::new(Storage::Mapped(map))
+    }
+
+    /// Reads a snapshot from bytes in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the snapshot.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Snapshot, Error>` - The snapshot, or an error if the bytes are not a snapshot
+    ///   or have a different `SNAPSHOT_VERSION`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        Self::new(Storage::Owned(bytes))
+    }
+
+    fn new(data: Storage) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::Invalid(format!("snapshot: {}", message));
+        if data.len() < HEADER_LEN || &data[..8] != MAGIC {
+            return Err(invalid("missing header"));
+        }
+        let version = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid(&format!(
+                "unsupported version {}, expected {}",
+                version, SNAPSHOT_VERSION
+            )));
+        }
+        let count = |index: usize| {
+            let offset = 16 + 8 * index;
+            usize::try_from(u64::from_le_bytes(
+                data[offset..offset + 8].try_into().unwrap(),
+            ))
+            .map_err(|_| invalid("section too large"))
+        };
+        let (node_count, edge_count, coord_count, toponode_count, topo_edge_count) =
+            (count(0)?, count(1)?, count(2)?, count(3)?, count(4)?);
+        if toponode_count != 2 * node_count {
+            return Err(invalid("toponode count does not match node count"));
+        }
+
+        let sections = [
+            (node_count, NODE_LEN),
+            (edge_count, EDGE_LEN),
+            (coord_count, COORD_LEN),
+            (toponode_count + 1, OFFSET_LEN),
+            (topo_edge_count, ADJACENCY_LEN),
+        ];
+        let mut starts = [0; 5];
+        let mut end = HEADER_LEN;
+        for (start, (count, len)) in starts.iter_mut().zip(sections) {
+            *start = end;
+            end = count
+                .checked_mul(len)
+                .and_then(|size| end.checked_add(size))
+                .ok_or_else(|| invalid("section too large"))?;
+        }
+        if end != data.len() {
+            return Err(invalid("length does not match header"));
+        }
+
+        let snapshot = Snapshot {
+            data,
+            node_count,
+            edge_count,
+            coords: starts[2],
+            offsets: starts[3],
+            adjacency: starts[4],
+        };
+        // Validates all indices once, so that queries can rely on them.
+        for index in 0..edge_count {
+            let (start, len) = snapshot.path_range(index);
+            if start.checked_add(len).is_none_or(|end| end > coord_count) {
+                return Err(invalid("edge path out of bounds"));
+            }
+        }
+        for toponode in 0..toponode_count {
+            let (start, end) = snapshot.adjacency_range(toponode);
+            if start > end || end > topo_edge_count {
+                return Err(invalid("adjacency offsets out of bounds"));
+            }
+        }
+        for index in 0..topo_edge_count {
+            let (to, edge) = snapshot.adjacency_entry(index);
+            if to >= toponode_count || edge >= edge_count {
+                return Err(invalid("adjacency entry out of bounds"));
+            }
+        }
+        Ok(snapshot)
+    }
+
+    fn u64_at(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.data[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn f64_at(&self, offset: usize) -> f64 {
+        f64::from_le_bytes(self.data[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn usize_at(&self, offset: usize) -> usize {
+        usize::try_from(self.u64_at(offset)).unwrap_or(usize::MAX)
+    }
+
+    fn node_id(&self, index: usize) -> NodeId {
+        self.u64_at(HEADER_LEN + index * NODE_LEN)
+    }
+
+    fn edge_offset(&self, index: usize) -> usize {
+        HEADER_LEN + self.node_count * NODE_LEN + index * EDGE_LEN
+    }
+
+    fn path_range(&self, index: usize) -> (usize, usize) {
+        let offset = self.edge_offset(index);
+        (self.usize_at(offset + 32), self.usize_at(offset + 40))
+    }
+
+    fn adjacency_range(&self, toponode: usize) -> (usize, usize) {
+        let offset = self.offsets + toponode * OFFSET_LEN;
+        (self.usize_at(offset), self.usize_at(offset + OFFSET_LEN))
+    }
+
+    fn adjacency_entry(&self, index: usize) -> (usize, usize) {
+        let offset = self.adjacency + index * ADJACENCY_LEN;
+        (self.usize_at(offset), self.usize_at(offset + 8))
+    }
+
+    fn node_index(&self, node_id: NodeId) -> Option<usize> {
+        binary_search(self.node_count, node_id, |index| self.node_id(index))
+    }
+
+    fn edge_index(&self, edge_id: EdgeId) -> Option<usize> {
+        binary_search(self.edge_count, edge_id, |index| {
+            self.u64_at(self.edge_offset(index))
+        })
+    }
+
+    fn edge_at(&self, index: usize) -> TransitEdge<f64> {
+        let offset = self.edge_offset(index);
+        let (start, len) = self.path_range(index);
+        let path = (start..start + len)
+            .map(|coord| {
+                let offset = self.coords + coord * COORD_LEN;
+                Coord {
+                    x: self.f64_at(offset),
+                    y: self.f64_at(offset + 8),
+                }
+            })
+            .collect();
+        TransitEdge {
+            id: self.u64_at(offset),
+            source: self.u64_at(offset + 8),
+            target: self.u64_at(offset + 16),
+            length: self.f64_at(offset + 24),
+            path: LineString(path),
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Returns the coordinate reference system of the network, if known.
+    pub fn crs(&self) -> Option<Crs> {
+        match u32::from_le_bytes(self.data[12..16].try_into().unwrap()) {
+            0 => None,
+            code => Some(Crs::from_epsg(code)),
+        }
+    }
+
+    /// Returns the IDs of all nodes in increasing order.
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.node_count).map(|index| self.node_id(index))
+    }
+
+    /// Returns the location of the node with the given ID.
+    pub fn node_location(&self, node_id: NodeId) -> Option<Coord<f64>> {
+        let offset = HEADER_LEN + self.node_index(node_id)? * NODE_LEN;
+        Some(Coord {
+            x: self.f64_at(offset + 8),
+            y: self.f64_at(offset + 16),
+        })
+    }
+
+    /// Returns the `NodeTag`s of the node with the given ID, or `None` if there is no such node.
+    pub fn node_tags(&self, node_id: NodeId) -> Option<Vec<NodeTag>> {
+        let tags = self.u64_at(HEADER_LEN + self.node_index(node_id)? * NODE_LEN + 24);
+        Some(
+            NODE_TAGS
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| tags & (1 << bit) != 0)
+                .map(|(_, tag)| *tag)
+                .collect(),
+        )
+    }
+
+    /// Returns the maximum permitted speed on the edge with the given ID, or `None` if it is unrestricted.
+    pub fn speed_limit(&self, edge_id: EdgeId) -> Option<f64> {
+        let limit = self.f64_at(self.edge_offset(self.edge_index(edge_id)?) + 48);
+        (!limit.is_nan()).then_some(limit)
+    }
+
+    /// Returns the edge with the given ID, including its path.
+    pub fn edge(&self, edge_id: EdgeId) -> Option<TransitEdge<f64>> {
+        Some(self.edge_at(self.edge_index(edge_id)?))
+    }
+
+    /// Loads the snapshot into a `TransitNetwork` with the same nodes, edges, `TopoEdge`s, `Crs`, node tags and
+    /// speed limits.
+    ///
+    /// # Returns
+    ///
+    /// * `TransitNetwork<R, f64>` - The network stored in the snapshot.
+    pub fn to_network<R>(&self) -> TransitNetwork<R, f64>
+    where
+        R: Copy + From<Coord<f64>>,
+    {
+        let mut network = TransitNetwork::new();
+        network.crs = self.crs();
+        let mut toponodes = Vec::with_capacity(2 * self.node_count);
+        for node_id in self.node_ids() {
+            let location = self.node_location(node_id).unwrap();
+            network.add_node(TransitNode {
+                id: node_id,
+                location: R::from(location),
+            });
+            let (first, second) = *network.topology_graph.id_to_index(node_id).unwrap();
+            toponodes.extend([first, second]);
+            for tag in self.node_tags(node_id).unwrap() {
+                network.tag_node(node_id, tag);
+            }
+        }
+        let edges: Vec<TransitEdge<f64>> = (0..self.edge_count)
+            .map(|index| self.edge_at(index))
+            .collect();
+        for edge in &edges {
+            network.physical_graph.add_transit_edge(edge.clone());
+            if let Some(limit) = self.speed_limit(edge.id) {
+                network.set_speed_limit(edge.id, limit);
+            }
+        }
+        for (from, toponode) in toponodes.iter().enumerate() {
+            let (start, end) = self.adjacency_range(from);
+            for index in start..end {
+                let (to, edge) = self.adjacency_entry(index);
+                network
+                    .topology_graph
+                    .add_topo_edge(edges[edge].id, *toponode, toponodes[to]);
+            }
+        }
+        network
+    }
+
+    /// Finds the cheapest path from any of the `starts` to any of the `goals` in the topology.
+    /// `cost` receives the index of an edge and its target node and returns `f64::INFINITY` for impassable edges.
+    fn dijkstra<F>(
+        &self,
+        starts: [usize; 2],
+        goals: [usize; 2],
+        mut cost: F,
+    ) -> Option<(f64, Vec<usize>)>
+    where
+        F: FnMut(usize, NodeId) -> f64,
+    {
+        let mut costs: HashMap<usize, f64> = HashMap::new();
+        let mut previous: HashMap<usize, usize> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        for start in starts {
+            costs.insert(start, 0.0);
+            queue.push(QueueEntry {
+                cost: 0.0,
+                toponode: start,
+            });
+        }
+        while let Some(QueueEntry {
+            cost: current,
+            toponode,
+        }) = queue.pop()
+        {
+            if current > costs[&toponode] {
+                continue;
+            }
+            if goals.contains(&toponode) {
+                let mut path = vec![toponode];
+                while let Some(before) = previous.get(path.last().unwrap()) {
+                    path.push(*before);
+                }
+                path.reverse();
+                return Some((current, path));
+            }
+            let (start, end) = self.adjacency_range(toponode);
+            for index in start..end {
+                let (to, edge) = self.adjacency_entry(index);
+                let edge_cost = cost(edge, self.node_id(to / 2));
+                if !edge_cost.is_finite() {
+                    continue;
+                }
+                let next = current + edge_cost;
+                if costs.get(&to).is_none_or(|known| next < *known) {
+                    costs.insert(to, next);
+                    previous.insert(to, toponode);
+                    queue.push(QueueEntry {
+                        cost: next,
+                        toponode: to,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+fn binary_search<F: Fn(usize) -> u64>(len: usize, id: u64, id_at: F) -> Option<usize> {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match id_at(middle).cmp(&id) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Greater => high = middle,
+            Ordering::Equal => return Some(middle),
+        }
+    }
+    None
+}
+
+impl ShortestPath<Coord<f64>, f64> for Snapshot {
+    fn find_shortest_path(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        self.find_shortest_path_with_accessability(
+            from,
+            to,
+            Accessability::UnreachableNodes(vec![]),
+            |_edge| 1.0,
+        )
+        .map(|(_, path)| path)
+    }
+}
+
+impl ShortestPathWithAccessability<Coord<f64>, f64> for Snapshot {
+    fn calc_edge_cost<F>(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        accessability: &Accessability,
+        edge_cost: &mut F,
+    ) -> f64
+    where
+        F: FnMut(TransitEdge<f64>) -> f64,
+    {
+        if let Accessability::UnreachableNodes(unreachable_nodes) = accessability {
+            if unreachable_nodes.contains(&to) {
+                return f64::INFINITY;
+            }
+        }
+        let Some(from) = self.node_index(from) else {
+            return f64::INFINITY;
+        };
+        (2 * from..2 * from + 2)
+            .flat_map(|toponode| {
+                let (start, end) = self.adjacency_range(toponode);
+                (start..end).map(|index| self.adjacency_entry(index))
+            })
+            .find(|(target, _)| self.node_id(target / 2) == to)
+            .map_or(f64::INFINITY, |(_, edge)| edge_cost(self.edge_at(edge)))
+    }
+
+    fn find_shortest_path_with_accessability<F>(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        accessability: Accessability,
+        mut edge_cost: F,
+    ) -> Option<(f64, Vec<NodeId>)>
+    where
+        F: FnMut(TransitEdge<f64>) -> f64,
+    {
+        let start = self.node_index(from)?;
+        let goal = self.node_index(to)?;
+        let unreachable = match &accessability {
+            Accessability::UnreachableNodes(nodes) => nodes.as_slice(),
+            Accessability::ReachableNodes(_) => &[],
+        };
+        self.dijkstra(
+            [2 * start, 2 * start + 1],
+            [2 * goal, 2 * goal + 1],
+            |edge, target| {
+                if unreachable.contains(&target) {
+                    f64::INFINITY
+                } else {
+                    edge_cost(self.edge_at(edge))
+                }
+            },
+        )
+        .map(|(cost, path)| {
+            let path = path
+                .into_iter()
+                .map(|toponode| self.node_id(toponode / 2))
+                .collect();
+            (cost, path)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::coord;
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (3.0, 0.0)];
+        for (id, (x, y)) in locations.into_iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId * 10,
+                location: coord! { x: x, y: y },
+            });
+        }
+        let edge = |id, source: usize, target: usize, length| TransitEdge {
+            id,
+            source: source as NodeId * 10,
+            target: target as NodeId * 10,
+            length,
+            path: LineString(vec![locations[source].into(), locations[target].into()]),
+        };
+        network.add_edge(edge(0, 0, 1, 1.0));
+        network.add_edge_with_accessibility(
+            edge(1, 1, 2, 1.0),
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            edge(2, 1, 3, 5.0),
+            Accessability::ReachableNodes(vec![0]),
+        );
+        network.add_edge_with_accessibility(
+            edge(3, 2, 4, 1.0),
+            Accessability::ReachableNodes(vec![10]),
+        );
+        network.tag_node(20, NodeTag::Station);
+        network.tag_node(40, NodeTag::Station);
+        network.tag_node(40, NodeTag::ReversalPoint);
+        network.set_speed_limit(1, 20.0);
+        network
+    }
+
+    fn snapshot_bytes(network: &TransitNetwork<Coord, f64>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_snapshot(network, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_snapshot_queries() {
+        let network = create_network();
+        let snapshot = Snapshot::from_bytes(snapshot_bytes(&network)).unwrap();
+
+        assert_eq!(snapshot.node_count(), 5);
+        assert_eq!(snapshot.edge_count(), 4);
+        assert_eq!(
+            snapshot.node_ids().collect::<Vec<_>>(),
+            vec![0, 10, 20, 30, 40]
+        );
+        assert_eq!(snapshot.node_location(30), Some(coord! { x: 2.0, y: 1.0 }));
+        assert_eq!(snapshot.node_location(31), None);
+        assert_eq!(snapshot.edge(2).as_ref(), network.get_edge_by_id(2));
+        assert_eq!(snapshot.edge(9), None);
+        assert_eq!(snapshot.crs(), None);
+        assert_eq!(snapshot.node_tags(0), Some(vec![]));
+        assert_eq!(
+            snapshot.node_tags(40),
+            Some(vec![NodeTag::Station, NodeTag::ReversalPoint])
+        );
+        assert_eq!(snapshot.node_tags(31), None);
+        assert_eq!(snapshot.speed_limit(1), Some(20.0));
+        assert_eq!(snapshot.speed_limit(2), None);
+
+        for from in snapshot.node_ids() {
+            for to in snapshot.node_ids() {
+                assert_eq!(
+                    snapshot.find_shortest_path(from, to),
+                    network.find_shortest_path(from, to),
+                    "path from {} to {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_accessability() {
+        let network = create_network();
+        let snapshot = Snapshot::from_bytes(snapshot_bytes(&network)).unwrap();
+        let length = |edge: TransitEdge<f64>| edge.length;
+
+        assert_eq!(
+            snapshot.find_shortest_path_with_accessability(
+                0,
+                30,
+                Accessability::UnreachableNodes(vec![]),
+                length
+            ),
+            Some((6.0, vec![0, 10, 30]))
+        );
+        assert_eq!(
+            snapshot.find_shortest_path_with_accessability(
+                0,
+                40,
+                Accessability::UnreachableNodes(vec![20]),
+                length
+            ),
+            None
+        );
+        assert_eq!(
+            snapshot.calc_edge_cost(10, 30, &Accessability::UnreachableNodes(vec![]), &mut {
+                length
+            }),
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_snapshot_to_network() {
+        let network = create_network().with_crs(Crs::Utm {
+            zone: 32,
+            north: true,
+        });
+        let snapshot = Snapshot::from_bytes(snapshot_bytes(&network)).unwrap();
+
+        let loaded: TransitNetwork<Coord, f64> = snapshot.to_network();
+
+        assert_eq!(loaded.physical_graph.graph.node_count(), 5);
+        assert_eq!(loaded.physical_graph.graph.edge_count(), 4);
+        assert_eq!(
+            loaded.topology_graph.graph.edge_count(),
+            network.topology_graph.graph.edge_count()
+        );
+        assert_eq!(loaded.get_edge_by_id(3), network.get_edge_by_id(3));
+        assert_eq!(loaded.crs, network.crs);
+        assert_eq!(loaded.node_tags, network.node_tags);
+        assert_eq!(loaded.speed_limits, network.speed_limits);
+        for from in snapshot.node_ids() {
+            for to in snapshot.node_ids() {
+                assert_eq!(
+                    loaded.find_shortest_path(from, to),
+                    network.find_shortest_path(from, to),
+                    "path from {} to {}",
+                    from,
+                    to
+                );
+            }
+        }
+        assert!(loaded.audit_topology().is_empty());
+        assert_eq!(snapshot_bytes(&loaded), snapshot_bytes(&network));
+    }
+
+    #[test]
+    fn test_snapshot_file() {
+        let network = create_network();
+        let path =
+            std::env::temp_dir().join(format!("transit-grid-snapshot-{}.bin", std::process::id()));
+        write_snapshot(&network, File::create(&path).unwrap()).unwrap();
+
+        let snapshot = Snapshot::open(&path).unwrap();
+        assert_eq!(
+            snapshot.find_shortest_path(0, 40),
+            Some(vec![0, 10, 20, 40])
+        );
+        drop(snapshot);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_rejects_invalid_data() {
+        let mut bytes = snapshot_bytes(&create_network());
+
+        let mut other_version = bytes.clone();
+        other_version[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Snapshot::from_bytes(other_version),
+            Err(Error::Invalid(message)) if message.contains("version")
+        ));
+
+        bytes.pop();
+        assert!(matches!(
+            Snapshot::from_bytes(bytes),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            Snapshot::from_bytes(b"not a snapshot".to_vec()),
+            Err(Error::Invalid(_))
+        ));
+    }
+}
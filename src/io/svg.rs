@@ -0,0 +1,564 @@
+//! Headless rendering of a `TransitNetwork` as an SVG map.
+//!
+//! The renderer draws every `TransitEdge::path` at its real coordinates, scaled uniformly to the canvas with the
+//! y axis pointing up, and marks the nodes by their degree or their role. `Overlay`s highlight a route,
+//! an isochrone or a metric per edge on top of the network.
+use std::{collections::HashMap, fmt::Write};
+
+use geo::{Coord, CoordNum};
+
+use crate::{
+    core::{EdgeId, NodeId, NodeTag},
+    graphs::TransitNetwork,
+};
+
+/// Enum `NodeMarking` defining how nodes are drawn.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NodeMarking {
+    /// Draws nodes as circles growing with the number of edges at the node.
+    Degree,
+    /// Draws stations as squares, junctions with more than two edges in red and dead ends in black.
+    Role,
+    /// Does not draw nodes.
+    Hidden,
+}
+
+/// Options for `SvgRender`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// The width of the image in pixels.
+    pub width: f64,
+    /// The height of the image in pixels.
+    pub height: f64,
+    /// The margin between the network and the border of the image in pixels.
+    pub margin: f64,
+    /// The stroke width of edges in pixels.
+    pub edge_width: f64,
+    /// The radius of nodes in pixels.
+    pub node_radius: f64,
+    /// How nodes are drawn.
+    pub nodes: NodeMarking,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            width: 800.0,
+            height: 600.0,
+            margin: 20.0,
+            edge_width: 2.0,
+            node_radius: 3.0,
+            nodes: NodeMarking::Role,
+        }
+    }
+}
+
+/// Enum `Overlay` describing information drawn on top of the network.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Overlay<T> {
+    /// Highlights the edges between consecutive nodes of a route, such as a result of `ShortestPath`.
+    Route(Vec<NodeId>),
+    /// Colours the nodes reached within `limit` by their cost, from green for cheap to red for expensive,
+    /// and highlights the edges between them.
+    Isochrone {
+        /// The travel cost to reach each node.
+        costs: HashMap<NodeId, T>,
+        /// The largest cost to draw.
+        limit: T,
+    },
+    /// Colours edges by a value, from green for the smallest to red for the largest value.
+    EdgeMetric(HashMap<EdgeId, T>),
+}
+
+/// The `SvgRender` trait draws a network as an SVG image.
+pub trait SvgRender<T> {
+    /// Renders the network with the given overlays, drawn in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `overlays` - The `Overlay`s to draw on top of the network.
+    /// * `options` - The `SvgOptions` to apply.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The SVG document.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::io::svg::{Overlay, SvgOptions, SvgRender};
+    /// use transit_grid::prelude::*;
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 1.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let svg = network.render_svg(&[Overlay::Route(vec![1, 2])], &SvgOptions::default());
+    /// assert!(svg.starts_with("<svg"));
+    /// ```
+    fn render_svg(&self, overlays: &[Overlay<T>], options: &SvgOptions) -> String;
+}
+
+/// Maps network coordinates onto the canvas.
+struct Projection {
+    min: Coord<f64>,
+    scale: f64,
+    offset: Coord<f64>,
+    height: f64,
+}
+
+impl Projection {
+    fn new(coords: &[Coord<f64>], options: &SvgOptions) -> Self {
+        let (min, max) = coords.iter().fold(
+            (
+                Coord {
+                    x: f64::INFINITY,
+                    y: f64::INFINITY,
+                },
+                Coord {
+                    x: f64::NEG_INFINITY,
+                    y: f64::NEG_INFINITY,
+                },
+            ),
+            |(min, max), coord| {
+                (
+                    Coord {
+                        x: min.x.min(coord.x),
+                        y: min.y.min(coord.y),
+                    },
+                    Coord {
+                        x: max.x.max(coord.x),
+                        y: max.y.max(coord.y),
+                    },
+                )
+            },
+        );
+        if coords.is_empty() {
+            return Projection {
+                min: Coord { x: 0.0, y: 0.0 },
+                scale: 1.0,
+                offset: Coord {
+                    x: options.margin,
+                    y: options.margin,
+                },
+                height: options.height,
+            };
+        }
+        let available = Coord {
+            x: (options.width - 2.0 * options.margin).max(0.0),
+            y: (options.height - 2.0 * options.margin).max(0.0),
+        };
+        let extent = max - min;
+        let scale = match (extent.x > 0.0, extent.y > 0.0) {
+            (true, true) => (available.x / extent.x).min(available.y / extent.y),
+            (true, false) => available.x / extent.x,
+            (false, true) => available.y / extent.y,
+            (false, false) => 1.0,
+        };
+        // Centres the network in the available space.
+        let offset = Coord {
+            x: options.margin + (available.x - extent.x * scale) / 2.0,
+            y: options.margin + (available.y - extent.y * scale) / 2.0,
+        };
+        Projection {
+            min,
+            scale,
+            offset,
+            height: options.height,
+        }
+    }
+
+    fn project(&self, coord: Coord<f64>) -> Coord<f64> {
+        Coord {
+            x: self.offset.x + (coord.x - self.min.x) * self.scale,
+            y: self.height - (self.offset.y + (coord.y - self.min.y) * self.scale),
+        }
+    }
+
+    fn points(&self, coords: &[Coord<f64>]) -> String {
+        coords
+            .iter()
+            .map(|coord| {
+                let point = self.project(*coord);
+                format!("{:.2},{:.2}", point.x, point.y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Returns a colour on a scale from green at `0` over yellow to red at `1`.
+fn ramp(value: f64) -> String {
+    let value = if value.is_finite() {
+        value.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (red, green) = if value < 0.5 {
+        (value * 2.0 * 255.0, 200.0)
+    } else {
+        (255.0, (1.0 - value) * 2.0 * 200.0)
+    };
+    format!("rgb({},{},0)", red.round(), green.round())
+}
+
+fn to_f64<T: CoordNum>(value: T) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+fn coord_f64<T: CoordNum>(coord: Coord<T>) -> Coord<f64> {
+    Coord {
+        x: to_f64(coord.x),
+        y: to_f64(coord.y),
+    }
+}
+
+impl<R, T> SvgRender<T> for TransitNetwork<R, T>
+where
+    R: Copy + Into<Coord<T>>,
+    T: CoordNum,
+{
+    fn render_svg(&self, overlays: &[Overlay<T>], options: &SvgOptions) -> String {
+        let mut edges: Vec<_> = self.physical_graph.graph.edge_weights().collect();
+        edges.sort_by_key(|edge| edge.id);
+        let mut nodes: Vec<(NodeId, Coord<f64>)> = self
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| (node.id, coord_f64(node.location.into())))
+            .collect();
+        nodes.sort_by_key(|(node_id, _)| *node_id);
+        let paths: HashMap<EdgeId, Vec<Coord<f64>>> = edges
+            .iter()
+            .map(|edge| (edge.id, edge.path.0.iter().map(|c| coord_f64(*c)).collect()))
+            .collect();
+        let mut degrees: HashMap<NodeId, usize> = HashMap::new();
+        for edge in &edges {
+            *degrees.entry(edge.source).or_default() += 1;
+            *degrees.entry(edge.target).or_default() += 1;
+        }
+
+        let coords: Vec<Coord<f64>> = nodes
+            .iter()
+            .map(|(_, coord)| *coord)
+            .chain(paths.values().flatten().copied())
+            .filter(|coord| coord.x.is_finite() && coord.y.is_finite())
+            .collect();
+        let projection = Projection::new(&coords, options);
+        let locations: HashMap<NodeId, Coord<f64>> = nodes.iter().copied().collect();
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = options.width,
+            h = options.height
+        )
+        .unwrap();
+        svg.push_str("  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+        writeln!(
+            svg,
+            r##"  <g class="edges" fill="none" stroke="#888888" stroke-width="{}" stroke-linecap="round">"##,
+            options.edge_width
+        )
+        .unwrap();
+        for edge in &edges {
+            writeln!(
+                svg,
+                r#"    <polyline data-edge="{}" points="{}"/>"#,
+                edge.id,
+                projection.points(&paths[&edge.id])
+            )
+            .unwrap();
+        }
+        svg.push_str("  </g>\n");
+
+        let overlay_width = options.edge_width * 2.0;
+        for overlay in overlays {
+            match overlay {
+                Overlay::Route(route) => {
+                    writeln!(
+                        svg,
+                        r##"  <g class="route" fill="none" stroke="#1f6feb" stroke-width="{}" stroke-linecap="round">"##,
+                        overlay_width
+                    )
+                    .unwrap();
+                    for pair in route.windows(2) {
+                        if let Some(edge) = self.physical_graph.get_transit_edge(pair[0], pair[1]) {
+                            writeln!(
+                                svg,
+                                r#"    <polyline data-edge="{}" points="{}"/>"#,
+                                edge.id,
+                                projection.points(&paths[&edge.id])
+                            )
+                            .unwrap();
+                        }
+                    }
+                    for node_id in [route.first(), route.last()].into_iter().flatten() {
+                        if let Some(location) = locations.get(node_id) {
+                            let point = projection.project(*location);
+                            writeln!(
+                                svg,
+                                r##"    <circle data-node="{}" cx="{:.2}" cy="{:.2}" r="{}" fill="#1f6feb"/>"##,
+                                node_id,
+                                point.x,
+                                point.y,
+                                options.node_radius * 2.0
+                            )
+                            .unwrap();
+                        }
+                    }
+                    svg.push_str("  </g>\n");
+                }
+                Overlay::Isochrone { costs, limit } => {
+                    let limit = to_f64(*limit);
+                    let reached = |node_id: &NodeId| {
+                        costs
+                            .get(node_id)
+                            .map(|cost| to_f64(*cost))
+                            .filter(|cost| *cost <= limit)
+                    };
+                    writeln!(
+                        svg,
+                        r#"  <g class="isochrone" fill="none" stroke-width="{}" stroke-linecap="round">"#,
+                        overlay_width
+                    )
+                    .unwrap();
+                    for edge in &edges {
+                        if let (Some(source), Some(target)) =
+                            (reached(&edge.source), reached(&edge.target))
+                        {
+                            writeln!(
+                                svg,
+                                r#"    <polyline data-edge="{}" stroke="{}" points="{}"/>"#,
+                                edge.id,
+                                ramp(source.max(target) / limit),
+                                projection.points(&paths[&edge.id])
+                            )
+                            .unwrap();
+                        }
+                    }
+                    for (node_id, location) in &nodes {
+                        if let Some(cost) = reached(node_id) {
+                            let point = projection.project(*location);
+                            writeln!(
+                                svg,
+                                r#"    <circle data-node="{}" cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                                node_id,
+                                point.x,
+                                point.y,
+                                options.node_radius * 1.5,
+                                ramp(cost / limit)
+                            )
+                            .unwrap();
+                        }
+                    }
+                    svg.push_str("  </g>\n");
+                }
+                Overlay::EdgeMetric(values) => {
+                    let values: HashMap<EdgeId, f64> = values
+                        .iter()
+                        .map(|(edge_id, value)| (*edge_id, to_f64(*value)))
+                        .filter(|(_, value)| value.is_finite())
+                        .collect();
+                    let min = values.values().copied().fold(f64::INFINITY, f64::min);
+                    let max = values.values().copied().fold(f64::NEG_INFINITY, f64::max);
+                    writeln!(
+                        svg,
+                        r#"  <g class="metric" fill="none" stroke-width="{}" stroke-linecap="round">"#,
+                        overlay_width
+                    )
+                    .unwrap();
+                    for edge in &edges {
+                        if let Some(value) = values.get(&edge.id) {
+                            let relative = if max > min {
+                                (value - min) / (max - min)
+                            } else {
+                                0.0
+                            };
+                            writeln!(
+                                svg,
+                                r#"    <polyline data-edge="{}" stroke="{}" points="{}"/>"#,
+                                edge.id,
+                                ramp(relative),
+                                projection.points(&paths[&edge.id])
+                            )
+                            .unwrap();
+                        }
+                    }
+                    svg.push_str("  </g>\n");
+                }
+            }
+        }
+
+        if options.nodes != NodeMarking::Hidden {
+            svg.push_str("  <g class=\"nodes\" stroke=\"black\" stroke-width=\"1\">\n");
+            for (node_id, location) in &nodes {
+                let point = projection.project(*location);
+                let degree = degrees.get(node_id).copied().unwrap_or(0);
+                let radius = options.node_radius;
+                let marker = match options.nodes {
+                    NodeMarking::Degree => format!(
+                        r#"<circle data-node="{}" cx="{:.2}" cy="{:.2}" r="{}" fill="white"/>"#,
+                        node_id,
+                        point.x,
+                        point.y,
+                        radius * (1.0 + 0.5 * degree.saturating_sub(1) as f64)
+                    ),
+                    _ if self.has_tag(*node_id, NodeTag::Station) => format!(
+                        r#"<rect data-node="{}" class="station" x="{:.2}" y="{:.2}" width="{}" height="{}" fill="white"/>"#,
+                        node_id,
+                        point.x - radius * 1.5,
+                        point.y - radius * 1.5,
+                        radius * 3.0,
+                        radius * 3.0
+                    ),
+                    _ => {
+                        let (class, fill) = match degree {
+                            0 | 1 => ("end", "black"),
+                            2 => ("link", "white"),
+                            _ => ("junction", "red"),
+                        };
+                        format!(
+                            r#"<circle data-node="{}" class="{}" cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                            node_id, class, point.x, point.y, radius, fill
+                        )
+                    }
+                };
+                writeln!(svg, "    {}", marker).unwrap();
+            }
+            svg.push_str("  </g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::{coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let locations = [(0.0, 0.0), (10.0, 0.0), (20.0, 0.0), (10.0, 5.0)];
+        for (id, (x, y)) in locations.into_iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: x, y: y },
+            });
+        }
+        for (id, source, target) in [(0, 0, 1), (1, 1, 2), (2, 1, 3)] {
+            network.add_edge(TransitEdge {
+                id,
+                source,
+                target,
+                length: 10.0,
+                path: LineString(vec![
+                    locations[source as usize].into(),
+                    locations[target as usize].into(),
+                ]),
+            });
+        }
+        network.tag_node(3, NodeTag::Station);
+        network
+    }
+
+    #[test]
+    fn test_projection() {
+        let options = SvgOptions {
+            width: 120.0,
+            height: 100.0,
+            margin: 10.0,
+            ..Default::default()
+        };
+        let projection = Projection::new(
+            &[coord! { x: 0.0, y: 0.0 }, coord! { x: 20.0, y: 5.0 }],
+            &options,
+        );
+
+        assert_eq!(
+            projection.project(coord! { x: 0.0, y: 0.0 }),
+            coord! { x: 10.0, y: 62.5 }
+        );
+        assert_eq!(
+            projection.project(coord! { x: 20.0, y: 5.0 }),
+            coord! { x: 110.0, y: 37.5 }
+        );
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let network = create_network();
+        let options = SvgOptions {
+            width: 120.0,
+            height: 100.0,
+            margin: 10.0,
+            ..Default::default()
+        };
+
+        let svg = network.render_svg(&[], &options);
+
+        assert_eq!(svg.matches("<polyline").count(), 3);
+        assert!(svg.contains(r#"<polyline data-edge="1" points="60.00,62.50 110.00,62.50"/>"#));
+        assert!(svg.contains(r#"data-node="1" class="junction""#));
+        assert!(svg.contains(r#"data-node="0" class="end""#));
+        assert!(svg.contains(r#"<rect data-node="3" class="station""#));
+    }
+
+    fn group<'a>(svg: &'a str, class: &str) -> &'a str {
+        let start = svg.find(&format!("<g class=\"{}\"", class)).unwrap();
+        let end = start + svg[start..].find("</g>").unwrap();
+        &svg[start..end]
+    }
+
+    #[test]
+    fn test_render_svg_overlays() {
+        let network = create_network();
+        let overlays = [
+            Overlay::Route(vec![0, 1, 3]),
+            Overlay::Isochrone {
+                costs: HashMap::from([(0, 0.0), (1, 10.0), (2, 20.0)]),
+                limit: 10.0,
+            },
+            Overlay::EdgeMetric(HashMap::from([(0, 1.0), (1, 3.0)])),
+        ];
+        let options = SvgOptions {
+            nodes: NodeMarking::Hidden,
+            ..Default::default()
+        };
+
+        let svg = network.render_svg(&overlays, &options);
+
+        let route = group(&svg, "route");
+        assert!(route.contains(r#"<polyline data-edge="0""#));
+        assert!(route.contains(r#"<polyline data-edge="2""#));
+        let isochrone = group(&svg, "isochrone");
+        assert!(isochrone.contains(r#"data-edge="0" stroke="rgb(255,0,0)""#));
+        assert!(!isochrone.contains(r#"data-edge="1""#));
+        let metric = group(&svg, "metric");
+        assert!(metric.contains(r#"data-edge="0" stroke="rgb(0,200,0)""#));
+        assert!(metric.contains(r#"data-edge="1" stroke="rgb(255,0,0)""#));
+        assert!(!svg.contains("<g class=\"nodes\""));
+    }
+
+    #[test]
+    fn test_ramp() {
+        assert_eq!(ramp(0.0), "rgb(0,200,0)");
+        assert_eq!(ramp(0.5), "rgb(255,200,0)");
+        assert_eq!(ramp(2.0), "rgb(255,0,0)");
+    }
+}
@@ -14,9 +14,15 @@
 /// * `graphs` - This module defines several types of graphs that can represent a transit network at different levels of detail,
 ///              including the physical graph, the topological graph, and the transit network.
 ///
+/// * `io` - This module provides readers and writers for exchanging transit networks with other tools,
+///          such as importing GTFS feeds.
+///
 /// * `operations` - This module provides operations for manipulating transit networks,
 ///                  such as adding or removing nodes or edges, merging networks, etc.
 ///
+/// * `simulation` - This module provides a deterministic discrete-event simulation of vehicles
+///                  moving along routes on a transit network.
+///
 /// The `prelude` module re-exports the most commonly used items from the `core`, `graphs`, and `operations` modules,
 /// providing a convenient way to import many items at once.
 ///
@@ -25,7 +31,9 @@
 pub mod algorithms;
 pub mod core;
 pub mod graphs;
+pub mod io;
 pub mod operations;
+pub mod simulation;
 
 /// The `prelude` module re-exports the most commonly used items from the `core`, `graphs`, and `operations` modules,
 pub mod prelude {
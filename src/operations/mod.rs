@@ -4,15 +4,20 @@
 //!
 //! ## `TransitNetworkModifier`
 //!
-//! The `TransitNetworkModifier` trait provides an interface for modifying the network. This includes adding nodes and edges to the network.
+//! The `TransitNetworkModifier` trait provides an interface for modifying the network by adding nodes and edges.
 //! Implementors of this trait can be used to add `TransitNode` and `TransitEdge` instances to a network.
 //!
 //! For instance, an implementor might add a `TransitNode` to an internal data structure upon invocation of the `add_node` method.
 //! Similarly, the `add_edge` and `add_edge_with_accessibility` methods are used to add `TransitEdge` instances to the network.
 //! The `add_edge_with_accessibility` method also allows specifying the accessibility of the edge, represented by the `Accessability` enum.
 //!
+//! ## `TransitNetworkRemover`
+//!
+//! The `TransitNetworkRemover` trait provides the `remove_node` and `remove_edge` methods for removing `TransitNode` and
+//! `TransitEdge` instances again.
+//!
 
-use crate::core::{Accessability, NodeId, TransitEdge, TransitNode};
+use crate::core::{Accessability, EdgeId, NodeId, TransitEdge, TransitNode};
 use geo::{Coord, CoordNum, EuclideanDistance};
 
 /// Trait providing methods for modifying a transit network.
@@ -46,6 +51,34 @@ pub trait TransitNetworkModifier<R, T: CoordNum> {
     fn add_edge_with_accessibility(&mut self, edge: TransitEdge<T>, accessibility: Accessability);
 }
 
+/// Trait providing methods for removing nodes and edges from a transit network.
+///
+/// This trait is separate from `TransitNetworkModifier`, so that implementors of that trait which only build
+/// networks are not required to support removal.
+pub trait TransitNetworkRemover<R, T: CoordNum> {
+    /// Removes a `TransitNode` and all edges connected to it from the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The ID of the node to be removed.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<TransitNode<R>>` - The removed node, or `None` if the node does not exist.
+    fn remove_node(&mut self, node_id: NodeId) -> Option<TransitNode<R>>;
+
+    /// Removes a `TransitEdge` from the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge_id` - The ID of the edge to be removed.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<TransitEdge<T>>` - The removed edge, or `None` if the edge does not exist.
+    fn remove_edge(&mut self, edge_id: EdgeId) -> Option<TransitEdge<T>>;
+}
+
 /// A trait for repairing transit networks, particularly for ensuring that all edges in the network are in the correct direction.
 ///
 /// This trait provides two methods:
@@ -0,0 +1,390 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use geo::CoordNum;
+
+use crate::{
+    core::{EdgeId, IdType, NetworkPosition, NodeId},
+    graphs::TransitNetwork,
+};
+
+use super::VehicleId;
+
+/// Type alias for the identifier of a block section.
+pub type BlockId = IdType;
+
+/// Structure representing a fixed block section, which may be occupied by at most one vehicle at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// The unique identifier of the block.
+    pub id: BlockId,
+    /// The IDs of the edges forming the block.
+    pub edges: Vec<EdgeId>,
+}
+
+/// The division of a network into block sections.
+///
+/// Every edge belongs to at most one block; edges without a block are not protected.
+///
+/// # Example
+///
+/// ```
+/// use transit_grid::simulation::{Block, BlockLayout};
+///
+/// let mut layout = BlockLayout::new();
+/// assert!(layout.add_block(Block { id: 1, edges: vec![1, 2] }));
+/// assert!(!layout.add_block(Block { id: 2, edges: vec![2, 3] }));
+/// assert_eq!(layout.block_of(2), Some(1));
+/// assert_eq!(layout.block_of(3), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockLayout {
+    blocks: BTreeMap<BlockId, Block>,
+    edge_blocks: HashMap<EdgeId, BlockId>,
+}
+
+impl BlockLayout {
+    /// Creates a layout without any blocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a layout with one block for each edge of the network, using the edge ID as block ID.
+    pub fn per_edge<R: Copy, T: CoordNum>(network: &TransitNetwork<R, T>) -> Self {
+        let mut layout = BlockLayout::new();
+        for edge in network.physical_graph.graph.edge_weights() {
+            layout.add_block(Block {
+                id: edge.id,
+                edges: vec![edge.id],
+            });
+        }
+        layout
+    }
+
+    /// Adds a block to the layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block to add.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` if the block was not added, because it has no edges, its ID is already in use
+    ///   or one of its edges already belongs to another block.
+    pub fn add_block(&mut self, block: Block) -> bool {
+        if block.edges.is_empty()
+            || self.blocks.contains_key(&block.id)
+            || block
+                .edges
+                .iter()
+                .any(|edge_id| self.edge_blocks.contains_key(edge_id))
+        {
+            return false;
+        }
+        for edge_id in &block.edges {
+            self.edge_blocks.insert(*edge_id, block.id);
+        }
+        self.blocks.insert(block.id, block);
+        true
+    }
+
+    /// Returns the block with the given ID.
+    pub fn block(&self, block_id: BlockId) -> Option<&Block> {
+        self.blocks.get(&block_id)
+    }
+
+    /// Returns the ID of the block the edge with the given ID belongs to.
+    pub fn block_of(&self, edge_id: EdgeId) -> Option<BlockId> {
+        self.edge_blocks.get(&edge_id).copied()
+    }
+
+    /// Returns an iterator over all blocks, sorted by ID.
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.values()
+    }
+}
+
+/// Enum `Resource` defining the parts of the network that only one vehicle may use at a time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Resource {
+    /// A block section.
+    Block(BlockId),
+    /// A switch, given by the node whose pair of toponodes the vehicles pass.
+    Switch(NodeId),
+}
+
+/// Structure representing two vehicles requiring the same resource at the same time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Conflict {
+    /// The simulation time in seconds at which the resource was requested.
+    pub time: f64,
+    /// The contested resource.
+    pub resource: Resource,
+    /// The ID of the vehicle requesting the resource.
+    pub vehicle_id: VehicleId,
+    /// The ID of the vehicle holding the resource.
+    pub other_vehicle_id: VehicleId,
+    /// The position of the requesting vehicle.
+    pub position: NetworkPosition<f64>,
+}
+
+/// Returns the IDs of the nodes joining more than two edges, whose pair of toponodes vehicles contest.
+pub(super) fn switch_nodes<R: Copy, T: CoordNum>(
+    network: &TransitNetwork<R, T>,
+) -> HashSet<NodeId> {
+    let mut degrees: HashMap<NodeId, usize> = HashMap::new();
+    for edge in network.physical_graph.graph.edge_weights() {
+        *degrees.entry(edge.source).or_default() += 1;
+        *degrees.entry(edge.target).or_default() += 1;
+    }
+    degrees
+        .into_iter()
+        .filter(|(_, degree)| *degree > 2)
+        .map(|(node_id, _)| node_id)
+        .collect()
+}
+
+/// Tracks which vehicles occupy and reserve resources, and records the conflicts between them.
+#[derive(Debug, Clone)]
+pub(super) struct Occupancy {
+    pub(super) layout: BlockLayout,
+    pub(super) switches: HashSet<NodeId>,
+    occupants: BTreeMap<Resource, Vec<VehicleId>>,
+    reservations: BTreeMap<BlockId, VehicleId>,
+    /// The resources of each vehicle with the route distance its head must pass to clear them.
+    releases: HashMap<VehicleId, Vec<(Resource, f64)>>,
+    pub(super) conflicts: Vec<Conflict>,
+}
+
+impl Occupancy {
+    pub(super) fn new<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        layout: BlockLayout,
+    ) -> Self {
+        Occupancy {
+            layout,
+            switches: switch_nodes(network),
+            occupants: BTreeMap::new(),
+            reservations: BTreeMap::new(),
+            releases: HashMap::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    pub(super) fn occupants(&self, resource: Resource) -> &[VehicleId] {
+        self.occupants.get(&resource).map_or(&[], Vec::as_slice)
+    }
+
+    pub(super) fn reservation(&self, block_id: BlockId) -> Option<VehicleId> {
+        self.reservations.get(&block_id).copied()
+    }
+
+    /// Occupies a resource until the head of the vehicle has passed `clear_at`, reporting conflicts
+    /// with other vehicles occupying or reserving it.
+    pub(super) fn occupy(
+        &mut self,
+        time: f64,
+        vehicle_id: VehicleId,
+        resource: Resource,
+        clear_at: f64,
+        position: NetworkPosition<f64>,
+    ) {
+        let releases = self.releases.entry(vehicle_id).or_default();
+        match releases.iter_mut().find(|(held, _)| *held == resource) {
+            Some((_, distance)) => *distance = clear_at,
+            None => releases.push((resource, clear_at)),
+        }
+        let occupants = self.occupants.entry(resource).or_default();
+        if occupants.contains(&vehicle_id) {
+            return;
+        }
+        let mut others: Vec<VehicleId> = occupants.clone();
+        if let Resource::Block(block_id) = resource {
+            match self.reservations.get(&block_id) {
+                Some(reserved) if *reserved == vehicle_id => {
+                    self.reservations.remove(&block_id);
+                }
+                Some(reserved) if !others.contains(reserved) => others.push(*reserved),
+                _ => {}
+            }
+        }
+        occupants.push(vehicle_id);
+        self.report(time, vehicle_id, resource, others, position);
+    }
+
+    /// Reserves a block ahead of a vehicle, if no other vehicle occupies or reserves it.
+    pub(super) fn reserve(&mut self, vehicle_id: VehicleId, block_id: BlockId) -> bool {
+        let occupants = self.occupants(Resource::Block(block_id));
+        if occupants.contains(&vehicle_id) {
+            return true;
+        }
+        if !occupants.is_empty() {
+            return false;
+        }
+        *self.reservations.entry(block_id).or_insert(vehicle_id) == vehicle_id
+    }
+
+    /// Releases all resources the vehicle has cleared once its head reached `distance`.
+    pub(super) fn release(&mut self, vehicle_id: VehicleId, distance: f64) {
+        let Some(releases) = self.releases.get_mut(&vehicle_id) else {
+            return;
+        };
+        let mut cleared = Vec::new();
+        releases.retain(|(resource, clear_at)| {
+            let keep = *clear_at > distance;
+            if !keep {
+                cleared.push(*resource);
+            }
+            keep
+        });
+        for resource in cleared {
+            if let Some(occupants) = self.occupants.get_mut(&resource) {
+                occupants.retain(|occupant| *occupant != vehicle_id);
+                if occupants.is_empty() {
+                    self.occupants.remove(&resource);
+                }
+            }
+        }
+    }
+
+    /// Releases all resources and reservations of a vehicle leaving the simulation.
+    pub(super) fn release_all(&mut self, vehicle_id: VehicleId) {
+        self.release(vehicle_id, f64::INFINITY);
+        self.releases.remove(&vehicle_id);
+        self.reservations
+            .retain(|_, reserved| *reserved != vehicle_id);
+    }
+
+    fn report(
+        &mut self,
+        time: f64,
+        vehicle_id: VehicleId,
+        resource: Resource,
+        others: Vec<VehicleId>,
+        position: NetworkPosition<f64>,
+    ) {
+        for other_vehicle_id in others {
+            if other_vehicle_id != vehicle_id {
+                self.conflicts.push(Conflict {
+                    time,
+                    resource,
+                    vehicle_id,
+                    other_vehicle_id,
+                    position,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{
+        fixtures::create_network, Route, Simulation, SimulationOptions, Vehicle,
+    };
+    use geo::Coord;
+
+    fn vehicle(
+        network: &TransitNetwork<Coord, f64>,
+        id: VehicleId,
+        (from, to): (NodeId, NodeId),
+        departure: f64,
+    ) -> Vehicle {
+        Vehicle {
+            id,
+            route: Route::shortest(network, from, to).unwrap(),
+            departure,
+            speed: 10.0,
+            stops: HashMap::new(),
+            dynamics: None,
+        }
+    }
+
+    #[test]
+    fn test_block_layout() {
+        let network = create_network();
+        let mut layout = BlockLayout::per_edge(&network);
+
+        assert_eq!(layout.blocks().count(), 3);
+        assert_eq!(layout.block_of(3), Some(3));
+        assert!(!layout.add_block(Block {
+            id: 4,
+            edges: vec![]
+        }));
+        assert!(!layout.add_block(Block {
+            id: 3,
+            edges: vec![4]
+        }));
+        assert!(layout.add_block(Block {
+            id: 4,
+            edges: vec![4]
+        }));
+        assert_eq!(layout.block(4).unwrap().edges, vec![4]);
+    }
+
+    #[test]
+    fn test_block_conflicts() {
+        let network = create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default())
+            .with_blocks(BlockLayout::per_edge(&network));
+        simulation.add_vehicle(vehicle(&network, 1, (0, 2), 0.0));
+        simulation.add_vehicle(vehicle(&network, 2, (0, 2), 5.0));
+        simulation.add_vehicle(vehicle(&network, 3, (0, 2), 25.0));
+
+        simulation.run_until(1.0);
+        assert_eq!(simulation.occupants(Resource::Block(1)), &[1]);
+        assert_eq!(simulation.reservation(2), Some(1));
+
+        simulation.run();
+
+        let conflicts = simulation.conflicts();
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(
+            conflicts[0],
+            Conflict {
+                time: 5.0,
+                resource: Resource::Block(1),
+                vehicle_id: 2,
+                other_vehicle_id: 1,
+                position: NetworkPosition {
+                    edge_id: 1,
+                    offset: 0.0
+                },
+            }
+        );
+        // Node 1 is a switch, which vehicle 1 holds until it leaves edge 1.
+        assert_eq!(
+            (conflicts[1].time, conflicts[1].resource),
+            (5.0, Resource::Switch(1))
+        );
+        assert_eq!(
+            (conflicts[2].time, conflicts[2].resource),
+            (15.0, Resource::Block(2))
+        );
+        assert!(simulation.occupants(Resource::Block(2)).is_empty());
+        assert_eq!(simulation.reservation(2), None);
+    }
+
+    #[test]
+    fn test_switch_conflicts() {
+        let network = create_network();
+        let mut simulation =
+            Simulation::new(&network, SimulationOptions::default()).with_blocks(BlockLayout::new());
+        simulation.add_vehicle(vehicle(&network, 1, (0, 2), 0.0));
+        simulation.add_vehicle(vehicle(&network, 2, (3, 0), 0.0));
+
+        simulation.run_until(0.0);
+        assert_eq!(simulation.occupants(Resource::Switch(1)), &[1, 2]);
+        simulation.run();
+
+        let conflicts = simulation.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].resource, Resource::Switch(1));
+        assert_eq!(
+            (conflicts[0].vehicle_id, conflicts[0].other_vehicle_id),
+            (2, 1)
+        );
+        assert_eq!(conflicts[0].position.edge_id, 3);
+        assert_eq!(conflicts[0].position.offset, 100.0);
+    }
+}
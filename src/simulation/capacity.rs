@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+
+use geo::CoordNum;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{EdgeId, ServiceId, Timetable};
+
+use super::{
+    blocks::{BlockId, Resource},
+    headway::{BlockingTime, HeadwayAnalyser, Stairway},
+    route::Route,
+};
+
+/// The occupation of a single edge of a corridor in the compressed timetable.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeCapacity {
+    /// The ID of the edge.
+    pub edge_id: EdgeId,
+    /// The ID of the block the edge belongs to, or `None` if the edge is not protected by a block.
+    pub block_id: Option<BlockId>,
+    /// The time in seconds the block of the edge is blocked by the runs of the corridor.
+    pub occupation_time: f64,
+    /// The occupation time as a percentage of the period.
+    pub occupancy: f64,
+}
+
+/// The capacity utilisation of a corridor found by `HeadwayAnalyser::capacity`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityReport {
+    /// The length of the timetable period in seconds.
+    pub period: f64,
+    /// The IDs of the services using the corridor, in the order they were compressed.
+    pub service_ids: Vec<ServiceId>,
+    /// The stairways of the services on the corridor after compression.
+    pub stairways: Vec<Stairway>,
+    /// The time in seconds from the first blocking to the last release in the compressed timetable.
+    pub occupation_time: f64,
+    /// The occupation time as a percentage of the period.
+    pub occupancy: f64,
+    /// The occupation of each edge of the corridor, in corridor order.
+    pub edges: Vec<EdgeCapacity>,
+}
+
+impl CapacityReport {
+    /// Returns the occupancy percentage of each edge of the corridor, e.g. to colour a map with `Overlay::EdgeMetric`.
+    pub fn edge_occupancy(&self) -> HashMap<EdgeId, f64> {
+        self.edges
+            .iter()
+            .map(|edge| (edge.edge_id, edge.occupancy))
+            .collect()
+    }
+}
+
+impl<'a, R: Copy, T: CoordNum> HeadwayAnalyser<'a, R, T> {
+    /// Compresses the runs of a timetable on a corridor following UIC Code 406 and reports its occupancy.
+    ///
+    /// Only the blocks of the corridor edges and the switches at its nodes are considered.
+    /// The runs using the corridor keep their order of first blocking, and each is moved as early as the
+    /// runs before it allow, until the blocking times of consecutive runs touch. The occupation time
+    /// of the compressed timetable divided by the period gives the occupancy.
+    ///
+    /// # Arguments
+    ///
+    /// * `timetable` - The timetable pattern to compress.
+    /// * `corridor` - The nodes and edges along the corridor, a path through the topology graph.
+    /// * `period` - The length of the timetable pattern in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CapacityReport>` - The occupancy of the corridor and its edges, or `None` if the corridor
+    ///   has fewer than two nodes or is not traversable through its edges, for example because an edge does not
+    ///   join its pair of nodes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    /// use transit_grid::simulation::{BlockLayout, HeadwayAnalyser, HeadwayOptions, Route};
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1000.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let stop = |node_id, time| Stop { node_id, arrival: time, departure: time };
+    /// let mut timetable = Timetable::new();
+    /// timetable.add_service(Service::new(1, vec![stop(1, 0.0), stop(2, 100.0)]));
+    /// timetable.add_service(Service::new(2, vec![stop(1, 1800.0), stop(2, 1900.0)]));
+    ///
+    /// let options = HeadwayOptions { min_headway: 20.0, ..Default::default() };
+    /// let analyser = HeadwayAnalyser::new(&network, BlockLayout::per_edge(&network), options);
+    /// let corridor = Route::from_nodes(&network, vec![1, 2]).unwrap();
+    /// let report = analyser.capacity(&timetable, &corridor, 3600.0).unwrap();
+    /// assert_eq!(report.occupation_time, 240.0);
+    /// assert_eq!(report.edge_occupancy()[&1], report.occupancy);
+    /// ```
+    pub fn capacity(
+        &self,
+        timetable: &Timetable,
+        corridor: &Route,
+        period: f64,
+    ) -> Option<CapacityReport> {
+        if corridor.nodes.len() < 2
+            || !self
+                .network
+                .is_traversable_along(&corridor.nodes, &corridor.edges)
+        {
+            return None;
+        }
+        let edges: Vec<(EdgeId, Option<BlockId>)> = corridor
+            .edges
+            .iter()
+            .map(|edge_id| (*edge_id, self.layout.block_of(*edge_id)))
+            .collect();
+        let resources: HashSet<Resource> = edges
+            .iter()
+            .filter_map(|(_, block_id)| block_id.map(Resource::Block))
+            .chain(
+                corridor
+                    .nodes
+                    .iter()
+                    .filter(|node_id| self.switches.contains(node_id))
+                    .map(|node_id| Resource::Switch(*node_id)),
+            )
+            .collect();
+
+        let mut stairways: Vec<Stairway> = self
+            .stairways(timetable)
+            .into_iter()
+            .map(|mut stairway| {
+                stairway
+                    .blocking_times
+                    .retain(|blocking| resources.contains(&blocking.resource));
+                stairway
+            })
+            .filter(|stairway| !stairway.blocking_times.is_empty())
+            .collect();
+        stairways.sort_by(|a, b| first_start(a).total_cmp(&first_start(b)));
+
+        let mut compressed: Vec<Stairway> = Vec::with_capacity(stairways.len());
+        for stairway in stairways {
+            let shift = match compressed.last() {
+                Some(previous) => compressed
+                    .iter()
+                    .filter_map(|earlier| earlier.separation(&stairway))
+                    .fold(first_start(previous) - first_start(&stairway), f64::max),
+                None => 0.0,
+            };
+            compressed.push(Stairway {
+                service_id: stairway.service_id,
+                blocking_times: stairway
+                    .blocking_times
+                    .iter()
+                    .map(|blocking| BlockingTime {
+                        resource: blocking.resource,
+                        start: blocking.start + shift,
+                        end: blocking.end + shift,
+                    })
+                    .collect(),
+            });
+        }
+
+        let blocking_times = || {
+            compressed
+                .iter()
+                .flat_map(|stairway| &stairway.blocking_times)
+        };
+        let occupation_time = match blocking_times().next() {
+            Some(_) => {
+                blocking_times()
+                    .map(|blocking| blocking.end)
+                    .fold(f64::MIN, f64::max)
+                    - blocking_times()
+                        .map(|blocking| blocking.start)
+                        .fold(f64::MAX, f64::min)
+            }
+            None => 0.0,
+        };
+        let edges = edges
+            .into_iter()
+            .map(|(edge_id, block_id)| {
+                let occupation_time = blocking_times()
+                    .filter(|blocking| block_id.map(Resource::Block) == Some(blocking.resource))
+                    .map(|blocking| blocking.end - blocking.start)
+                    .sum();
+                EdgeCapacity {
+                    edge_id,
+                    block_id,
+                    occupation_time,
+                    occupancy: occupation_time / period * 100.0,
+                }
+            })
+            .collect();
+
+        Some(CapacityReport {
+            period,
+            service_ids: compressed
+                .iter()
+                .map(|stairway| stairway.service_id)
+                .collect(),
+            occupation_time,
+            occupancy: occupation_time / period * 100.0,
+            stairways: compressed,
+            edges,
+        })
+    }
+}
+
+/// Returns the start of the first blocking time of a stairway.
+fn first_start(stairway: &Stairway) -> f64 {
+    stairway
+        .blocking_times
+        .iter()
+        .map(|blocking| blocking.start)
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::NodeId,
+        simulation::{
+            fixtures::{create_double_track, create_network, service},
+            BlockLayout, HeadwayOptions,
+        },
+    };
+
+    #[test]
+    fn test_capacity() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(2, 0, 3, 50.0, 20.0));
+        timetable.add_service(service(1, 0, 2, 0.0, 20.0));
+
+        let corridor = Route::from_nodes(&network, vec![0, 1, 2]).unwrap();
+        let report = analyser.capacity(&timetable, &corridor, 100.0).unwrap();
+
+        assert_eq!(report.service_ids, vec![1, 2]);
+        // Service 2 follows as soon as service 1 has released block 1.
+        assert_eq!(report.stairways[1].blocking_times[0].start, 10.0);
+        assert_eq!(report.occupation_time, 20.0);
+        assert_eq!(report.occupancy, 20.0);
+        assert_eq!(
+            report.edges,
+            vec![
+                EdgeCapacity {
+                    edge_id: 1,
+                    block_id: Some(1),
+                    occupation_time: 20.0,
+                    occupancy: 20.0
+                },
+                EdgeCapacity {
+                    edge_id: 2,
+                    block_id: Some(2),
+                    occupation_time: 10.0,
+                    occupancy: 10.0
+                },
+            ]
+        );
+        assert_eq!(report.edge_occupancy().len(), 2);
+    }
+
+    #[test]
+    fn test_capacity_invalid_corridor() {
+        let network = create_network();
+        let analyser =
+            HeadwayAnalyser::new(&network, BlockLayout::new(), HeadwayOptions::default());
+        let timetable = Timetable::new();
+
+        let corridor = |nodes: Vec<NodeId>, edges: Vec<EdgeId>| Route { nodes, edges };
+        assert!(analyser
+            .capacity(&timetable, &corridor(vec![0], vec![]), 100.0)
+            .is_none());
+        assert!(analyser
+            .capacity(&timetable, &corridor(vec![0, 1], vec![]), 100.0)
+            .is_none());
+        assert!(analyser
+            .capacity(&timetable, &corridor(vec![2, 1, 3], vec![2, 3]), 100.0)
+            .is_none());
+        assert!(analyser
+            .capacity(&timetable, &corridor(vec![0, 1], vec![3]), 100.0)
+            .is_none());
+
+        let report = analyser
+            .capacity(&timetable, &corridor(vec![0, 1, 3], vec![1, 3]), 100.0)
+            .unwrap();
+        assert_eq!(report.occupation_time, 0.0);
+        assert_eq!(report.edges[0].block_id, None);
+    }
+
+    #[test]
+    fn test_capacity_double_track() {
+        let network = create_double_track();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let mut timetable = Timetable::new();
+        for (id, from, to, edge_id) in [(1, 0, 1, 1), (2, 1, 0, 2)] {
+            let mut run = service(id, from, to, 0.0, 20.0);
+            run.paths = vec![vec![from, to]];
+            run.edges = vec![vec![edge_id]];
+            timetable.add_service(run);
+        }
+        let corridor = Route {
+            nodes: vec![1, 0],
+            edges: vec![2],
+        };
+
+        let report = analyser.capacity(&timetable, &corridor, 100.0).unwrap();
+
+        assert_eq!(report.service_ids, vec![2]);
+        assert_eq!(report.occupation_time, 20.0);
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::{HashMap, VecDeque};
+
+use geo::CoordNum;
+
+use crate::{
+    algorithms::ShortestPathWithAccessability,
+    core::{Accessability, NodeId, ServiceId, Timetable},
+    graphs::TransitNetwork,
+};
+
+use super::{delay::Dependency, engine::VehicleId};
+
+/// The length of a shortest path and the IDs of the nodes along it.
+type Path = (f64, Vec<NodeId>);
+
+/// Options for `CirculationPlan::new`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CirculationOptions {
+    /// The minimum time in seconds between the arrival of a vehicle and its next departure.
+    pub min_turnaround: f64,
+    /// The additional time in seconds a vehicle needs to change its direction of travel at a node.
+    pub reversal_time: f64,
+    /// The speed of empty movements, in units of the edge length per second.
+    pub deadhead_speed: f64,
+    /// The longest empty movement, in units of the edge length.
+    pub max_deadhead_length: f64,
+}
+
+impl Default for CirculationOptions {
+    fn default() -> Self {
+        CirculationOptions {
+            min_turnaround: 0.0,
+            reversal_time: 0.0,
+            deadhead_speed: 10.0,
+            max_deadhead_length: f64::INFINITY,
+        }
+    }
+}
+
+/// An empty movement of a vehicle from the end of one run to the start of the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deadhead {
+    /// The IDs of the nodes along the movement.
+    pub path: Vec<NodeId>,
+    /// The length of the movement, in units of the edge length.
+    pub length: f64,
+    /// The departure time in seconds.
+    pub departure: f64,
+    /// The arrival time in seconds.
+    pub arrival: f64,
+}
+
+/// The change of a vehicle from one run to the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    /// The ID of the service the vehicle arrives with.
+    pub from: ServiceId,
+    /// The ID of the service the vehicle departs with.
+    pub to: ServiceId,
+    /// The minimum time in seconds between the arrival and the next departure, including turnaround,
+    /// reversals and the empty movement.
+    pub min_time: f64,
+    /// The empty movement between the runs, or `None` if the next run starts where the previous one ended.
+    pub deadhead: Option<Deadhead>,
+}
+
+/// The sequence of runs operated by a single vehicle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rotation {
+    /// The ID of the vehicle.
+    pub vehicle_id: VehicleId,
+    /// The IDs of the services operated by the vehicle, in order of departure.
+    pub service_ids: Vec<ServiceId>,
+    /// The changes between consecutive runs.
+    pub turns: Vec<Turn>,
+}
+
+impl Rotation {
+    /// Returns the total length of the empty movements, in units of the edge length.
+    pub fn deadhead_length(&self) -> f64 {
+        self.turns
+            .iter()
+            .filter_map(|turn| turn.deadhead.as_ref())
+            .map(|deadhead| deadhead.length)
+            .sum()
+    }
+}
+
+/// The assignment of vehicles to the runs of a timetable.
+///
+/// Each vehicle starts its next run where the previous one ended, or moves there empty along the
+/// shortest path through the topology graph. Empty movements never reverse on the way; a vehicle only
+/// reverses at the end of a run or of an empty movement, which takes `CirculationOptions::reversal_time`.
+///
+/// The plan uses the fewest vehicles that can operate all runs, and among those the shortest empty movements.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::prelude::*;
+/// use transit_grid::simulation::{CirculationOptions, CirculationPlan};
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 1000.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+/// });
+///
+/// let stop = |node_id, time| Stop { node_id, arrival: time, departure: time };
+/// let mut timetable = Timetable::new();
+/// timetable.add_service(Service::new(1, vec![stop(1, 0.0), stop(2, 100.0)]));
+/// timetable.add_service(Service::new(2, vec![stop(2, 300.0), stop(1, 400.0)]));
+/// timetable.add_service(Service::new(3, vec![stop(1, 200.0), stop(2, 300.0)]));
+///
+/// let options = CirculationOptions { min_turnaround: 120.0, ..Default::default() };
+/// let plan = CirculationPlan::new(&network, &timetable, &options);
+/// assert_eq!(plan.rotations.len(), 2);
+/// assert_eq!(plan.rotations[0].service_ids, vec![1, 2]);
+/// assert_eq!(plan.deadhead_length(), 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CirculationPlan {
+    /// The rotations of the vehicles, in order of their first departure.
+    pub rotations: Vec<Rotation>,
+    /// The IDs of the services that could not be assigned, because they have fewer than two stops
+    /// or cannot be bound to a path.
+    pub unassigned: Vec<ServiceId>,
+}
+
+/// A run with the nodes it starts and ends with.
+struct Run {
+    service_id: ServiceId,
+    departure: f64,
+    arrival: f64,
+    nodes: Vec<NodeId>,
+}
+
+impl CirculationPlan {
+    /// Plans the rotations of the vehicles operating a timetable.
+    ///
+    /// A run is only followed by runs that come after it in order of departure, so that a run does not follow
+    /// itself and rotations never return to an earlier run, even for runs without running time.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the services run on.
+    /// * `timetable` - The runs to operate. Unbound services are bound to the shortest path.
+    /// * `options` - The turnaround, reversal and empty movement parameters.
+    ///
+    /// # Returns
+    ///
+    /// * `CirculationPlan` - The rotations of the vehicles.
+    pub fn new<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        timetable: &Timetable,
+        options: &CirculationOptions,
+    ) -> Self {
+        let mut runs = Vec::new();
+        let mut unassigned = Vec::new();
+        for service in &timetable.services {
+            let mut service = service.clone();
+            let bound = service.stops.len() > 1
+                && (service.is_bound() || network.bind_service(&mut service));
+            let nodes = service.nodes();
+            if !bound || nodes.len() < 2 {
+                unassigned.push(service.id);
+                continue;
+            }
+            runs.push(Run {
+                service_id: service.id,
+                departure: service.stops[0].departure,
+                arrival: service.stops[service.stops.len() - 1].arrival,
+                nodes,
+            });
+        }
+        runs.sort_by(|a, b| a.departure.total_cmp(&b.departure));
+
+        let mut paths: HashMap<(NodeId, NodeId), Option<Path>> = HashMap::new();
+        let mut turns: Vec<Vec<(usize, Turn)>> = Vec::with_capacity(runs.len());
+        for (position, run) in runs.iter().enumerate() {
+            let mut candidates = Vec::new();
+            for (index, next) in runs.iter().enumerate().skip(position + 1) {
+                if next.departure < run.arrival {
+                    continue;
+                }
+                let (from, to) = (run.nodes[run.nodes.len() - 1], next.nodes[0]);
+                let path = paths.entry((from, to)).or_insert_with(|| {
+                    network
+                        .find_shortest_path_with_accessability(
+                            from,
+                            to,
+                            Accessability::UnreachableNodes(vec![]),
+                            |edge| edge.length.to_f64().unwrap_or(f64::INFINITY),
+                        )
+                        .filter(|(length, _)| *length <= options.max_deadhead_length)
+                });
+                if let Some(turn) = Self::turn(network, options, run, next, from, to, path.as_ref())
+                {
+                    candidates.push((index, turn));
+                }
+            }
+            turns.push(candidates);
+        }
+
+        let successors = assign(&turns);
+        let mut has_predecessor = vec![false; runs.len()];
+        for successor in successors.iter().flatten() {
+            has_predecessor[successor.0] = true;
+        }
+        let mut rotations = Vec::new();
+        for first in (0..runs.len()).filter(|index| !has_predecessor[*index]) {
+            let mut rotation = Rotation {
+                vehicle_id: rotations.len() as VehicleId + 1,
+                service_ids: vec![runs[first].service_id],
+                turns: Vec::new(),
+            };
+            let mut current = first;
+            while let Some((next, candidate)) = successors[current] {
+                rotation.service_ids.push(runs[next].service_id);
+                rotation.turns.push(turns[current][candidate].1.clone());
+                current = next;
+            }
+            rotations.push(rotation);
+        }
+        CirculationPlan {
+            rotations,
+            unassigned,
+        }
+    }
+
+    /// Returns the change from one run to another, or `None` if the vehicle cannot make it in time.
+    fn turn<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        options: &CirculationOptions,
+        run: &Run,
+        next: &Run,
+        from: NodeId,
+        to: NodeId,
+        path: Option<&Path>,
+    ) -> Option<Turn> {
+        let reverses = |before: NodeId, node: NodeId, after: NodeId| {
+            before == after || !network.is_traversable(&[before, node, after])
+        };
+        let reversal = |reverses: bool| if reverses { options.reversal_time } else { 0.0 };
+        let arrived_from = run.nodes[run.nodes.len() - 2];
+        let departs_to = next.nodes[1];
+
+        let (min_time, deadhead) = if from == to {
+            let min_time =
+                options.min_turnaround + reversal(reverses(arrived_from, from, departs_to));
+            (min_time, None)
+        } else {
+            let (length, path) = path?;
+            let departure = run.arrival
+                + options.min_turnaround
+                + reversal(reverses(arrived_from, from, path[1]));
+            let arrival = departure + length / options.deadhead_speed;
+            let ready = arrival + reversal(reverses(path[path.len() - 2], to, departs_to));
+            let deadhead = Deadhead {
+                path: path.clone(),
+                length: *length,
+                departure,
+                arrival,
+            };
+            (ready - run.arrival, Some(deadhead))
+        };
+        (run.arrival + min_time <= next.departure).then_some(Turn {
+            from: run.service_id,
+            to: next.service_id,
+            min_time,
+            deadhead,
+        })
+    }
+
+    /// Returns the number of vehicles needed.
+    pub fn vehicle_count(&self) -> usize {
+        self.rotations.len()
+    }
+
+    /// Returns the total length of all empty movements, in units of the edge length.
+    pub fn deadhead_length(&self) -> f64 {
+        self.rotations.iter().map(Rotation::deadhead_length).sum()
+    }
+
+    /// Returns the total length of all empty movements in kilometres, for edge lengths in metres.
+    pub fn deadhead_kilometres(&self) -> f64 {
+        self.deadhead_length() / 1000.0
+    }
+
+    /// Returns the turnarounds of all vehicles as dependencies for `DelayPropagation`.
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        self.rotations
+            .iter()
+            .flat_map(|rotation| &rotation.turns)
+            .map(|turn| Dependency::Turnaround {
+                from: turn.from,
+                to: turn.to,
+                min_time: turn.min_time,
+            })
+            .collect()
+    }
+}
+
+/// An edge of the residual graph of the assignment.
+struct Arc {
+    to: usize,
+    capacity: i32,
+    cost: f64,
+    reverse: usize,
+}
+
+/// Assigns each run at most one successor and predecessor, with the most assignments and, among those, the
+/// lowest total deadhead length, by successive shortest paths through a flow network.
+///
+/// # Returns
+///
+/// * `Vec<Option<(usize, usize)>>` - For each run the index of its successor and of the candidate turn.
+fn assign(turns: &[Vec<(usize, Turn)>]) -> Vec<Option<(usize, usize)>> {
+    let count = turns.len();
+    let (source, sink) = (2 * count, 2 * count + 1);
+    let mut arcs: Vec<Vec<Arc>> = (0..2 * count + 2).map(|_| Vec::new()).collect();
+    let add_arc = |arcs: &mut Vec<Vec<Arc>>, from: usize, to: usize, cost: f64| {
+        let (forward, backward) = (arcs[to].len(), arcs[from].len());
+        arcs[from].push(Arc {
+            to,
+            capacity: 1,
+            cost,
+            reverse: forward,
+        });
+        arcs[to].push(Arc {
+            to: from,
+            capacity: 0,
+            cost: -cost,
+            reverse: backward,
+        });
+    };
+    for (run, candidates) in turns.iter().enumerate() {
+        add_arc(&mut arcs, source, run, 0.0);
+        add_arc(&mut arcs, count + run, sink, 0.0);
+        for (next, turn) in candidates {
+            let length = turn
+                .deadhead
+                .as_ref()
+                .map_or(0.0, |deadhead| deadhead.length);
+            add_arc(&mut arcs, run, count + next, length);
+        }
+    }
+
+    loop {
+        // Bellman-Ford with a queue, as reverse arcs have negative costs.
+        let mut distances = vec![f64::INFINITY; arcs.len()];
+        let mut previous: Vec<Option<(usize, usize)>> = vec![None; arcs.len()];
+        let mut queued = vec![false; arcs.len()];
+        let mut queue = VecDeque::from([source]);
+        distances[source] = 0.0;
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+            for (index, arc) in arcs[node].iter().enumerate() {
+                let distance = distances[node] + arc.cost;
+                if arc.capacity > 0 && distance < distances[arc.to] - 1e-9 {
+                    distances[arc.to] = distance;
+                    previous[arc.to] = Some((node, index));
+                    if !queued[arc.to] {
+                        queued[arc.to] = true;
+                        queue.push_back(arc.to);
+                    }
+                }
+            }
+        }
+        if previous[sink].is_none() {
+            break;
+        }
+        let mut node = sink;
+        while let Some((from, index)) = previous[node] {
+            arcs[from][index].capacity -= 1;
+            let reverse = arcs[from][index].reverse;
+            arcs[node][reverse].capacity += 1;
+            node = from;
+        }
+    }
+
+    (0..count)
+        .map(|run| {
+            turns[run]
+                .iter()
+                .enumerate()
+                .find(|(_, (next, _))| {
+                    arcs[run]
+                        .iter()
+                        .any(|arc| arc.to == count + next && arc.capacity == 0)
+                })
+                .map(|(candidate, (next, _))| (*next, candidate))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::fixtures::{create_network, service};
+
+    fn options() -> CirculationOptions {
+        CirculationOptions {
+            min_turnaround: 60.0,
+            reversal_time: 30.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rotations() {
+        let network = create_network();
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(1, 0, 2, 0.0, 100.0));
+        timetable.add_service(service(2, 2, 0, 200.0, 100.0));
+        timetable.add_service(service(3, 0, 3, 50.0, 100.0));
+        timetable.add_service(service(5, 0, 2, 500.0, 100.0));
+        timetable.add_service(service(6, 2, 3, 0.0, 100.0));
+
+        let plan = CirculationPlan::new(&network, &timetable, &options());
+
+        assert_eq!(plan.vehicle_count(), 2);
+        assert_eq!(plan.rotations[0].service_ids, vec![1, 2, 5]);
+        assert_eq!(plan.rotations[0].turns[0].min_time, 90.0);
+        assert_eq!(plan.rotations[1].service_ids, vec![3]);
+        assert_eq!(plan.deadhead_length(), 0.0);
+        assert_eq!(plan.unassigned, vec![6]);
+        assert_eq!(
+            plan.dependencies()[1],
+            Dependency::Turnaround {
+                from: 2,
+                to: 5,
+                min_time: 90.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_runs_without_running_time() {
+        let network = create_network();
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(1, 0, 2, 100.0, 0.0));
+        timetable.add_service(service(2, 2, 0, 100.0, 0.0));
+        timetable.add_service(service(3, 0, 2, 100.0, 0.0));
+        let options = CirculationOptions {
+            min_turnaround: 0.0,
+            reversal_time: 0.0,
+            ..Default::default()
+        };
+
+        let plan = CirculationPlan::new(&network, &timetable, &options);
+
+        assert_eq!(plan.vehicle_count(), 1);
+        assert_eq!(plan.rotations[0].service_ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_deadhead() {
+        let network = create_network();
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(3, 0, 3, 50.0, 100.0));
+        timetable.add_service(service(5, 0, 2, 500.0, 100.0));
+
+        let plan = CirculationPlan::new(&network, &timetable, &options());
+
+        assert_eq!(plan.vehicle_count(), 1);
+        let turn = &plan.rotations[0].turns[0];
+        assert_eq!(turn.min_time, 140.0);
+        assert_eq!(
+            turn.deadhead,
+            Some(Deadhead {
+                path: vec![3, 1, 0],
+                length: 200.0,
+                departure: 240.0,
+                arrival: 260.0
+            })
+        );
+        assert_eq!(plan.deadhead_kilometres(), 0.2);
+    }
+
+    #[test]
+    fn test_reversal_constraints() {
+        let network = create_network();
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(3, 0, 3, 0.0, 100.0));
+        timetable.add_service(service(4, 2, 0, 1000.0, 100.0));
+
+        // The vehicle cannot move from node 3 to node 2 without reversing at switch 1.
+        let plan = CirculationPlan::new(&network, &timetable, &options());
+        assert_eq!(plan.vehicle_count(), 2);
+        assert!(plan.dependencies().is_empty());
+    }
+}
@@ -0,0 +1,695 @@
+use std::collections::{BTreeMap, HashMap};
+
+use geo::CoordNum;
+
+use crate::core::{NodeId, Service, ServiceId, Timetable};
+
+use super::{
+    blocks::Resource,
+    headway::{BlockingTime, HeadwayAnalyser},
+};
+
+/// Enum `DispatchRule` defining which run is served first when two runs request the same resource.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DispatchRule {
+    /// Runs are served in the order they are ready to depart.
+    Fifo,
+    /// Runs are served in the order they are ready to depart, unless a ready run of higher priority
+    /// would be held by the run in front of it.
+    Priority,
+}
+
+/// Options for `DelayPropagation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DelayOptions {
+    /// The share of each scheduled running time a delayed run can recover, between 0 and 1.
+    pub running_time_buffer: f64,
+    /// The dwell time in seconds a delayed run needs at each stop. Any scheduled dwell beyond it is buffer.
+    pub min_dwell: f64,
+    /// The rule deciding the order of runs on shared blocks and switches.
+    pub rule: DispatchRule,
+}
+
+impl Default for DelayOptions {
+    fn default() -> Self {
+        DelayOptions {
+            running_time_buffer: 0.0,
+            min_dwell: 0.0,
+            rule: DispatchRule::Fifo,
+        }
+    }
+}
+
+/// Enum `Dependency` defining a run that has to wait for another run besides sharing blocks and switches.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Dependency {
+    /// The vehicle of a run turns around to operate the next run, which cannot depart before it has arrived.
+    Turnaround {
+        /// The ID of the arriving service.
+        from: ServiceId,
+        /// The ID of the service departing from its first stop.
+        to: ServiceId,
+        /// The minimum turnaround time in seconds.
+        min_time: f64,
+    },
+    /// Passengers change between two runs at a node, and the departing run waits for them.
+    Connection {
+        /// The ID of the feeding service.
+        from: ServiceId,
+        /// The ID of the waiting service.
+        to: ServiceId,
+        /// The ID of the node both services stop at.
+        node_id: NodeId,
+        /// The minimum transfer time in seconds.
+        transfer_time: f64,
+        /// The longest time in seconds the waiting service is held beyond its scheduled departure.
+        max_wait: f64,
+    },
+}
+
+/// An initial delay of a run, e.g. caused by a failure, which is propagated to other runs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PrimaryDelay {
+    /// The ID of the delayed service.
+    pub service_id: ServiceId,
+    /// The index of the stop the run is delayed at. At the last stop, the delay holds the vehicle after its arrival.
+    pub stop_index: usize,
+    /// The delay of the departure in seconds.
+    pub delay: f64,
+}
+
+/// The delays of a run at one of its stops.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StopDelay {
+    /// The ID of the node of the stop.
+    pub node_id: NodeId,
+    /// The arrival delay in seconds.
+    pub arrival: f64,
+    /// The departure delay in seconds.
+    pub departure: f64,
+    /// The time in seconds the run was held at its departure by other runs.
+    pub knock_on: f64,
+}
+
+/// The delays of a single run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunDelays {
+    /// The ID of the service.
+    pub service_id: ServiceId,
+    /// The sum of the primary delays of the run in seconds.
+    pub primary: f64,
+    /// The time in seconds the run was held at its departures by other runs, through shared blocks and switches
+    /// or dependencies.
+    pub knock_on: f64,
+    /// The delays at each stop of the run.
+    pub stops: Vec<StopDelay>,
+}
+
+impl RunDelays {
+    /// Returns the arrival delay in seconds at the last stop of the run.
+    pub fn final_delay(&self) -> f64 {
+        self.stops.last().map_or(0.0, |stop| stop.arrival)
+    }
+}
+
+/// The delays of all runs at a station.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StationDelay {
+    /// The ID of the node of the station.
+    pub node_id: NodeId,
+    /// The sum of the delays in seconds of all runs stopping at the station.
+    pub total_delay: f64,
+    /// The largest delay in seconds of a run stopping at the station.
+    pub max_delay: f64,
+    /// The number of delayed runs stopping at the station.
+    pub delayed_runs: usize,
+    /// The sum of the times in seconds runs were held at their departures from the station by other runs.
+    pub knock_on: f64,
+}
+
+/// The result of `DelayPropagation::propagate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelayReport {
+    /// The delays of each run, in the order of the services in the timetable.
+    pub runs: Vec<RunDelays>,
+}
+
+impl DelayReport {
+    /// Returns the delays of the run of a service.
+    pub fn run(&self, service_id: ServiceId) -> Option<&RunDelays> {
+        self.runs.iter().find(|run| run.service_id == service_id)
+    }
+
+    /// Returns the delays at each station, sorted by node ID.
+    ///
+    /// The delay of a run at a station is its arrival delay, or its departure delay at the first stop.
+    pub fn stations(&self) -> Vec<StationDelay> {
+        let mut stations: BTreeMap<NodeId, StationDelay> = BTreeMap::new();
+        for run in &self.runs {
+            for (index, stop) in run.stops.iter().enumerate() {
+                let delay = if index == 0 {
+                    stop.departure
+                } else {
+                    stop.arrival
+                };
+                let station = stations.entry(stop.node_id).or_insert(StationDelay {
+                    node_id: stop.node_id,
+                    total_delay: 0.0,
+                    max_delay: 0.0,
+                    delayed_runs: 0,
+                    knock_on: 0.0,
+                });
+                station.total_delay += delay;
+                station.knock_on += stop.knock_on;
+                station.max_delay = station.max_delay.max(delay);
+                station.delayed_runs += usize::from(delay > 0.0);
+            }
+        }
+        stations.into_values().collect()
+    }
+}
+
+/// The state of a run while delays are propagated.
+struct RunState<'s> {
+    service: &'s Service,
+    priority: i32,
+    /// The blocking times of each run between consecutive stops.
+    legs: Vec<Vec<BlockingTime>>,
+    arrivals: Vec<f64>,
+    departures: Vec<f64>,
+    /// The index of the stop the run departs from next.
+    next: usize,
+    /// The time each departure was held by other runs.
+    knock_on: Vec<f64>,
+}
+
+impl RunState<'_> {
+    fn is_finished(&self) -> bool {
+        self.next + 1 >= self.service.stops.len()
+    }
+}
+
+/// A run ready to depart from its next stop.
+struct Candidate {
+    run: usize,
+    /// The earliest departure time in seconds.
+    ready: f64,
+    /// The earliest departure time in seconds without waiting for other runs.
+    own: f64,
+}
+
+/// Propagates primary delays through a timetable.
+///
+/// Runs depend on each other through the blocks and switches they share, as given by the stairways of a
+/// `HeadwayAnalyser`, and through `Dependency`s. Runs depart from each stop in the order of the `DispatchRule`,
+/// and a run entering a resource waits until the run before it has released it. Delayed runs recover time
+/// from the buffers of their running and dwell times.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::prelude::*;
+/// use transit_grid::simulation::{
+///     BlockLayout, DelayOptions, DelayPropagation, HeadwayAnalyser, HeadwayOptions, PrimaryDelay,
+/// };
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 1000.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+/// });
+///
+/// let stop = |node_id, time| Stop { node_id, arrival: time, departure: time };
+/// let mut timetable = Timetable::new();
+/// timetable.add_service(Service::new(1, vec![stop(1, 0.0), stop(2, 100.0)]));
+/// timetable.add_service(Service::new(2, vec![stop(1, 300.0), stop(2, 400.0)]));
+///
+/// let analyser = HeadwayAnalyser::new(&network, BlockLayout::per_edge(&network), HeadwayOptions::default());
+/// let propagation = DelayPropagation::new(&analyser, DelayOptions::default());
+/// let primary = PrimaryDelay { service_id: 1, stop_index: 0, delay: 600.0 };
+/// let report = propagation.propagate(&timetable, &[primary]);
+///
+/// // Service 2 departs before the delayed service 1 and keeps its schedule.
+/// assert_eq!(report.run(1).unwrap().final_delay(), 600.0);
+/// assert_eq!(report.run(2).unwrap().final_delay(), 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DelayPropagation<'a, R: Copy, T: CoordNum> {
+    analyser: &'a HeadwayAnalyser<'a, R, T>,
+    options: DelayOptions,
+    dependencies: Vec<Dependency>,
+    priorities: HashMap<ServiceId, i32>,
+}
+
+impl<'a, R: Copy, T: CoordNum> DelayPropagation<'a, R, T> {
+    /// Creates a delay propagation without dependencies, with all runs at priority 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `analyser` - The analyser giving the blocking times of the runs.
+    /// * `options` - The buffers and the dispatching rule.
+    pub fn new(analyser: &'a HeadwayAnalyser<'a, R, T>, options: DelayOptions) -> Self {
+        DelayPropagation {
+            analyser,
+            options,
+            dependencies: Vec::new(),
+            priorities: HashMap::new(),
+        }
+    }
+
+    /// Adds a dependency between two runs.
+    pub fn add_dependency(&mut self, dependency: Dependency) {
+        self.dependencies.push(dependency);
+    }
+
+    /// Sets the priority of the run of a service for `DispatchRule::Priority`, higher values are served first.
+    pub fn set_priority(&mut self, service_id: ServiceId, priority: i32) {
+        self.priorities.insert(service_id, priority);
+    }
+
+    /// Propagates primary delays through a timetable.
+    ///
+    /// Dependencies that wait for each other in a cycle are ignored where needed to continue.
+    ///
+    /// # Arguments
+    ///
+    /// * `timetable` - The scheduled runs. Services with fewer than two stops are ignored, services without a
+    ///   stairway do not share any resources.
+    /// * `primary` - The initial delays. Delays at stops beyond the last stop of a run are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `DelayReport` - The delays of all runs.
+    pub fn propagate(&self, timetable: &Timetable, primary: &[PrimaryDelay]) -> DelayReport {
+        let setup_time = self.analyser.options.setup_time;
+        let mut runs: Vec<RunState> = timetable
+            .services
+            .iter()
+            .filter(|service| service.stops.len() > 1)
+            .map(|service| {
+                let mut legs = vec![Vec::new(); service.stops.len() - 1];
+                let stairway = self.analyser.stairway(service);
+                for blocking in stairway
+                    .into_iter()
+                    .flat_map(|stairway| stairway.blocking_times)
+                {
+                    // The leg in which the head of the train reaches the resource.
+                    let entry = blocking.start + setup_time;
+                    let leg = service.stops[..legs.len()]
+                        .iter()
+                        .rposition(|stop| stop.departure <= entry)
+                        .unwrap_or(0);
+                    legs[leg].push(blocking);
+                }
+                RunState {
+                    service,
+                    priority: self.priorities.get(&service.id).copied().unwrap_or(0),
+                    legs,
+                    arrivals: vec![f64::NAN; service.stops.len()],
+                    departures: vec![f64::NAN; service.stops.len()],
+                    next: 0,
+                    knock_on: vec![0.0; service.stops.len()],
+                }
+            })
+            .collect();
+        let indices: HashMap<ServiceId, usize> = runs
+            .iter()
+            .enumerate()
+            .map(|(index, run)| (run.service.id, index))
+            .collect();
+        let mut primary_delays: HashMap<(usize, usize), f64> = HashMap::new();
+        for delay in primary {
+            let Some(run) = indices.get(&delay.service_id) else {
+                continue;
+            };
+            if delay.stop_index < runs[*run].service.stops.len() {
+                *primary_delays.entry((*run, delay.stop_index)).or_default() += delay.delay;
+            }
+        }
+        for run in &mut runs {
+            run.arrivals[0] = run.service.stops[0].arrival;
+        }
+
+        let mut free: HashMap<Resource, f64> = HashMap::new();
+        while runs.iter().any(|run| !run.is_finished()) {
+            let mut candidates: Vec<Candidate> = (0..runs.len())
+                .filter_map(|run| self.candidate(&runs, &indices, &primary_delays, run, false))
+                .collect();
+            if candidates.is_empty() {
+                candidates = (0..runs.len())
+                    .filter_map(|run| self.candidate(&runs, &indices, &primary_delays, run, true))
+                    .collect();
+            }
+            let Some(candidate) = self.select(&runs, candidates) else {
+                break;
+            };
+
+            let run = &mut runs[candidate.run];
+            let index = run.next;
+            let (stop, next_stop) = (&run.service.stops[index], &run.service.stops[index + 1]);
+            let shift = run.legs[index]
+                .iter()
+                .filter_map(|blocking| free.get(&blocking.resource).map(|end| end - blocking.start))
+                .fold(candidate.ready - stop.departure, f64::max);
+            for blocking in &run.legs[index] {
+                let end = free.entry(blocking.resource).or_insert(f64::NEG_INFINITY);
+                *end = end.max(blocking.end + shift);
+            }
+            let departure = stop.departure + shift;
+            let running_time = next_stop.arrival - stop.departure;
+            run.departures[index] = departure;
+            run.knock_on[index] = departure - candidate.own;
+            run.arrivals[index + 1] = next_stop
+                .arrival
+                .max(departure + running_time * (1.0 - self.options.running_time_buffer));
+            run.next += 1;
+            if run.is_finished() {
+                let held = primary_delays
+                    .get(&(candidate.run, index + 1))
+                    .copied()
+                    .unwrap_or(0.0);
+                run.departures[index + 1] = next_stop.departure.max(run.arrivals[index + 1]) + held;
+            }
+        }
+
+        DelayReport {
+            runs: runs
+                .iter()
+                .map(|run| RunDelays {
+                    service_id: run.service.id,
+                    primary: primary_delays
+                        .iter()
+                        .filter(|((index, _), _)| indices[&run.service.id] == *index)
+                        .map(|(_, delay)| *delay)
+                        .sum(),
+                    knock_on: run.knock_on.iter().sum(),
+                    stops: run
+                        .service
+                        .stops
+                        .iter()
+                        .enumerate()
+                        .map(|(index, stop)| StopDelay {
+                            node_id: stop.node_id,
+                            arrival: run.arrivals[index] - stop.arrival,
+                            departure: run.departures[index] - stop.departure,
+                            knock_on: run.knock_on[index],
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the earliest departure of a run from its next stop, or `None` if the run is finished or waits
+    /// for a run that has not arrived yet and `force` is not set.
+    fn candidate(
+        &self,
+        runs: &[RunState],
+        indices: &HashMap<ServiceId, usize>,
+        primary_delays: &HashMap<(usize, usize), f64>,
+        index: usize,
+        force: bool,
+    ) -> Option<Candidate> {
+        let run = &runs[index];
+        if run.is_finished() {
+            return None;
+        }
+        let stop_index = run.next;
+        let stop = &run.service.stops[stop_index];
+        let dwell = (stop.departure - stop.arrival).min(self.options.min_dwell);
+        let own = stop.departure.max(run.arrivals[stop_index] + dwell)
+            + primary_delays
+                .get(&(index, stop_index))
+                .copied()
+                .unwrap_or(0.0);
+
+        let mut ready = own;
+        for dependency in &self.dependencies {
+            let earliest = match *dependency {
+                Dependency::Turnaround { from, to, min_time }
+                    if to == run.service.id && stop_index == 0 =>
+                {
+                    let Some(&feeder_index) = indices.get(&from) else {
+                        continue;
+                    };
+                    let feeder = &runs[feeder_index];
+                    let last = feeder.arrivals.len() - 1;
+                    let held = primary_delays
+                        .get(&(feeder_index, last))
+                        .copied()
+                        .unwrap_or(0.0);
+                    feeder
+                        .is_finished()
+                        .then_some(feeder.arrivals[last] + held + min_time)
+                }
+                Dependency::Connection {
+                    from,
+                    to,
+                    node_id,
+                    transfer_time,
+                    max_wait,
+                } if to == run.service.id && stop.node_id == node_id => {
+                    let Some(feeder) = indices.get(&from).map(|from| &runs[*from]) else {
+                        continue;
+                    };
+                    let Some(feeder_stop) = feeder
+                        .service
+                        .stops
+                        .iter()
+                        .position(|stop| stop.node_id == node_id)
+                    else {
+                        continue;
+                    };
+                    let arrived = feeder_stop <= feeder.next;
+                    let arrival = feeder.arrivals[feeder_stop] + transfer_time;
+                    arrived.then_some(arrival.min(stop.departure + max_wait))
+                }
+                _ => continue,
+            };
+            match earliest {
+                Some(earliest) => ready = ready.max(earliest),
+                None if !force => return None,
+                None => {}
+            }
+        }
+        Some(Candidate {
+            run: index,
+            ready,
+            own,
+        })
+    }
+
+    /// Selects the candidate departing next according to the dispatching rule.
+    fn select(&self, runs: &[RunState], mut candidates: Vec<Candidate>) -> Option<Candidate> {
+        candidates.sort_by(|a, b| a.ready.total_cmp(&b.ready).then(a.run.cmp(&b.run)));
+        if self.options.rule == DispatchRule::Fifo || candidates.is_empty() {
+            return candidates.into_iter().next();
+        }
+
+        // The blocking times of a candidate's next leg, shifted by its delay.
+        let blocking = |candidate: &Candidate| {
+            let run = &runs[candidate.run];
+            let shift = candidate.ready - run.service.stops[run.next].departure;
+            run.legs[run.next].iter().map(move |blocking| {
+                (
+                    blocking.resource,
+                    blocking.start + shift,
+                    blocking.end + shift,
+                )
+            })
+        };
+        let first = &candidates[0];
+        let held = (1..candidates.len())
+            .filter(|index| runs[candidates[*index].run].priority > runs[first.run].priority)
+            .filter(|index| {
+                blocking(&candidates[*index]).any(|(resource, start, _)| {
+                    blocking(first).any(|(other, _, end)| other == resource && start < end)
+                })
+            })
+            .max_by_key(|index| {
+                (
+                    runs[candidates[*index].run].priority,
+                    std::cmp::Reverse(*index),
+                )
+            });
+        Some(candidates.swap_remove(held.unwrap_or(0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{
+        fixtures::{create_network, service},
+        BlockLayout, HeadwayOptions,
+    };
+
+    fn create_timetable() -> Timetable {
+        let mut timetable = Timetable::new();
+        timetable.add_service(service(1, 0, 2, 0.0, 20.0));
+        timetable.add_service(service(2, 0, 2, 30.0, 20.0));
+        timetable.add_service(service(3, 2, 1, 60.0, 20.0));
+        timetable
+    }
+
+    fn delay(service_id: ServiceId, delay: f64) -> PrimaryDelay {
+        PrimaryDelay {
+            service_id,
+            stop_index: 0,
+            delay,
+        }
+    }
+
+    #[test]
+    fn test_turnaround() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let mut propagation = DelayPropagation::new(&analyser, DelayOptions::default());
+        propagation.add_dependency(Dependency::Turnaround {
+            from: 1,
+            to: 3,
+            min_time: 30.0,
+        });
+
+        let report = propagation.propagate(&create_timetable(), &[delay(1, 600.0)]);
+
+        let first = report.run(1).unwrap();
+        assert_eq!((first.primary, first.knock_on), (600.0, 0.0));
+        assert_eq!(first.final_delay(), 600.0);
+        assert_eq!(report.run(2).unwrap().final_delay(), 0.0);
+        let turnaround = report.run(3).unwrap();
+        assert_eq!((turnaround.primary, turnaround.knock_on), (0.0, 590.0));
+        assert_eq!(turnaround.stops[0].departure, 590.0);
+
+        let stations = report.stations();
+        assert_eq!(
+            stations[2],
+            StationDelay {
+                node_id: 2,
+                total_delay: 1190.0,
+                max_delay: 600.0,
+                delayed_runs: 2,
+                knock_on: 590.0
+            }
+        );
+        assert_eq!(turnaround.stops[0].knock_on, 590.0);
+    }
+
+    #[test]
+    fn test_primary_delay_at_last_stop() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let mut propagation = DelayPropagation::new(&analyser, DelayOptions::default());
+        propagation.add_dependency(Dependency::Turnaround {
+            from: 1,
+            to: 3,
+            min_time: 30.0,
+        });
+        let primary = [
+            PrimaryDelay {
+                service_id: 1,
+                stop_index: 1,
+                delay: 100.0,
+            },
+            PrimaryDelay {
+                service_id: 2,
+                stop_index: 2,
+                delay: 100.0,
+            },
+        ];
+
+        let report = propagation.propagate(&create_timetable(), &primary);
+
+        let first = report.run(1).unwrap();
+        assert_eq!(first.primary, 100.0);
+        assert_eq!(first.final_delay(), 0.0);
+        assert_eq!(first.stops[1].departure, 100.0);
+        assert_eq!(report.run(2).unwrap().primary, 0.0);
+        // The vehicle held at node 2 until 120 turns around to depart with service 3 at 150.
+        let turnaround = report.run(3).unwrap();
+        assert_eq!(turnaround.stops[0].departure, 90.0);
+        assert_eq!(turnaround.knock_on, 90.0);
+    }
+
+    #[test]
+    fn test_connection_and_buffers() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let options = DelayOptions {
+            running_time_buffer: 0.5,
+            ..Default::default()
+        };
+        let mut propagation = DelayPropagation::new(&analyser, options);
+        propagation.add_dependency(Dependency::Connection {
+            from: 1,
+            to: 3,
+            node_id: 2,
+            transfer_time: 30.0,
+            max_wait: 570.0,
+        });
+
+        // Service 3 waits at node 2 for the delayed service 1 and runs back over the blocks 2 and 1,
+        // holding service 4 which needs block 1 next.
+        let mut timetable = create_timetable();
+        timetable.services[2] = service(3, 2, 0, 60.0, 20.0);
+        timetable.add_service(service(4, 0, 3, 635.0, 20.0));
+
+        let report = propagation.propagate(&timetable, &[delay(1, 600.0)]);
+
+        assert_eq!(report.run(1).unwrap().final_delay(), 590.0);
+        // The connection would hold service 3 until 640, but it waits no longer than until 630.
+        let waiting = report.run(3).unwrap();
+        assert_eq!((waiting.primary, waiting.knock_on), (0.0, 570.0));
+        assert_eq!(waiting.final_delay(), 560.0);
+        // Service 4 enters block 1 after service 3 has left it at 650.
+        let follower = report.run(4).unwrap();
+        assert_eq!(follower.stops[0].departure, 15.0);
+        assert_eq!(follower.final_delay(), 5.0);
+    }
+
+    #[test]
+    fn test_dispatch_rules() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+
+        let propagation = DelayPropagation::new(&analyser, DelayOptions::default());
+        let report = propagation.propagate(&create_timetable(), &[delay(1, 25.0)]);
+        assert_eq!(report.run(1).unwrap().final_delay(), 25.0);
+        assert_eq!(report.run(2).unwrap().knock_on, 5.0);
+
+        let mut propagation = DelayPropagation::new(
+            &analyser,
+            DelayOptions {
+                rule: DispatchRule::Priority,
+                ..Default::default()
+            },
+        );
+        propagation.set_priority(2, 1);
+        let report = propagation.propagate(&create_timetable(), &[delay(1, 25.0)]);
+        assert_eq!(report.run(2).unwrap().final_delay(), 0.0);
+        assert_eq!(report.run(1).unwrap().knock_on, 15.0);
+        assert_eq!(report.run(1).unwrap().final_delay(), 40.0);
+    }
+}
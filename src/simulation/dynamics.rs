@@ -0,0 +1,379 @@
+use geo::CoordNum;
+
+use crate::{core::EdgeId, graphs::TransitNetwork};
+
+use super::Route;
+
+/// Structure describing the driving characteristics of a train.
+///
+/// Speeds, accelerations and the length are given in units of the edge length and seconds,
+/// e.g. meters per second and meters per second squared for edges measured in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainDynamics {
+    /// The maximum speed of the train.
+    pub max_speed: f64,
+    /// The acceleration of the train, assumed constant up to the maximum speed.
+    pub acceleration: f64,
+    /// The service braking deceleration of the train, given as a positive value.
+    pub deceleration: f64,
+    /// The length of the train.
+    pub length: f64,
+    /// The mass of the train in tonnes. The speed profile does not depend on it, as the acceleration and the
+    /// deceleration are given directly. It is carried for callers, e.g. to estimate the traction energy.
+    pub mass: f64,
+}
+
+/// A point of a `SpeedProfile`, between which the train accelerates, cruises or brakes uniformly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfilePoint {
+    /// The distance of the head of the train from the start of the route.
+    pub distance: f64,
+    /// The speed of the train at the point.
+    pub speed: f64,
+    /// The time in seconds since the departure at the start of the route.
+    pub time: f64,
+}
+
+/// The speed of a train over the distance along a route, as computed by `TrainDynamics::speed_profile`.
+///
+/// The profile starts and ends at standstill and its points are sorted by distance and time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedProfile {
+    /// The points of the profile.
+    pub points: Vec<ProfilePoint>,
+}
+
+impl SpeedProfile {
+    /// Returns the time in seconds the train needs for the whole route.
+    pub fn running_time(&self) -> f64 {
+        self.points.last().map_or(0.0, |point| point.time)
+    }
+
+    /// Returns the length of the route.
+    pub fn distance(&self) -> f64 {
+        self.points.last().map_or(0.0, |point| point.distance)
+    }
+
+    /// Returns the time in seconds at which the head of the train reaches the given distance.
+    ///
+    /// Distances outside of the route are clamped to its start and end.
+    pub fn time_at(&self, distance: f64) -> f64 {
+        let Some(index) = self
+            .points
+            .iter()
+            .position(|point| point.distance >= distance)
+        else {
+            return self.running_time();
+        };
+        if index == 0 {
+            return 0.0;
+        }
+        let (start, end) = (self.points[index - 1], self.points[index]);
+        let acceleration =
+            (end.speed.powi(2) - start.speed.powi(2)) / (2.0 * (end.distance - start.distance));
+        let travelled = distance - start.distance;
+        if acceleration.abs() < 1e-12 {
+            return start.time + travelled / start.speed;
+        }
+        let speed = (start.speed.powi(2) + 2.0 * acceleration * travelled)
+            .max(0.0)
+            .sqrt();
+        start.time + (speed - start.speed) / acceleration
+    }
+
+    /// Returns the distance the head of the train has travelled at the given time in seconds.
+    ///
+    /// Times outside of the running time are clamped to its start and end.
+    pub fn distance_at(&self, time: f64) -> f64 {
+        let Some(index) = self.points.iter().position(|point| point.time >= time) else {
+            return self.distance();
+        };
+        if index == 0 {
+            return 0.0;
+        }
+        let (start, end) = (self.points[index - 1], self.points[index]);
+        let elapsed = time - start.time;
+        let acceleration = (end.speed - start.speed) / (end.time - start.time);
+        start.distance + start.speed * elapsed + 0.5 * acceleration * elapsed.powi(2)
+    }
+
+    /// Returns the speed of the train when its head is at the given distance.
+    pub fn speed_at(&self, distance: f64) -> f64 {
+        let Some(index) = self
+            .points
+            .iter()
+            .position(|point| point.distance >= distance)
+        else {
+            return 0.0;
+        };
+        if index == 0 {
+            return self.points[0].speed;
+        }
+        let (start, end) = (self.points[index - 1], self.points[index]);
+        let fraction = (distance - start.distance) / (end.distance - start.distance);
+        (start.speed.powi(2) + fraction * (end.speed.powi(2) - start.speed.powi(2)))
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+impl TrainDynamics {
+    /// Computes the fastest speed profile of the train along a route, from standstill to standstill.
+    ///
+    /// The train never exceeds its maximum speed nor the speed limit of an edge, see `TransitNetwork::speed_limit`.
+    /// It brakes early enough to meet lower limits ahead, and it only accelerates after a restriction once
+    /// its full length has cleared it.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the route runs on.
+    /// * `route` - The route of the train.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SpeedProfile>` - The speed profile, or `None` if an edge of the route is missing in the network
+    ///   or the train cannot move.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geo::{coord, Coord, LineString};
+    /// use transit_grid::prelude::*;
+    /// use transit_grid::simulation::{Route, TrainDynamics};
+    ///
+    /// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+    /// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+    /// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+    /// network.add_edge(TransitEdge {
+    ///     id: 1,
+    ///     source: 1,
+    ///     target: 2,
+    ///     length: 1000.0,
+    ///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+    /// });
+    ///
+    /// let train = TrainDynamics { max_speed: 20.0, acceleration: 1.0, deceleration: 1.0, length: 100.0, mass: 200.0 };
+    /// let route = Route::from_nodes(&network, vec![1, 2]).unwrap();
+    /// let profile = train.speed_profile(&network, &route).unwrap();
+    ///
+    /// // 20 s accelerating over 200, 30 s cruising over 600 and 20 s braking over 200.
+    /// assert!((profile.running_time() - 70.0).abs() < 1e-9);
+    /// ```
+    pub fn speed_profile<R: Copy, T: CoordNum>(
+        &self,
+        network: &TransitNetwork<R, T>,
+        route: &Route,
+    ) -> Option<SpeedProfile> {
+        self.profile_for_edges(network, &route.edges)
+    }
+
+    /// Computes the speed profile along consecutive edges, from standstill to standstill.
+    pub(super) fn profile_for_edges<R: Copy, T: CoordNum>(
+        &self,
+        network: &TransitNetwork<R, T>,
+        edges: &[EdgeId],
+    ) -> Option<SpeedProfile> {
+        if self.max_speed <= 0.0 || self.acceleration <= 0.0 || self.deceleration <= 0.0 {
+            return None;
+        }
+
+        // Sections of the route with the limit that applies while the head of the train is on them.
+        let mut sections = Vec::with_capacity(edges.len());
+        let mut start = 0.0;
+        for edge_id in edges {
+            let edge = network.get_edge_by_id(*edge_id)?;
+            let end = start + edge.length.to_f64()?;
+            let limit = network
+                .speed_limit(*edge_id)
+                .map_or(self.max_speed, |limit| limit.min(self.max_speed));
+            sections.push((start, end, limit));
+            start = end;
+        }
+        let total = start;
+
+        // A limit keeps applying until the tail of the train has left its section.
+        let mut breakpoints: Vec<f64> = sections
+            .iter()
+            .flat_map(|(start, end, _)| [*start, (end + self.length).min(total)])
+            .chain([total])
+            .collect();
+        breakpoints.sort_by(f64::total_cmp);
+        breakpoints.dedup();
+        let intervals: Vec<(f64, f64, f64)> = breakpoints
+            .windows(2)
+            .map(|pair| {
+                let limit = sections
+                    .iter()
+                    .filter(|(start, end, _)| *start <= pair[0] && pair[0] < end + self.length)
+                    .map(|(_, _, limit)| *limit)
+                    .fold(self.max_speed, f64::min);
+                (pair[0], pair[1], limit)
+            })
+            .collect();
+        if intervals.iter().any(|(_, _, limit)| *limit <= 0.0) {
+            return None;
+        }
+
+        // Speeds at the boundaries of the intervals, limited by braking ahead and accelerating behind.
+        let mut speeds = vec![0.0; intervals.len() + 1];
+        for i in 1..intervals.len() {
+            speeds[i] = intervals[i - 1].2.min(intervals[i].2);
+        }
+        for (i, (start, end, _)) in intervals.iter().enumerate().rev() {
+            let braking = (speeds[i + 1].powi(2) + 2.0 * self.deceleration * (end - start)).sqrt();
+            speeds[i] = speeds[i].min(braking);
+        }
+        for (i, (start, end, _)) in intervals.iter().enumerate() {
+            let accelerating = (speeds[i].powi(2) + 2.0 * self.acceleration * (end - start)).sqrt();
+            speeds[i + 1] = speeds[i + 1].min(accelerating);
+        }
+
+        let mut points = vec![ProfilePoint {
+            distance: 0.0,
+            speed: 0.0,
+            time: 0.0,
+        }];
+        for (i, (start, end, limit)) in intervals.iter().enumerate() {
+            let (entry, exit) = (speeds[i], speeds[i + 1]);
+            let (a, b) = (self.acceleration, self.deceleration);
+            let peak = ((2.0 * a * b * (end - start) + b * entry.powi(2) + a * exit.powi(2))
+                / (a + b))
+                .sqrt()
+                .min(*limit)
+                .max(entry.max(exit));
+            let accelerating = (peak.powi(2) - entry.powi(2)) / (2.0 * a);
+            let braking = (peak.powi(2) - exit.powi(2)) / (2.0 * b);
+            let cruising = (end - start - accelerating - braking).max(0.0);
+
+            let mut push = |distance: f64, speed: f64, duration: f64| {
+                let last = points[points.len() - 1];
+                if distance > last.distance {
+                    points.push(ProfilePoint {
+                        distance,
+                        speed,
+                        time: last.time + duration,
+                    });
+                }
+            };
+            let accelerated = (start + accelerating).min(*end);
+            push(accelerated, peak, (peak - entry) / a);
+            push((accelerated + cruising).min(*end), peak, cruising / peak);
+            push(*end, exit, (peak - exit) / b);
+        }
+        Some(SpeedProfile { points })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Accessability, NodeId, TransitEdge, TransitNode},
+        operations::TransitNetworkModifier,
+    };
+    use geo::{coord, Coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        let positions = [0.0, 1000.0, 1200.0, 3000.0];
+        for (id, x) in positions.iter().enumerate() {
+            network.add_node(TransitNode {
+                id: id as NodeId,
+                location: coord! { x: *x, y: 0.0 },
+            });
+        }
+        let edge = |id, source: usize, target: usize| TransitEdge {
+            id,
+            source: source as NodeId,
+            target: target as NodeId,
+            length: positions[target] - positions[source],
+            path: LineString(vec![
+                coord! { x: positions[source], y: 0.0 },
+                coord! { x: positions[target], y: 0.0 },
+            ]),
+        };
+        network.add_edge(edge(1, 0, 1));
+        network.add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0]));
+        network.add_edge_with_accessibility(edge(3, 2, 3), Accessability::ReachableNodes(vec![1]));
+        network
+    }
+
+    fn train() -> TrainDynamics {
+        TrainDynamics {
+            max_speed: 40.0,
+            acceleration: 0.5,
+            deceleration: 1.0,
+            length: 100.0,
+            mass: 400.0,
+        }
+    }
+
+    fn route() -> Route {
+        Route {
+            nodes: vec![0, 1, 2, 3],
+            edges: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_speed_profile_unrestricted() {
+        let network = create_network();
+
+        let profile = train().speed_profile(&network, &route()).unwrap();
+
+        // 80 s accelerating over 1600, 40 s braking over 800 and 600 cruising at 40 in 15 s.
+        assert!((profile.running_time() - 135.0).abs() < 1e-9);
+        assert_eq!(profile.distance(), 3000.0);
+        assert_eq!(profile.points.first().unwrap().speed, 0.0);
+        assert_eq!(profile.points.last().unwrap().speed, 0.0);
+        assert!((profile.speed_at(2000.0) - 40.0).abs() < 1e-9);
+        assert!((profile.time_at(1600.0) - 80.0).abs() < 1e-9);
+        assert!((profile.distance_at(80.0) - 1600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_speed_profile_respects_restriction() {
+        let mut network = create_network();
+        network.set_speed_limit(2, 10.0);
+
+        let profile = train().speed_profile(&network, &route()).unwrap();
+
+        // The train brakes to reach the limit where its head enters edge 2.
+        assert!((profile.speed_at(1000.0) - 10.0).abs() < 1e-9);
+        assert!(profile.speed_at(950.0) > 10.0);
+        // The limit applies until the tail has cleared edge 2.
+        assert!((profile.speed_at(1299.0) - 10.0).abs() < 1e-9);
+        assert!(profile.speed_at(1350.0) > 10.0);
+        for point in &profile.points {
+            if (1000.0..=1300.0).contains(&point.distance) {
+                assert!(point.speed <= 10.0 + 1e-9);
+            }
+        }
+        let unrestricted = train().speed_profile(&create_network(), &route()).unwrap();
+        assert!(profile.running_time() > unrestricted.running_time());
+        assert!(profile
+            .points
+            .windows(2)
+            .all(|pair| pair[0].time < pair[1].time && pair[0].distance < pair[1].distance));
+        for distance in [500.0, 1100.0, 2500.0] {
+            let time = profile.time_at(distance);
+            assert!((profile.distance_at(time) - distance).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_speed_profile_invalid() {
+        let network = create_network();
+        let missing = Route {
+            nodes: vec![0, 4],
+            edges: vec![4],
+        };
+
+        assert!(train().speed_profile(&network, &missing).is_none());
+        let stalled = TrainDynamics {
+            acceleration: 0.0,
+            ..train()
+        };
+        assert!(stalled.speed_profile(&network, &route()).is_none());
+    }
+}
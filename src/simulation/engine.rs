@@ -0,0 +1,871 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    iter::Sum,
+};
+
+use geo::{Coord, CoordFloat, CoordNum};
+
+use crate::{
+    algorithms::edge_split::split_path,
+    core::{IdType, NetworkPosition, NodeId, TransitEdge},
+    graphs::TransitNetwork,
+};
+
+use super::{
+    blocks::Occupancy, BlockId, BlockLayout, Conflict, Event, EventKind, Resource, Rng, Route,
+    SpeedProfile, TrainDynamics,
+};
+
+/// Type alias for the identifier of a vehicle.
+pub type VehicleId = IdType;
+
+/// Structure representing a vehicle to be simulated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vehicle {
+    /// The unique identifier of the vehicle.
+    pub id: VehicleId,
+    /// The route the vehicle follows.
+    pub route: Route,
+    /// The time in seconds the vehicle departs from the first node of its route.
+    pub departure: f64,
+    /// The speed of the vehicle in units of the edge length per second.
+    pub speed: f64,
+    /// The dwell time in seconds at each intermediate node the vehicle stops at.
+    pub stops: HashMap<NodeId, f64>,
+    /// The driving characteristics of the vehicle. Without them, the vehicle moves at constant `speed`
+    /// and ignores speed limits, with them it accelerates and brakes between its stops
+    /// and `speed` caps its maximum speed.
+    pub dynamics: Option<TrainDynamics>,
+}
+
+/// Options controlling the random variations of a `Simulation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationOptions {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The maximum relative deviation of a vehicle's speed, drawn once per vehicle, in `[0, 1)`.
+    pub speed_variation: f64,
+    /// The maximum relative deviation of a dwell time, drawn once per stop, in `[0, 1)`.
+    pub dwell_variation: f64,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        SimulationOptions {
+            seed: 0,
+            speed_variation: 0.0,
+            dwell_variation: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Waiting,
+    Moving { entered: f64, leave: f64 },
+    Stopped,
+}
+
+/// The movement of a vehicle from one stop to the next.
+#[derive(Debug, Clone)]
+struct Run {
+    departed: f64,
+    first_leg: usize,
+    /// The distance from the start of the run to the start of each of its edges.
+    starts: Vec<f64>,
+    profile: SpeedProfile,
+}
+
+#[derive(Debug, Clone)]
+struct VehicleState {
+    vehicle: Vehicle,
+    speed: f64,
+    /// The distance from the start of the route to the start of each edge and to its end.
+    starts: Vec<f64>,
+    /// The index of the edge the vehicle is on or enters next.
+    leg: usize,
+    phase: Phase,
+    run: Option<Run>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scheduled {
+    time: f64,
+    sequence: u64,
+    vehicle: usize,
+    kind: EventKind,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    // Reversed, so that the `BinaryHeap` pops the earliest event first and ties in the order of scheduling.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then(other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// A discrete-event simulation of vehicles moving along their routes on a `TransitNetwork`.
+///
+/// Each vehicle departs from the first node of its route, enters and leaves the edges of the route one after
+/// another and arrives at its last node. At the nodes listed in its `stops` it arrives, dwells and departs again.
+/// Events with the same time are emitted in the order they were scheduled, so the simulation is deterministic
+/// for the same vehicles, added in the same order, and the same `SimulationOptions`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::prelude::*;
+/// use transit_grid::simulation::{EventKind, Route, Simulation, SimulationOptions, Vehicle};
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 100.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 100.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 100.0, y: 0.0 }]),
+/// });
+///
+/// let mut simulation = Simulation::new(&network, SimulationOptions::default());
+/// simulation.add_vehicle(Vehicle {
+///     id: 1,
+///     route: Route::shortest(&network, 1, 2).unwrap(),
+///     departure: 0.0,
+///     speed: 10.0,
+///     stops: HashMap::new(),
+///     dynamics: None,
+/// });
+///
+/// let events = simulation.run();
+/// assert_eq!(events.len(), 4);
+/// assert_eq!(events[3].kind, EventKind::Arrive { node_id: 2 });
+/// assert_eq!(events[3].time, 10.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulation<'a, R: Copy, T: CoordNum> {
+    network: &'a TransitNetwork<R, T>,
+    options: SimulationOptions,
+    rng: Rng,
+    now: f64,
+    sequence: u64,
+    queue: BinaryHeap<Scheduled>,
+    vehicles: Vec<VehicleState>,
+    indices: HashMap<VehicleId, usize>,
+    occupancy: Option<Occupancy>,
+}
+
+impl<'a, R: Copy, T: CoordNum> Simulation<'a, R, T> {
+    /// Creates an empty simulation at time zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the vehicles move on.
+    /// * `options` - The seed and the random variations. Variations outside `[0, 1)` are clamped to it, so that
+    ///   varied speeds and dwell times stay positive.
+    ///
+    /// # Returns
+    ///
+    /// * `Simulation<R, T>` - The simulation.
+    pub fn new(network: &'a TransitNetwork<R, T>, options: SimulationOptions) -> Self {
+        let options = SimulationOptions {
+            speed_variation: clamp_variation(options.speed_variation),
+            dwell_variation: clamp_variation(options.dwell_variation),
+            ..options
+        };
+        Simulation {
+            network,
+            options,
+            rng: Rng::new(options.seed),
+            now: 0.0,
+            sequence: 0,
+            queue: BinaryHeap::new(),
+            vehicles: Vec::new(),
+            indices: HashMap::new(),
+            occupancy: None,
+        }
+    }
+
+    /// Tracks the occupancy of block sections and switches and records conflicts between vehicles.
+    ///
+    /// A vehicle occupies the block of an edge and the switch at its end when its head enters the edge,
+    /// and reserves the next block on its route if no other vehicle holds it. Both are released once its tail,
+    /// given by the length of its `dynamics`, has cleared them, checked whenever its head leaves an edge.
+    /// Switches are the nodes with more than two incident edges. A vehicle entering a resource that is occupied
+    /// or reserved by another vehicle causes a `Conflict`. Conflicts are reported, not prevented.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The block sections of the network.
+    ///
+    /// # Returns
+    ///
+    /// * `Simulation<R, T>` - The simulation tracking occupancy.
+    pub fn with_blocks(mut self, layout: BlockLayout) -> Self {
+        self.occupancy = Some(Occupancy::new(self.network, layout));
+        self
+    }
+
+    /// Returns the conflicts between vehicles found so far, in the order of their time.
+    pub fn conflicts(&self) -> &[Conflict] {
+        self.occupancy
+            .as_ref()
+            .map_or(&[], |occupancy| occupancy.conflicts.as_slice())
+    }
+
+    /// Returns the IDs of the vehicles currently occupying a resource, in the order they entered it.
+    pub fn occupants(&self, resource: Resource) -> &[VehicleId] {
+        self.occupancy
+            .as_ref()
+            .map_or(&[], |occupancy| occupancy.occupants(resource))
+    }
+
+    /// Returns the ID of the vehicle currently reserving a block ahead of it.
+    pub fn reservation(&self, block_id: BlockId) -> Option<VehicleId> {
+        self.occupancy.as_ref()?.reservation(block_id)
+    }
+
+    /// Returns the current simulation time in seconds.
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    /// Adds a vehicle and schedules its departure.
+    ///
+    /// # Arguments
+    ///
+    /// * `vehicle` - The vehicle to add.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` if the vehicle was not added, because its ID is already in use, its speed is not positive
+    ///   and finite before or after the variation, its departure is not a number or lies in the past, or its route
+    ///   does not match the edges of the network or cannot be traversed through them in the topology graph.
+    pub fn add_vehicle(&mut self, vehicle: Vehicle) -> bool {
+        let route = &vehicle.route;
+        let valid_route = route.nodes.len() == route.edges.len() + 1
+            && route
+                .edges
+                .iter()
+                .zip(route.nodes.windows(2))
+                .all(|(id, pair)| {
+                    self.network.get_edge_by_id(*id).is_some_and(|edge| {
+                        (edge.source, edge.target) == (pair[0], pair[1])
+                            || (edge.source, edge.target) == (pair[1], pair[0])
+                    })
+                })
+            && self
+                .network
+                .is_traversable_along(&route.nodes, &route.edges);
+        let valid_speed = |speed: f64| speed > 0.0 && speed.is_finite();
+        if !valid_route
+            || route.edges.is_empty()
+            || !valid_speed(vehicle.speed)
+            || vehicle.departure.is_nan()
+            || vehicle.departure < self.now
+            || self.indices.contains_key(&vehicle.id)
+        {
+            return false;
+        }
+        let speed = vehicle.speed * self.rng.variation(self.options.speed_variation);
+        if !valid_speed(speed) {
+            return false;
+        }
+
+        let mut starts = vec![0.0];
+        for edge_id in &route.edges {
+            let length = self
+                .network
+                .get_edge_by_id(*edge_id)
+                .and_then(|edge| edge.length.to_f64())
+                .unwrap_or(0.0);
+            starts.push(starts[starts.len() - 1] + length);
+        }
+        let index = self.vehicles.len();
+        let kind = EventKind::Depart {
+            node_id: route.origin(),
+        };
+        self.schedule(vehicle.departure, index, kind);
+        self.indices.insert(vehicle.id, index);
+        self.vehicles.push(VehicleState {
+            vehicle,
+            speed,
+            starts,
+            leg: 0,
+            phase: Phase::Waiting,
+            run: None,
+        });
+        true
+    }
+
+    /// Processes the next event.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Event>` - The processed event, or `None` if no events are left.
+    pub fn step(&mut self) -> Option<Event> {
+        let scheduled = self.queue.pop()?;
+        self.now = scheduled.time;
+        let time = scheduled.time;
+        let index = scheduled.vehicle;
+
+        match scheduled.kind {
+            EventKind::Depart { .. } => {
+                self.vehicles[index].run = self.plan_run(&self.vehicles[index], time);
+                let state = &self.vehicles[index];
+                let edge_id = state.vehicle.route.edges[state.leg];
+                self.schedule(time, index, EventKind::EnterEdge { edge_id });
+            }
+            EventKind::EnterEdge { edge_id } => {
+                let duration = self
+                    .network
+                    .get_edge_by_id(edge_id)
+                    .map_or(0.0, |edge| self.traversal_time(&self.vehicles[index], edge));
+                self.vehicles[index].phase = Phase::Moving {
+                    entered: time,
+                    leave: time + duration,
+                };
+                self.schedule(time + duration, index, EventKind::LeaveEdge { edge_id });
+                self.claim(index, time);
+            }
+            EventKind::LeaveEdge { .. } => {
+                let state = &mut self.vehicles[index];
+                state.leg += 1;
+                state.phase = Phase::Stopped;
+                if let Some(occupancy) = &mut self.occupancy {
+                    if state.leg == state.vehicle.route.edges.len() {
+                        occupancy.release_all(state.vehicle.id);
+                    } else {
+                        occupancy.release(state.vehicle.id, state.starts[state.leg]);
+                    }
+                }
+                let route = &state.vehicle.route;
+                let node_id = route.nodes[state.leg];
+                let kind = if state.leg == route.edges.len()
+                    || state.vehicle.stops.contains_key(&node_id)
+                {
+                    EventKind::Arrive { node_id }
+                } else {
+                    EventKind::EnterEdge {
+                        edge_id: route.edges[state.leg],
+                    }
+                };
+                self.schedule(time, index, kind);
+            }
+            EventKind::Arrive { node_id } => {
+                let state = &self.vehicles[index];
+                if state.leg < state.vehicle.route.edges.len() {
+                    let dwell = state.vehicle.stops.get(&node_id).copied().unwrap_or(0.0);
+                    let dwell = dwell * self.rng.variation(self.options.dwell_variation);
+                    self.schedule(time + dwell.max(0.0), index, EventKind::Depart { node_id });
+                }
+            }
+        }
+
+        Some(Event {
+            time,
+            vehicle_id: self.vehicles[index].vehicle.id,
+            kind: scheduled.kind,
+        })
+    }
+
+    /// Processes all events up to and including the given time and advances the clock to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time in seconds to run to.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Event>` - The processed events in order.
+    pub fn run_until(&mut self, time: f64) -> Vec<Event> {
+        let mut events = Vec::new();
+        while self.queue.peek().is_some_and(|next| next.time <= time) {
+            events.extend(self.step());
+        }
+        self.now = self.now.max(time);
+        events
+    }
+
+    /// Processes all remaining events.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Event>` - The processed events in order.
+    pub fn run(&mut self) -> Vec<Event> {
+        std::iter::from_fn(|| self.step()).collect()
+    }
+
+    /// Returns the position of a vehicle at the current time.
+    ///
+    /// Moving vehicles are interpolated along their edge at constant speed, the offset is measured
+    /// from the first coordinate of the edge path in the units of the edge length.
+    /// Vehicles stopped at a node are placed at the end of the edge they arrived on.
+    ///
+    /// # Arguments
+    ///
+    /// * `vehicle_id` - The ID of the vehicle.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<NetworkPosition<f64>>` - The position, or `None` if the vehicle is unknown or has not departed yet.
+    pub fn position(&self, vehicle_id: VehicleId) -> Option<NetworkPosition<f64>> {
+        let state = &self.vehicles[*self.indices.get(&vehicle_id)?];
+        let route = &state.vehicle.route;
+        let (leg, fraction) = match state.phase {
+            Phase::Waiting => return None,
+            Phase::Moving { entered, leave } if leave > entered => {
+                let fraction = match &state.run {
+                    Some(run) => {
+                        let start = run.starts[state.leg - run.first_leg];
+                        let end = run.starts[state.leg - run.first_leg + 1];
+                        (run.profile.distance_at(self.now - run.departed) - start) / (end - start)
+                    }
+                    None => (self.now - entered) / (leave - entered),
+                };
+                (state.leg, fraction.clamp(0.0, 1.0))
+            }
+            Phase::Moving { .. } => (state.leg, 1.0),
+            Phase::Stopped => (state.leg - 1, 1.0),
+        };
+        let edge = self.network.get_edge_by_id(route.edges[leg])?;
+        let length = edge.length.to_f64()?;
+        let travelled = fraction * length;
+        let offset = if edge.source == route.nodes[leg] {
+            travelled
+        } else {
+            length - travelled
+        };
+        Some(NetworkPosition {
+            edge_id: edge.id,
+            offset,
+        })
+    }
+
+    /// Returns the time a vehicle needs to traverse an edge.
+    fn traversal_time(&self, state: &VehicleState, edge: &TransitEdge<T>) -> f64 {
+        match &state.run {
+            Some(run) => {
+                let leg = state.leg - run.first_leg;
+                run.profile.time_at(run.starts[leg + 1]) - run.profile.time_at(run.starts[leg])
+            }
+            None => edge.length.to_f64().unwrap_or(0.0) / state.speed,
+        }
+    }
+
+    /// Occupies the block and switch ahead of a vehicle entering an edge and reserves the following block.
+    fn claim(&mut self, index: usize, time: f64) {
+        let (Some(occupancy), Some(position)) = (
+            &self.occupancy,
+            self.position(self.vehicles[index].vehicle.id),
+        ) else {
+            return;
+        };
+        let state = &self.vehicles[index];
+        let route = &state.vehicle.route;
+        let leg = state.leg;
+        let block = occupancy.layout.block_of(route.edges[leg]);
+        let mut resources: Vec<Resource> = block.map(Resource::Block).into_iter().collect();
+        let node_id = route.nodes[leg + 1];
+        if occupancy.switches.contains(&node_id) {
+            resources.push(Resource::Switch(node_id));
+        }
+        let ahead = route.edges[leg + 1..]
+            .iter()
+            .filter_map(|edge_id| occupancy.layout.block_of(*edge_id))
+            .find(|ahead| Some(*ahead) != block);
+        let length = state
+            .vehicle
+            .dynamics
+            .map_or(0.0, |dynamics| dynamics.length);
+        let clear_at = state.starts[leg + 1] + length;
+        let vehicle_id = state.vehicle.id;
+
+        let Some(occupancy) = &mut self.occupancy else {
+            return;
+        };
+        for resource in resources {
+            occupancy.occupy(time, vehicle_id, resource, clear_at, position);
+        }
+        if let Some(ahead) = ahead {
+            occupancy.reserve(vehicle_id, ahead);
+        }
+    }
+
+    /// Computes the speed profile of a vehicle with dynamics from its current node to its next stop.
+    fn plan_run(&self, state: &VehicleState, departed: f64) -> Option<Run> {
+        let dynamics = state.vehicle.dynamics?;
+        let dynamics = TrainDynamics {
+            max_speed: state.speed.min(dynamics.max_speed),
+            ..dynamics
+        };
+        let route = &state.vehicle.route;
+        let last_leg = (state.leg..route.edges.len())
+            .find(|leg| state.vehicle.stops.contains_key(&route.nodes[leg + 1]))
+            .unwrap_or(route.edges.len() - 1);
+        let edges = &route.edges[state.leg..=last_leg];
+        let profile = dynamics.profile_for_edges(self.network, edges)?;
+        let mut starts = vec![0.0];
+        for edge_id in edges {
+            let length = self.network.get_edge_by_id(*edge_id)?.length.to_f64()?;
+            starts.push(starts[starts.len() - 1] + length);
+        }
+        Some(Run {
+            departed,
+            first_leg: state.leg,
+            starts,
+            profile,
+        })
+    }
+
+    fn schedule(&mut self, time: f64, vehicle: usize, kind: EventKind) {
+        self.queue.push(Scheduled {
+            time,
+            sequence: self.sequence,
+            vehicle,
+            kind,
+        });
+        self.sequence += 1;
+    }
+}
+
+impl<'a, R: Copy, T: CoordFloat + Sum> Simulation<'a, R, T> {
+    /// Returns the coordinate of a vehicle at the current time on the path of its edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `vehicle_id` - The ID of the vehicle.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Coord<T>>` - The coordinate, or `None` if the vehicle has no `position`.
+    pub fn location(&self, vehicle_id: VehicleId) -> Option<Coord<T>> {
+        let position = self.position(vehicle_id)?;
+        let edge = self.network.get_edge_by_id(position.edge_id)?;
+        let length = edge.length.to_f64()?;
+        let fraction = if length > 0.0 {
+            position.offset / length
+        } else {
+            0.0
+        };
+        let (first, _) = split_path(&edge.path, T::from(fraction)?);
+        first.0.last().copied()
+    }
+}
+
+/// Limits a relative variation to `[0, 1)`, treating a variation that is not a number as none.
+fn clamp_variation(variation: f64) -> f64 {
+    if variation.is_nan() {
+        0.0
+    } else {
+        variation.clamp(0.0, 1.0 - f64::EPSILON)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Accessability, EdgeId, TransitNode},
+        operations::TransitNetworkModifier,
+        simulation::fixtures,
+    };
+    use geo::{coord, LineString};
+
+    fn create_network() -> TransitNetwork<Coord, f64> {
+        let mut network = TransitNetwork::new();
+        for id in 0..4 {
+            network.add_node(TransitNode {
+                id,
+                location: coord! { x: id as f64 * 100.0, y: 0.0 },
+            });
+        }
+        let edge = |id, source: NodeId, target: NodeId| TransitEdge {
+            id,
+            source,
+            target,
+            length: 100.0,
+            path: LineString(vec![
+                coord! { x: source as f64 * 100.0, y: 0.0 },
+                coord! { x: target as f64 * 100.0, y: 0.0 },
+            ]),
+        };
+        network.add_edge(edge(1, 0, 1));
+        network.add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0]));
+        network.add_edge_with_accessibility(edge(3, 2, 3), Accessability::ReachableNodes(vec![1]));
+        network
+    }
+
+    fn vehicle(network: &TransitNetwork<Coord, f64>, id: VehicleId, departure: f64) -> Vehicle {
+        Vehicle {
+            id,
+            route: Route::shortest(network, 0, 3).unwrap(),
+            departure,
+            speed: 10.0,
+            stops: HashMap::from([(2, 30.0)]),
+            dynamics: None,
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let network = create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        assert!(simulation.add_vehicle(vehicle(&network, 7, 5.0)));
+
+        let events: Vec<(f64, EventKind)> = simulation
+            .run()
+            .into_iter()
+            .map(|event| {
+                assert_eq!(event.vehicle_id, 7);
+                (event.time, event.kind)
+            })
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                (5.0, EventKind::Depart { node_id: 0 }),
+                (5.0, EventKind::EnterEdge { edge_id: 1 }),
+                (15.0, EventKind::LeaveEdge { edge_id: 1 }),
+                (15.0, EventKind::EnterEdge { edge_id: 2 }),
+                (25.0, EventKind::LeaveEdge { edge_id: 2 }),
+                (25.0, EventKind::Arrive { node_id: 2 }),
+                (55.0, EventKind::Depart { node_id: 2 }),
+                (55.0, EventKind::EnterEdge { edge_id: 3 }),
+                (65.0, EventKind::LeaveEdge { edge_id: 3 }),
+                (65.0, EventKind::Arrive { node_id: 3 }),
+            ]
+        );
+        assert!(simulation.step().is_none());
+    }
+
+    #[test]
+    fn test_position() {
+        let network = create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        simulation.add_vehicle(vehicle(&network, 1, 0.0));
+
+        assert_eq!(simulation.position(1), None);
+        simulation.run_until(2.5);
+        assert_eq!(
+            simulation.position(1),
+            Some(NetworkPosition {
+                edge_id: 1,
+                offset: 25.0
+            })
+        );
+        assert_eq!(simulation.location(1), Some(coord! { x: 25.0, y: 0.0 }));
+
+        simulation.run_until(12.5);
+        assert_eq!(
+            simulation.position(1),
+            Some(NetworkPosition {
+                edge_id: 2,
+                offset: 25.0
+            })
+        );
+        assert_eq!(simulation.location(1), Some(coord! { x: 125.0, y: 0.0 }));
+
+        simulation.run_until(40.0);
+        assert_eq!(simulation.location(1), Some(coord! { x: 200.0, y: 0.0 }));
+        assert_eq!(simulation.position(2), None);
+    }
+
+    #[test]
+    fn test_position_against_path_direction() {
+        let network = create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        simulation.add_vehicle(Vehicle {
+            route: Route::shortest(&network, 3, 0).unwrap(),
+            stops: HashMap::new(),
+            ..vehicle(&network, 1, 0.0)
+        });
+
+        simulation.run_until(12.5);
+
+        assert_eq!(
+            simulation.position(1),
+            Some(NetworkPosition {
+                edge_id: 2,
+                offset: 75.0
+            })
+        );
+        assert_eq!(simulation.location(1), Some(coord! { x: 175.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn test_dynamics() {
+        let mut network = create_network();
+        network.set_speed_limit(2, 5.0);
+        let dynamics = TrainDynamics {
+            max_speed: 20.0,
+            acceleration: 1.0,
+            deceleration: 1.0,
+            length: 10.0,
+            mass: 100.0,
+        };
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        simulation.add_vehicle(Vehicle {
+            dynamics: Some(dynamics),
+            ..vehicle(&network, 1, 0.0)
+        });
+
+        let events = simulation.run();
+
+        let leave = |edge_id| {
+            events
+                .iter()
+                .find(|event| event.kind == EventKind::LeaveEdge { edge_id })
+                .unwrap()
+                .time
+        };
+        // The speed of the vehicle caps the maximum speed of its dynamics.
+        let capped = TrainDynamics {
+            max_speed: 10.0,
+            ..dynamics
+        };
+        let first_run = capped
+            .speed_profile(
+                &network,
+                &Route {
+                    nodes: vec![0, 1, 2],
+                    edges: vec![1, 2],
+                },
+            )
+            .unwrap();
+        assert!((leave(2) - first_run.running_time()).abs() < 1e-9);
+        assert!((leave(1) - first_run.time_at(100.0)).abs() < 1e-9);
+        // Accelerating from the stop at node 2 to 10 and braking again takes 20 s.
+        assert!((leave(3) - leave(2) - 30.0 - 20.0).abs() < 1e-9);
+
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        simulation.add_vehicle(Vehicle {
+            dynamics: Some(dynamics),
+            ..vehicle(&network, 1, 0.0)
+        });
+        simulation.run_until(5.0);
+        let position = simulation.position(1).unwrap();
+        assert_eq!(position.edge_id, 1);
+        assert!((position.offset - 12.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_deterministic_for_seed() {
+        let network = create_network();
+        let options = SimulationOptions {
+            seed: 42,
+            speed_variation: 0.2,
+            dwell_variation: 0.5,
+        };
+        let run = |options: SimulationOptions| {
+            let mut simulation = Simulation::new(&network, options);
+            for id in 0..5 {
+                simulation.add_vehicle(vehicle(&network, id, id as f64 * 10.0));
+            }
+            simulation.run()
+        };
+
+        let events = run(options);
+
+        assert_eq!(events.len(), 50);
+        assert!(events.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert_eq!(events, run(options));
+        assert_ne!(
+            events,
+            run(SimulationOptions {
+                seed: 43,
+                ..options
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_vehicle_rejects_invalid() {
+        let network = create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+
+        assert!(simulation.add_vehicle(vehicle(&network, 1, 0.0)));
+        assert!(!simulation.add_vehicle(vehicle(&network, 1, 0.0)));
+        assert!(!simulation.add_vehicle(Vehicle {
+            speed: 0.0,
+            ..vehicle(&network, 2, 0.0)
+        }));
+        assert!(!simulation.add_vehicle(Vehicle {
+            route: Route {
+                nodes: vec![0, 2],
+                edges: vec![1],
+            },
+            ..vehicle(&network, 3, 0.0)
+        }));
+        assert!(!simulation.add_vehicle(Vehicle {
+            route: Route {
+                nodes: vec![0, 1, 0],
+                edges: vec![1, 1],
+            },
+            ..vehicle(&network, 7, 0.0)
+        }));
+        assert!(!simulation.add_vehicle(Vehicle {
+            speed: f64::NAN,
+            ..vehicle(&network, 5, 0.0)
+        }));
+        assert!(!simulation.add_vehicle(vehicle(&network, 6, f64::NAN)));
+        simulation.run_until(10.0);
+        assert!(!simulation.add_vehicle(vehicle(&network, 4, 5.0)));
+    }
+
+    #[test]
+    fn test_add_vehicle_rejects_untraversable_route() {
+        let network = fixtures::create_network();
+        let mut simulation = Simulation::new(&network, SimulationOptions::default());
+        let route = |nodes: Vec<NodeId>, edges: Vec<EdgeId>| Vehicle {
+            route: Route { nodes, edges },
+            ..vehicle(&network, 1, 0.0)
+        };
+
+        assert!(!simulation.add_vehicle(route(vec![2, 1, 3], vec![2, 3])));
+        assert!(simulation.add_vehicle(route(vec![0, 1, 3], vec![1, 3])));
+    }
+
+    #[test]
+    fn test_variations_are_clamped() {
+        let network = create_network();
+        let options = SimulationOptions {
+            seed: 7,
+            speed_variation: 3.0,
+            dwell_variation: -1.0,
+        };
+        let mut simulation = Simulation::new(&network, options);
+        for id in 0..20 {
+            assert!(simulation.add_vehicle(vehicle(&network, id, 0.0)));
+        }
+
+        let events = simulation.run();
+
+        assert_eq!(events.len(), 200);
+        assert!(events
+            .iter()
+            .all(|event| event.time.is_finite() && event.time >= 0.0));
+        assert!(events.windows(2).all(|pair| pair[0].time <= pair[1].time));
+    }
+}
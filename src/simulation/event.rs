@@ -0,0 +1,39 @@
+use crate::core::{EdgeId, NodeId};
+
+use super::VehicleId;
+
+/// Enum `EventKind` defining what happened to a vehicle in an `Event`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EventKind {
+    /// The vehicle leaves a node it stopped at.
+    Depart {
+        /// The ID of the node.
+        node_id: NodeId,
+    },
+    /// The vehicle starts moving along an edge.
+    EnterEdge {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The vehicle reaches the end of an edge.
+    LeaveEdge {
+        /// The ID of the edge.
+        edge_id: EdgeId,
+    },
+    /// The vehicle stops at a node.
+    Arrive {
+        /// The ID of the node.
+        node_id: NodeId,
+    },
+}
+
+/// Structure representing something that happened to a vehicle at a point in time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Event {
+    /// The simulation time in seconds.
+    pub time: f64,
+    /// The ID of the vehicle.
+    pub vehicle_id: VehicleId,
+    /// What happened.
+    pub kind: EventKind,
+}
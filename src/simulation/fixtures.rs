@@ -0,0 +1,86 @@
+//! Networks and services shared by the tests of the simulation and the timetable.
+
+use geo::{coord, Coord, LineString};
+
+use crate::{
+    core::{Accessability, NodeId, Service, ServiceId, Stop, TransitEdge, TransitNode},
+    graphs::TransitNetwork,
+    operations::TransitNetworkModifier,
+};
+
+/// A line 0 - 1 - 2 with a branch from node 1 to node 3, all edges 100 long.
+/// Edge 1 joins nodes 0 and 1, edge 2 nodes 1 and 2 and edge 3 nodes 1 and 3.
+pub(crate) fn create_network() -> TransitNetwork<Coord, f64> {
+    let mut network = TransitNetwork::new();
+    let locations = [(0.0, 0.0), (100.0, 0.0), (200.0, 0.0), (200.0, 100.0)];
+    for (id, (x, y)) in locations.iter().enumerate() {
+        network.add_node(TransitNode {
+            id: id as NodeId,
+            location: coord! { x: *x, y: *y },
+        });
+    }
+    let edge = |id, source: usize, target: usize| TransitEdge {
+        id,
+        source: source as NodeId,
+        target: target as NodeId,
+        length: 100.0,
+        path: LineString(vec![locations[source].into(), locations[target].into()]),
+    };
+    network.add_edge(edge(1, 0, 1));
+    network.add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0]));
+    network.add_edge_with_accessibility(edge(3, 1, 3), Accessability::ReachableNodes(vec![0]));
+    network
+}
+
+/// Two nodes 0 and 1, 1000 apart and joined by the parallel edges 1 and 2 of a double track.
+pub(crate) fn create_double_track() -> TransitNetwork<Coord, f64> {
+    let mut network = TransitNetwork::new();
+    for (id, x) in [(0, 0.0), (1, 1000.0)] {
+        network.add_node(TransitNode {
+            id,
+            location: coord! { x: x, y: 0.0 },
+        });
+    }
+    for (id, y) in [(1, 0.0), (2, 5.0)] {
+        network.add_edge(TransitEdge {
+            id,
+            source: 0,
+            target: 1,
+            length: 1000.0,
+            path: LineString::from(vec![(0.0, 0.0), (500.0, y), (1000.0, 0.0)]),
+        });
+    }
+    network
+}
+
+/// A service departing from `from` at `departure` and arriving at `to` after `running_time`.
+pub(crate) fn service(
+    id: ServiceId,
+    from: NodeId,
+    to: NodeId,
+    departure: f64,
+    running_time: f64,
+) -> Service {
+    service_with_stops(
+        id,
+        &[
+            (from, departure, departure),
+            (to, departure + running_time, departure + running_time),
+        ],
+    )
+}
+
+/// A service calling at the given nodes with their arrival and departure times.
+pub(crate) fn service_with_stops(id: ServiceId, stops: &[(NodeId, f64, f64)]) -> Service {
+    Service::new(
+        id,
+        stops
+            .iter()
+            .map(|(node_id, arrival, departure)| Stop {
+                node_id: *node_id,
+                arrival: *arrival,
+                departure: *departure,
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,488 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use geo::CoordNum;
+
+use crate::{
+    core::{NodeId, Service, ServiceId, Timetable},
+    graphs::TransitNetwork,
+};
+
+use super::blocks::{switch_nodes, BlockLayout, Resource};
+
+/// Options for `HeadwayAnalyser`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadwayOptions {
+    /// The time in seconds a resource is blocked before the head of a train reaches it,
+    /// covering route setting, signal clearing and the approach from the previous signal.
+    pub setup_time: f64,
+    /// The time in seconds a resource stays blocked after the tail of a train has cleared it.
+    pub release_time: f64,
+    /// The minimum headway in seconds between the release of a resource by one run and its blocking by the next.
+    pub min_headway: f64,
+    /// The length of the trains, in units of the edge length.
+    pub train_length: f64,
+}
+
+impl Default for HeadwayOptions {
+    fn default() -> Self {
+        HeadwayOptions {
+            setup_time: 0.0,
+            release_time: 0.0,
+            min_headway: 0.0,
+            train_length: 0.0,
+        }
+    }
+}
+
+/// The interval in which a run blocks a resource for all other runs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlockingTime {
+    /// The blocked resource.
+    pub resource: Resource,
+    /// The time in seconds the blocking starts.
+    pub start: f64,
+    /// The time in seconds the resource is released, including the minimum headway.
+    pub end: f64,
+}
+
+/// The blocking times of a run in the order of its path, which plotted over distance form a stairway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stairway {
+    /// The ID of the service.
+    pub service_id: ServiceId,
+    /// The blocking times along the path of the service.
+    pub blocking_times: Vec<BlockingTime>,
+}
+
+impl Stairway {
+    /// Returns the time by which a following run has to be shifted so that it blocks each shared resource
+    /// only after this run has released it.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The stairway of the following run.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The shift in seconds, negative if the following run could start earlier,
+    ///   or `None` if the runs share no resource.
+    pub fn separation(&self, next: &Stairway) -> Option<f64> {
+        let mut separation: Option<f64> = None;
+        for blocking in &self.blocking_times {
+            for following in &next.blocking_times {
+                if following.resource == blocking.resource {
+                    let shift = blocking.end - following.start;
+                    separation = Some(separation.map_or(shift, |separation| separation.max(shift)));
+                }
+            }
+        }
+        separation
+    }
+}
+
+/// Structure representing two runs blocking the same resource at the same time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HeadwayConflict {
+    /// The contested resource.
+    pub resource: Resource,
+    /// The ID of the service blocking the resource first.
+    pub service_id: ServiceId,
+    /// The ID of the service blocking the resource before the first has released it.
+    pub other_service_id: ServiceId,
+    /// The time in seconds the overlap of the blocking times starts.
+    pub start: f64,
+    /// The time in seconds the overlap of the blocking times ends.
+    pub end: f64,
+}
+
+impl fmt::Display for HeadwayConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let resource = match self.resource {
+            Resource::Block(block_id) => format!("block {}", block_id),
+            Resource::Switch(node_id) => format!("switch {}", node_id),
+        };
+        write!(
+            f,
+            "service {} blocks {} from {} s while service {} holds it until {} s",
+            self.other_service_id, resource, self.start, self.service_id, self.end
+        )
+    }
+}
+
+/// Checks the runs of a timetable for blocking times overlapping on block sections and switches.
+///
+/// The runs follow the bound paths of their services, unbound services are bound to the shortest path.
+/// Between two stops a run moves at the constant speed its scheduled running time gives.
+/// A block is blocked from `HeadwayOptions::setup_time` before the head of the train enters it until
+/// `HeadwayOptions::release_time` and `HeadwayOptions::min_headway` after its tail has left it.
+/// Switches, nodes joining more than two edges, are blocked in the same way while the train passes them.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::prelude::*;
+/// use transit_grid::simulation::{BlockLayout, HeadwayAnalyser, HeadwayOptions, Resource};
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// network.add_node(TransitNode { id: 1, location: coord! { x: 0.0, y: 0.0 } });
+/// network.add_node(TransitNode { id: 2, location: coord! { x: 1000.0, y: 0.0 } });
+/// network.add_edge(TransitEdge {
+///     id: 1,
+///     source: 1,
+///     target: 2,
+///     length: 1000.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1000.0, y: 0.0 }]),
+/// });
+///
+/// let stop = |node_id, time| Stop { node_id, arrival: time, departure: time };
+/// let mut timetable = Timetable::new();
+/// timetable.add_service(Service::new(1, vec![stop(1, 0.0), stop(2, 100.0)]));
+/// timetable.add_service(Service::new(2, vec![stop(1, 60.0), stop(2, 160.0)]));
+///
+/// let options = HeadwayOptions { min_headway: 30.0, ..Default::default() };
+/// let analyser = HeadwayAnalyser::new(&network, BlockLayout::per_edge(&network), options);
+/// let conflicts = analyser.conflicts(&timetable);
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].resource, Resource::Block(1));
+/// assert_eq!((conflicts[0].start, conflicts[0].end), (60.0, 130.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeadwayAnalyser<'a, R: Copy, T: CoordNum> {
+    pub(super) network: &'a TransitNetwork<R, T>,
+    pub(super) layout: BlockLayout,
+    pub(super) switches: HashSet<NodeId>,
+    pub(super) options: HeadwayOptions,
+}
+
+impl<'a, R: Copy, T: CoordNum> HeadwayAnalyser<'a, R, T> {
+    /// Creates an analyser for the block sections of a network.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the services run on.
+    /// * `layout` - The block sections of the network, edges without a block are not checked.
+    /// * `options` - The times and train length of the analysis.
+    pub fn new(
+        network: &'a TransitNetwork<R, T>,
+        layout: BlockLayout,
+        options: HeadwayOptions,
+    ) -> Self {
+        HeadwayAnalyser {
+            network,
+            layout,
+            switches: switch_nodes(network),
+            options,
+        }
+    }
+
+    /// Computes the blocking times of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - The service to analyse.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Stairway>` - The blocking times of the run, or `None` if the service has fewer than two stops,
+    ///   cannot be bound to a path or one of its paths does not connect its stops along its bound edges.
+    pub fn stairway(&self, service: &Service) -> Option<Stairway> {
+        let mut service = service.clone();
+        if !service.is_bound() && !self.network.bind_service(&mut service) {
+            return None;
+        }
+        if service.stops.len() < 2 {
+            return None;
+        }
+
+        // The head of the train over time, as (distance, time) points along the path.
+        // Between two stops the head moves at constant speed, so the points at the stops suffice.
+        let mut points = vec![(0.0, service.stops[0].departure)];
+        // The resources along the path, as (resource, start distance, end distance).
+        let mut resources: Vec<(Resource, f64, f64)> = Vec::new();
+        let mut distance = 0.0;
+        for (index, path) in service.paths.iter().enumerate() {
+            let (from, to) = (&service.stops[index], &service.stops[index + 1]);
+            let edges = &service.edges[index];
+            if path.first() != Some(&from.node_id)
+                || path.last() != Some(&to.node_id)
+                || path.len() != edges.len() + 1
+            {
+                return None;
+            }
+            for (pair, edge_id) in path.windows(2).zip(edges) {
+                let edge = self
+                    .network
+                    .physical_graph
+                    .get_transit_edge_by_id(*edge_id)?;
+                let length = edge.length.to_f64().unwrap_or(f64::INFINITY);
+                if self.switches.contains(&pair[0]) {
+                    resources.push((Resource::Switch(pair[0]), distance, distance));
+                }
+                if let Some(block_id) = self.layout.block_of(edge.id) {
+                    // Consecutive edges of the same block extend its blocking, even across a switch.
+                    let last = resources
+                        .iter_mut()
+                        .rev()
+                        .find(|(resource, _, _)| matches!(resource, Resource::Block(_)));
+                    match last {
+                        Some((Resource::Block(last), _, end))
+                            if *last == block_id && *end == distance =>
+                        {
+                            *end = distance + length;
+                        }
+                        _ => {
+                            resources.push((Resource::Block(block_id), distance, distance + length))
+                        }
+                    }
+                }
+                distance += length;
+            }
+            points.push((distance, to.arrival));
+            if index + 2 < service.stops.len() {
+                points.push((distance, to.departure));
+            }
+        }
+        if let Some(last) = service.paths.last().and_then(|path| path.last()) {
+            if self.switches.contains(last) {
+                resources.push((Resource::Switch(*last), distance, distance));
+            }
+        }
+
+        let blocking_times = resources
+            .into_iter()
+            .map(|(resource, start, end)| {
+                let entry = match resource {
+                    Resource::Block(_) => last_time(&points, start),
+                    Resource::Switch(_) => first_time(&points, start),
+                };
+                BlockingTime {
+                    resource,
+                    start: entry - self.options.setup_time,
+                    end: first_time(&points, end + self.options.train_length)
+                        + self.options.release_time
+                        + self.options.min_headway,
+                }
+            })
+            .collect();
+        Some(Stairway {
+            service_id: service.id,
+            blocking_times,
+        })
+    }
+
+    /// Computes the blocking times of all runs of a timetable.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Stairway>` - The stairways in the order of the services, skipping services without a stairway.
+    pub fn stairways(&self, timetable: &Timetable) -> Vec<Stairway> {
+        timetable
+            .services
+            .iter()
+            .filter_map(|service| self.stairway(service))
+            .collect()
+    }
+
+    /// Finds all pairs of runs blocking the same resource at the same time.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<HeadwayConflict>` - The conflicts, sorted by the start of the overlap.
+    pub fn conflicts(&self, timetable: &Timetable) -> Vec<HeadwayConflict> {
+        let mut occupations: BTreeMap<Resource, Vec<(ServiceId, BlockingTime)>> = BTreeMap::new();
+        for stairway in self.stairways(timetable) {
+            for blocking in stairway.blocking_times {
+                occupations
+                    .entry(blocking.resource)
+                    .or_default()
+                    .push((stairway.service_id, blocking));
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (resource, mut occupations) in occupations {
+            occupations.sort_by(|a, b| a.1.start.total_cmp(&b.1.start));
+            for (index, (service_id, blocking)) in occupations.iter().enumerate() {
+                for (other_service_id, other) in &occupations[index + 1..] {
+                    if other.start >= blocking.end {
+                        break;
+                    }
+                    if other_service_id != service_id {
+                        conflicts.push(HeadwayConflict {
+                            resource,
+                            service_id: *service_id,
+                            other_service_id: *other_service_id,
+                            start: other.start,
+                            end: blocking.end.min(other.end),
+                        });
+                    }
+                }
+            }
+        }
+        conflicts.sort_by(|a, b| a.start.total_cmp(&b.start));
+        conflicts
+    }
+}
+
+/// Returns the first time the head of the train reaches a distance, or the last time if it never does.
+fn first_time(points: &[(f64, f64)], distance: f64) -> f64 {
+    match points.iter().position(|point| point.0 >= distance) {
+        Some(0) => points[0].1,
+        Some(index) => interpolate(points[index - 1], points[index], distance),
+        None => points[points.len() - 1].1,
+    }
+}
+
+/// Returns the last time the head of the train is at a distance.
+fn last_time(points: &[(f64, f64)], distance: f64) -> f64 {
+    match points.iter().rposition(|point| point.0 <= distance) {
+        Some(index) if index + 1 < points.len() => {
+            interpolate(points[index], points[index + 1], distance)
+        }
+        Some(index) => points[index].1,
+        None => points[0].1,
+    }
+}
+
+fn interpolate(from: (f64, f64), to: (f64, f64), distance: f64) -> f64 {
+    if to.0 <= from.0 {
+        return to.1;
+    }
+    from.1 + (to.1 - from.1) * (distance - from.0) / (to.0 - from.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::fixtures::{create_double_track, create_network, service_with_stops};
+
+    fn create_timetable() -> Timetable {
+        let mut timetable = Timetable::new();
+        timetable.add_service(service_with_stops(1, &[(0, 0.0, 0.0), (2, 20.0, 20.0)]));
+        timetable.add_service(service_with_stops(2, &[(0, 10.0, 10.0), (3, 30.0, 30.0)]));
+        timetable
+    }
+
+    #[test]
+    fn test_stairway() {
+        let network = create_network();
+        let options = HeadwayOptions {
+            setup_time: 2.0,
+            train_length: 50.0,
+            ..Default::default()
+        };
+        let analyser = HeadwayAnalyser::new(&network, BlockLayout::per_edge(&network), options);
+
+        let stairway = analyser
+            .stairway(&service_with_stops(
+                1,
+                &[(0, 0.0, 0.0), (1, 10.0, 30.0), (2, 40.0, 40.0)],
+            ))
+            .unwrap();
+
+        assert_eq!(stairway.service_id, 1);
+        assert_eq!(
+            stairway.blocking_times,
+            vec![
+                BlockingTime {
+                    resource: Resource::Block(1),
+                    start: -2.0,
+                    end: 35.0
+                },
+                BlockingTime {
+                    resource: Resource::Switch(1),
+                    start: 8.0,
+                    end: 35.0
+                },
+                BlockingTime {
+                    resource: Resource::Block(2),
+                    start: 28.0,
+                    end: 40.0
+                },
+            ]
+        );
+        assert!(analyser
+            .stairway(&service_with_stops(2, &[(2, 0.0, 0.0), (3, 10.0, 10.0)]))
+            .is_none());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        assert!(analyser.conflicts(&create_timetable()).is_empty());
+
+        let options = HeadwayOptions {
+            min_headway: 5.0,
+            ..Default::default()
+        };
+        let analyser = HeadwayAnalyser::new(&network, BlockLayout::per_edge(&network), options);
+        let conflicts = analyser.conflicts(&create_timetable());
+        assert_eq!(
+            conflicts,
+            vec![HeadwayConflict {
+                resource: Resource::Block(1),
+                service_id: 1,
+                other_service_id: 2,
+                start: 10.0,
+                end: 15.0
+            }]
+        );
+        assert_eq!(
+            conflicts[0].to_string(),
+            "service 2 blocks block 1 from 10 s while service 1 holds it until 15 s"
+        );
+    }
+
+    #[test]
+    fn test_double_track() {
+        let network = create_double_track();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let mut timetable = Timetable::new();
+        for (id, from, to, edge_id) in [(1, 0, 1, 1), (2, 1, 0, 2)] {
+            let mut run = service_with_stops(id, &[(from, 0.0, 0.0), (to, 100.0, 100.0)]);
+            run.paths = vec![vec![from, to]];
+            run.edges = vec![vec![edge_id]];
+            timetable.add_service(run);
+        }
+
+        let stairways = analyser.stairways(&timetable);
+
+        assert_eq!(stairways[0].blocking_times[0].resource, Resource::Block(1));
+        assert_eq!(stairways[1].blocking_times[0].resource, Resource::Block(2));
+        assert!(analyser.conflicts(&timetable).is_empty());
+    }
+
+    #[test]
+    fn test_separation() {
+        let network = create_network();
+        let analyser = HeadwayAnalyser::new(
+            &network,
+            BlockLayout::per_edge(&network),
+            HeadwayOptions::default(),
+        );
+        let stairways = analyser.stairways(&create_timetable());
+
+        assert_eq!(stairways.len(), 2);
+        assert_eq!(stairways[0].separation(&stairways[1]), Some(0.0));
+        assert_eq!(stairways[1].separation(&stairways[0]), Some(20.0));
+
+        let other = Stairway {
+            service_id: 3,
+            blocking_times: vec![],
+        };
+        assert_eq!(stairways[0].separation(&other), None);
+    }
+}
@@ -0,0 +1,533 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use geo::CoordNum;
+use petgraph::{
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    Direction,
+};
+
+use crate::{
+    core::{IdType, NodeId},
+    graphs::{TopologyGraph, TransitNetwork},
+};
+
+/// Type alias for the identifier of a route set in an `Interlocking`.
+pub type RouteId = IdType;
+
+/// Error raised when a switch cannot be set or a route cannot be set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterlockingError {
+    /// The node has no diverging movements.
+    NotASwitch(NodeId),
+    /// The node is not one of the branches of the switch.
+    InvalidBranch {
+        /// The ID of the switch node.
+        node_id: NodeId,
+        /// The ID of the requested branch node.
+        branch: NodeId,
+    },
+    /// The switch is locked by another route.
+    Locked {
+        /// The ID of the switch node.
+        node_id: NodeId,
+        /// The ID of the route holding the lock.
+        route_id: RouteId,
+    },
+    /// The path needs the switch in two different positions.
+    ConflictingPositions {
+        /// The ID of the switch node.
+        node_id: NodeId,
+        /// The branch the switch is needed in first, or already locked in by the same route.
+        first: NodeId,
+        /// The branch the switch is needed in later.
+        second: NodeId,
+    },
+    /// The path has fewer than two nodes or two consecutive nodes are not connected by an edge.
+    InvalidPath,
+    /// The topology does not allow passing a node from one neighbor to another.
+    NoMovement {
+        /// The ID of the node the movement comes from.
+        from: NodeId,
+        /// The ID of the passed node.
+        via: NodeId,
+        /// The ID of the node the movement leads to.
+        to: NodeId,
+    },
+}
+
+impl fmt::Display for InterlockingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterlockingError::NotASwitch(node_id) => write!(f, "node {} is not a switch", node_id),
+            InterlockingError::InvalidBranch { node_id, branch } => {
+                write!(f, "node {} is not a branch of switch {}", branch, node_id)
+            }
+            InterlockingError::Locked { node_id, route_id } => {
+                write!(f, "switch {} is locked by route {}", node_id, route_id)
+            }
+            InterlockingError::ConflictingPositions {
+                node_id,
+                first,
+                second,
+            } => write!(
+                f,
+                "switch {} is needed in the positions {} and {}",
+                node_id, first, second
+            ),
+            InterlockingError::InvalidPath => write!(f, "path is not connected"),
+            InterlockingError::NoMovement { from, via, to } => {
+                write!(f, "no movement from {} via {} to {}", from, via, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterlockingError {}
+
+/// The state of the switches of a network and the routes locking them.
+///
+/// The `TopologyGraph` describes all movements a switch allows. An `Interlocking` stores which branch each
+/// switch is currently set to, so that only movements to or from that branch are possible.
+/// Setting a route sets and locks all switches along its path until the route is released.
+/// Switches which have never been set allow no movement to or from any of their branches.
+///
+/// # Example
+///
+/// ```
+/// use geo::{coord, Coord, LineString};
+/// use transit_grid::prelude::*;
+/// use transit_grid::simulation::Interlocking;
+///
+/// let mut network: TransitNetwork<Coord, f64> = TransitNetwork::new();
+/// for (id, x, y) in [(0, 0.0, 0.0), (1, 1.0, 0.0), (2, 2.0, 0.0), (3, 2.0, 1.0)] {
+///     network.add_node(TransitNode { id, location: coord! { x: x, y: y } });
+/// }
+/// let edge = |id, source: u64, target: u64| TransitEdge {
+///     id,
+///     source,
+///     target,
+///     length: 1.0,
+///     path: LineString(vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }]),
+/// };
+/// network.add_edge(edge(1, 0, 1));
+/// network.add_edge_with_accessibility(edge(2, 1, 2), Accessability::ReachableNodes(vec![0]));
+/// network.add_edge_with_accessibility(edge(3, 1, 3), Accessability::ReachableNodes(vec![0]));
+///
+/// let mut interlocking = Interlocking::new();
+/// interlocking.set_route(&network, 1, &[0, 1, 3]).unwrap();
+/// assert_eq!(interlocking.position(1), Some(3));
+/// assert!(interlocking.set_switch(&network, 1, 2).is_err());
+/// assert_eq!(interlocking.find_route(&network, 0, 2), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Interlocking {
+    positions: BTreeMap<NodeId, NodeId>,
+    locks: BTreeMap<NodeId, RouteId>,
+}
+
+impl Interlocking {
+    /// Creates an interlocking with all switches unset and unlocked.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the branch the switch at the given node is set to.
+    pub fn position(&self, node_id: NodeId) -> Option<NodeId> {
+        self.positions.get(&node_id).copied()
+    }
+
+    /// Returns the ID of the route locking the switch at the given node.
+    pub fn lock(&self, node_id: NodeId) -> Option<RouteId> {
+        self.locks.get(&node_id).copied()
+    }
+
+    /// Returns the branches of the switch at a node, sorted by ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network containing the node.
+    /// * `node_id` - The ID of the node.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<NodeId>` - The neighbors the switch can be set to, empty if the node has no diverging movements.
+    pub fn branches<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        node_id: NodeId,
+    ) -> Vec<NodeId> {
+        let topology = &network.topology_graph;
+        let Some((first, second)) = topology.id_to_index(node_id) else {
+            return vec![];
+        };
+        let mut branches = Vec::new();
+        for toponode in [*first, *second] {
+            for direction in [Direction::Outgoing, Direction::Incoming] {
+                let neighbors = neighbor_ids(topology, toponode, direction);
+                if neighbors.len() > 1 {
+                    branches.extend(neighbors);
+                }
+            }
+        }
+        branches.sort_unstable();
+        branches.dedup();
+        branches
+    }
+
+    /// Returns the IDs of all nodes with diverging movements, sorted by ID.
+    pub fn switches<R: Copy, T: CoordNum>(network: &TransitNetwork<R, T>) -> Vec<NodeId> {
+        let mut switches: Vec<NodeId> = network
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .filter(|node_id| !Self::branches(network, *node_id).is_empty())
+            .collect();
+        switches.sort_unstable();
+        switches
+    }
+
+    /// Sets an unlocked switch to one of its branches.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network containing the switch.
+    /// * `node_id` - The ID of the switch node.
+    /// * `branch` - The ID of the branch node.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), InterlockingError>` - An error if the node is not a switch, the branch is not one of its
+    ///   branches or the switch is locked by a route.
+    pub fn set_switch<R: Copy, T: CoordNum>(
+        &mut self,
+        network: &TransitNetwork<R, T>,
+        node_id: NodeId,
+        branch: NodeId,
+    ) -> Result<(), InterlockingError> {
+        let branches = Self::branches(network, node_id);
+        if branches.is_empty() {
+            return Err(InterlockingError::NotASwitch(node_id));
+        }
+        if !branches.contains(&branch) {
+            return Err(InterlockingError::InvalidBranch { node_id, branch });
+        }
+        if let Some(route_id) = self.lock(node_id) {
+            return Err(InterlockingError::Locked { node_id, route_id });
+        }
+        self.positions.insert(node_id, branch);
+        Ok(())
+    }
+
+    /// Sets and locks all switches along a path for a route.
+    ///
+    /// Either all switches are set or, if an error is returned, the state remains unchanged.
+    /// Switches already locked by the same route may be passed again in the same position.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the path runs on.
+    /// * `route_id` - The ID of the route locking the switches.
+    /// * `path` - The IDs of the nodes along the path, as returned by the shortest path algorithms.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<NodeId>, InterlockingError>` - The IDs of the locked switches in path order, or an error if
+    ///   the path is not traversable, one of its switches is locked by another route or it needs a switch in
+    ///   two positions.
+    pub fn set_route<R: Copy, T: CoordNum>(
+        &mut self,
+        network: &TransitNetwork<R, T>,
+        route_id: RouteId,
+        path: &[NodeId],
+    ) -> Result<Vec<NodeId>, InterlockingError> {
+        let connected = path.len() >= 2
+            && path.windows(2).all(|pair| {
+                network
+                    .physical_graph
+                    .get_transit_edge(pair[0], pair[1])
+                    .is_some()
+            });
+        if !connected {
+            return Err(InterlockingError::InvalidPath);
+        }
+
+        let mut required: Vec<(NodeId, NodeId)> = Vec::new();
+        for triple in path.windows(3) {
+            let (from, via, to) = (triple[0], triple[1], triple[2]);
+            let toponode = movement(&network.topology_graph, from, via, to)
+                .ok_or(InterlockingError::NoMovement { from, via, to })?;
+            let Some(branch) = movement_branch(&network.topology_graph, toponode, from, to) else {
+                continue;
+            };
+            if let Some(locked) = self.lock(via).filter(|locked| *locked != route_id) {
+                return Err(InterlockingError::Locked {
+                    node_id: via,
+                    route_id: locked,
+                });
+            }
+            let previous = required.iter().find(|(node_id, _)| *node_id == via);
+            let fixed = previous
+                .map(|(_, branch)| *branch)
+                .or_else(|| self.lock(via).and(self.position(via)));
+            if let Some(first) = fixed.filter(|fixed| *fixed != branch) {
+                return Err(InterlockingError::ConflictingPositions {
+                    node_id: via,
+                    first,
+                    second: branch,
+                });
+            }
+            if previous.is_none() {
+                required.push((via, branch));
+            }
+        }
+
+        for (node_id, branch) in &required {
+            self.positions.insert(*node_id, *branch);
+            self.locks.insert(*node_id, route_id);
+        }
+        Ok(required.into_iter().map(|(node_id, _)| node_id).collect())
+    }
+
+    /// Releases all switches locked by a route. The switches keep their positions.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<NodeId>` - The IDs of the released switches, sorted by ID.
+    pub fn release_route(&mut self, route_id: RouteId) -> Vec<NodeId> {
+        let released: Vec<NodeId> = self
+            .locks
+            .iter()
+            .filter(|(_, locked)| **locked == route_id)
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        for node_id in &released {
+            self.locks.remove(node_id);
+        }
+        released
+    }
+
+    /// Returns `true` if the current state allows passing a node from one neighbor to another.
+    pub fn permits<R: Copy, T: CoordNum>(
+        &self,
+        network: &TransitNetwork<R, T>,
+        from: NodeId,
+        via: NodeId,
+        to: NodeId,
+    ) -> bool {
+        let topology = &network.topology_graph;
+        movement(topology, from, via, to).is_some_and(|toponode| {
+            movement_branch(topology, toponode, from, to)
+                .is_none_or(|branch| self.position(via) == Some(branch))
+        })
+    }
+
+    /// Returns the topology graph of the network with only the movements the current state allows.
+    ///
+    /// The view reflects the state at the time it is taken; it has to be taken again after switches change.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network whose topology is restricted.
+    ///
+    /// # Returns
+    ///
+    /// * `TopologyGraph` - A copy of the topology graph without the `TopoEdge`s leading to or coming from
+    ///   branches a switch is not set to.
+    pub fn topology<R: Copy, T: CoordNum>(&self, network: &TransitNetwork<R, T>) -> TopologyGraph {
+        let original = &network.topology_graph;
+        let node_id = |index: NodeIndex| original.graph[index].node_id;
+        let set = |switch: NodeIndex, branch: NodeIndex| {
+            self.position(node_id(switch)) == Some(node_id(branch))
+        };
+        let blocked: HashSet<_> = original
+            .graph
+            .edge_references()
+            .filter(|edge| {
+                let (source, target) = (edge.source(), edge.target());
+                let diverging = neighbor_ids(original, source, Direction::Outgoing).len() > 1;
+                let converging = neighbor_ids(original, target, Direction::Incoming).len() > 1;
+                (diverging && !set(source, target)) || (converging && !set(target, source))
+            })
+            .map(|edge| edge.id())
+            .collect();
+
+        let mut topology = original.clone();
+        topology
+            .graph
+            .retain_edges(|_, edge| !blocked.contains(&edge));
+        topology
+    }
+
+    /// Finds the shortest path by edge length between two nodes using only the movements the current state allows.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network to search.
+    /// * `from` - The ID of the starting node.
+    /// * `to` - The ID of the destination node.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<NodeId>>` - The nodes of the path, or `None` if the destination cannot be reached.
+    pub fn find_route<R: Copy, T: CoordNum>(
+        &self,
+        network: &TransitNetwork<R, T>,
+        from: NodeId,
+        to: NodeId,
+    ) -> Option<Vec<NodeId>> {
+        network
+            .path_through(&self.topology(network), from, to, |edge| {
+                edge.length.to_f64().unwrap_or(f64::INFINITY)
+            })
+            .map(|(path, _)| path)
+    }
+}
+
+/// Returns the IDs of the nodes connected to a toponode in the given direction, sorted and without duplicates.
+fn neighbor_ids(
+    topology: &TopologyGraph,
+    toponode: NodeIndex,
+    direction: Direction,
+) -> Vec<NodeId> {
+    let mut neighbors: Vec<NodeId> = topology
+        .graph
+        .edges_directed(toponode, direction)
+        .map(|edge| {
+            let other = match direction {
+                Direction::Outgoing => edge.target(),
+                Direction::Incoming => edge.source(),
+            };
+            topology.graph[other].node_id
+        })
+        .collect();
+    neighbors.sort_unstable();
+    neighbors.dedup();
+    neighbors
+}
+
+/// Returns the toponode of `via` a movement from `from` to `to` arrives at and departs from.
+fn movement(topology: &TopologyGraph, from: NodeId, via: NodeId, to: NodeId) -> Option<NodeIndex> {
+    let (first, second) = topology.id_to_index(via)?;
+    [*first, *second].into_iter().find(|toponode| {
+        neighbor_ids(topology, *toponode, Direction::Incoming).contains(&from)
+            && neighbor_ids(topology, *toponode, Direction::Outgoing).contains(&to)
+    })
+}
+
+/// Returns the branch a movement through a toponode depends on, `None` if it does not pass a switch.
+fn movement_branch(
+    topology: &TopologyGraph,
+    toponode: NodeIndex,
+    from: NodeId,
+    to: NodeId,
+) -> Option<NodeId> {
+    let count = |direction| neighbor_ids(topology, toponode, direction).len();
+    if count(Direction::Outgoing) > 1 {
+        Some(to)
+    } else if count(Direction::Incoming) > 1 {
+        Some(from)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::fixtures::create_network;
+
+    #[test]
+    fn test_switches() {
+        let network = create_network();
+
+        assert_eq!(Interlocking::switches(&network), vec![1]);
+        assert_eq!(Interlocking::branches(&network, 1), vec![2, 3]);
+        assert!(Interlocking::branches(&network, 0).is_empty());
+    }
+
+    #[test]
+    fn test_set_switch() {
+        let network = create_network();
+        let mut interlocking = Interlocking::new();
+
+        assert_eq!(interlocking.find_route(&network, 0, 2), None);
+        assert!(!interlocking.permits(&network, 0, 1, 2));
+
+        interlocking.set_switch(&network, 1, 2).unwrap();
+
+        assert_eq!(interlocking.position(1), Some(2));
+        assert!(interlocking.permits(&network, 0, 1, 2));
+        assert!(interlocking.permits(&network, 2, 1, 0));
+        assert!(!interlocking.permits(&network, 0, 1, 3));
+        assert!(!interlocking.permits(&network, 2, 1, 3));
+        assert_eq!(interlocking.find_route(&network, 0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(interlocking.find_route(&network, 2, 0), Some(vec![2, 1, 0]));
+        assert_eq!(interlocking.find_route(&network, 0, 3), None);
+        assert_eq!(interlocking.find_route(&network, 3, 0), None);
+        assert_eq!(interlocking.topology(&network).graph.edge_count(), 4);
+
+        assert_eq!(
+            interlocking.set_switch(&network, 0, 1),
+            Err(InterlockingError::NotASwitch(0))
+        );
+        assert_eq!(
+            interlocking.set_switch(&network, 1, 0),
+            Err(InterlockingError::InvalidBranch {
+                node_id: 1,
+                branch: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_route() {
+        let network = create_network();
+        let mut interlocking = Interlocking::new();
+
+        assert_eq!(interlocking.set_route(&network, 7, &[0, 1, 3]), Ok(vec![1]));
+
+        assert_eq!(interlocking.position(1), Some(3));
+        assert_eq!(interlocking.lock(1), Some(7));
+        assert_eq!(interlocking.find_route(&network, 3, 0), Some(vec![3, 1, 0]));
+        let locked = Err(InterlockingError::Locked {
+            node_id: 1,
+            route_id: 7,
+        });
+        assert_eq!(interlocking.set_switch(&network, 1, 2), locked);
+        assert_eq!(
+            interlocking.set_route(&network, 8, &[2, 1, 0]),
+            locked.map(|_| vec![])
+        );
+        assert_eq!(interlocking.set_route(&network, 7, &[3, 1, 0]), Ok(vec![1]));
+        assert_eq!(
+            interlocking.set_route(&network, 7, &[0, 1, 2]),
+            Err(InterlockingError::ConflictingPositions {
+                node_id: 1,
+                first: 3,
+                second: 2
+            })
+        );
+        assert_eq!(
+            interlocking.set_route(&network, 9, &[2, 1, 3]),
+            Err(InterlockingError::NoMovement {
+                from: 2,
+                via: 1,
+                to: 3
+            })
+        );
+        assert_eq!(
+            interlocking.set_route(&network, 9, &[0, 2]),
+            Err(InterlockingError::InvalidPath)
+        );
+
+        assert_eq!(interlocking.release_route(7), vec![1]);
+        assert_eq!(interlocking.lock(1), None);
+        assert_eq!(interlocking.position(1), Some(3));
+        assert_eq!(interlocking.set_route(&network, 8, &[2, 1, 0]), Ok(vec![1]));
+        assert_eq!(interlocking.position(1), Some(2));
+    }
+}
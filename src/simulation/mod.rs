@@ -0,0 +1,46 @@
+//! This module provides a discrete-event simulation of vehicles moving on a `TransitNetwork`.
+//!
+//! * `Route` - The nodes and edges a vehicle passes, usually found with the shortest path algorithms.
+//! * `Vehicle` - A vehicle with its route, departure time, speed and stops.
+//! * `TrainDynamics` - The acceleration, braking and speed of a train, giving its `SpeedProfile` along a route.
+//! * `Simulation` - The engine processing the events of all vehicles in the order of their time.
+//! * `BlockLayout` - The block sections of the network, whose occupancy the engine tracks to detect `Conflict`s.
+//! * `HeadwayAnalyser` - The blocking-time stairways of the runs of a timetable and the `HeadwayConflict`s between them.
+//! * `CapacityReport` - The occupancy of a corridor after compressing the stairways following UIC Code 406.
+//! * `CirculationPlan` - The rotations of the vehicles operating a timetable, with the empty movements between runs.
+//! * `DelayPropagation` - The knock-on delays of runs caused by primary delays, shared resources and `Dependency`s.
+//! * `Interlocking` - The positions of the switches and the routes locking them, restricting the topology to the set movements.
+//! * `Event` - The entering and leaving of edges and the arrival and departure at stops, emitted by the engine.
+//!
+//! Positions of vehicles are tracked as `NetworkPosition`s, an edge and an offset along its path.
+//! Random variations of speeds and dwell times are drawn from a generator seeded with `SimulationOptions::seed`,
+//! so that a simulation with the same vehicles and seed always emits the same events.
+
+mod blocks;
+mod capacity;
+mod circulation;
+mod delay;
+mod dynamics;
+mod engine;
+mod event;
+#[cfg(test)]
+pub(crate) mod fixtures;
+mod headway;
+mod interlocking;
+mod rng;
+mod route;
+
+pub use blocks::{Block, BlockId, BlockLayout, Conflict, Resource};
+pub use capacity::{CapacityReport, EdgeCapacity};
+pub use circulation::{CirculationOptions, CirculationPlan, Deadhead, Rotation, Turn};
+pub use delay::{
+    DelayOptions, DelayPropagation, DelayReport, Dependency, DispatchRule, PrimaryDelay, RunDelays,
+    StationDelay, StopDelay,
+};
+pub use dynamics::{ProfilePoint, SpeedProfile, TrainDynamics};
+pub use engine::{Simulation, SimulationOptions, Vehicle, VehicleId};
+pub use event::{Event, EventKind};
+pub use headway::{BlockingTime, HeadwayAnalyser, HeadwayConflict, HeadwayOptions, Stairway};
+pub use interlocking::{Interlocking, InterlockingError, RouteId};
+pub use rng::Rng;
+pub use route::Route;
@@ -0,0 +1,72 @@
+/// A small pseudo-random number generator for reproducible simulations.
+///
+/// The generator implements SplitMix64, which is fast, has no dependencies and produces the same sequence
+/// on every platform for the same seed.
+///
+/// # Examples
+///
+/// ```
+/// use transit_grid::simulation::Rng;
+///
+/// let mut rng = Rng::new(42);
+/// let value = rng.next_f64();
+/// assert!((0.0..1.0).contains(&value));
+/// assert_eq!(Rng::new(42).next_f64(), value);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Returns the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in the range `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random factor in the range `[1 - variation, 1 + variation)`.
+    pub fn variation(&mut self, variation: f64) -> f64 {
+        1.0 + variation * (2.0 * self.next_f64() - 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut first = Rng::new(7);
+        let mut second = Rng::new(7);
+        let values: Vec<u64> = (0..5).map(|_| first.next_u64()).collect();
+        assert_eq!(
+            values,
+            (0..5).map(|_| second.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(Rng::new(8).next_u64(), values[0]);
+    }
+
+    #[test]
+    fn test_rng_variation() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let factor = rng.variation(0.1);
+            assert!((0.9..1.1).contains(&factor));
+        }
+        assert_eq!(rng.variation(0.0), 1.0);
+    }
+}
@@ -0,0 +1,85 @@
+use geo::CoordNum;
+
+use crate::{
+    core::{EdgeId, NodeId},
+    graphs::TransitNetwork,
+};
+
+/// Structure representing the way of a vehicle through a `TransitNetwork`.
+///
+/// `nodes` holds the visited nodes in order and `edges` the edges between them,
+/// so that `edges[i]` connects `nodes[i]` and `nodes[i + 1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// The IDs of the visited nodes.
+    pub nodes: Vec<NodeId>,
+    /// The IDs of the traversed edges.
+    pub edges: Vec<EdgeId>,
+}
+
+impl Route {
+    /// Creates a route visiting the given nodes in order.
+    ///
+    /// Between two nodes connected by parallel edges, any of them is taken. Build the `Route` from the
+    /// `edges` of a bound `Service` or use `shortest` where the edges matter.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the route runs on.
+    /// * `nodes` - The IDs of the nodes, as returned by the shortest path algorithms.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Route>` - The route, or `None` if there are fewer than two nodes
+    ///   or two consecutive nodes are not connected by an edge.
+    pub fn from_nodes<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        nodes: Vec<NodeId>,
+    ) -> Option<Route> {
+        if nodes.len() < 2 {
+            return None;
+        }
+        let edges = nodes
+            .windows(2)
+            .map(|pair| {
+                network
+                    .physical_graph
+                    .get_transit_edge(pair[0], pair[1])
+                    .map(|edge| edge.id)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Route { nodes, edges })
+    }
+
+    /// Finds the shortest route by edge length between two nodes, following the topology graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network the route runs on.
+    /// * `from` - The ID of the first node.
+    /// * `to` - The ID of the last node.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Route>` - The route, or `None` if `to` cannot be reached from `from`.
+    pub fn shortest<R: Copy, T: CoordNum>(
+        network: &TransitNetwork<R, T>,
+        from: NodeId,
+        to: NodeId,
+    ) -> Option<Route> {
+        let (nodes, edges) = network.path_between(from, to, |edge| {
+            edge.length.to_f64().unwrap_or(f64::INFINITY)
+        })?;
+        (nodes.len() > 1).then_some(Route { nodes, edges })
+    }
+
+    /// Returns the ID of the first node.
+    pub fn origin(&self) -> NodeId {
+        self.nodes[0]
+    }
+
+    /// Returns the ID of the last node.
+    pub fn destination(&self) -> NodeId {
+        self.nodes[self.nodes.len() - 1]
+    }
+}